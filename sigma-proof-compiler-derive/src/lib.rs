@@ -1,14 +1,135 @@
 use proc_macro::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{Attribute, Data, DeriveInput, Fields};
 
 mod sym_instance;
 mod sym_witness;
 
-#[proc_macro_derive(SymInstance)]
+#[proc_macro_derive(SymInstance, attributes(sym))]
 pub fn derive_sym_instance(input: TokenStream) -> TokenStream {
     sym_instance::derive_sym_instance_impl(input)
 }
 
-#[proc_macro_derive(SymWitness)]
+#[proc_macro_derive(SymWitness, attributes(sym))]
 pub fn derive_sym_witness(input: TokenStream) -> TokenStream {
     sym_witness::derive_sym_witness_impl(input)
 }
+
+/// The options given with `#[sym(...)]` on the deriving type.
+struct SymOptions {
+    /// Derive `Clone` as well.
+    clone: bool,
+}
+
+fn parse_sym_options(attrs: &[Attribute]) -> syn::Result<SymOptions> {
+    let mut options = SymOptions { clone: false };
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("sym")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("clone") {
+                options.clone = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported sym option, expected `clone`"))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
+/// The options given with `#[sym(...)]` on a field.
+#[derive(Default)]
+struct FieldOptions {
+    /// Leave the field out of the transcript, for instance fields.
+    skip_absorb: bool,
+    /// The name of the field in specs, instead of its identifier.
+    name: Option<String>,
+    /// The field is a nested instance, for instance fields.
+    nested: bool,
+}
+
+fn parse_field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("sym"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip_absorb") {
+                options.skip_absorb = true;
+                Ok(())
+            } else if meta.path.is_ident("name") {
+                options.name = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("nested") {
+                options.nested = true;
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "unsupported sym field option, expected `skip_absorb`, `name` or `nested`",
+                ))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
+/// The name of the field at `index`: its `#[sym(name = "...")]`, else its
+/// identifier, else `field_{index}` for tuple structs.
+fn field_name(field: &syn::Field, options: &FieldOptions, index: usize) -> String {
+    match (&options.name, &field.ident) {
+        (Some(name), _) => name.clone(),
+        (None, Some(ident)) => ident.to_string(),
+        (None, None) => format!("field_{}", index),
+    }
+}
+
+/// Protocols clone their witnesses and instances, so the deriving type must be
+/// `Clone`. Implements it with `#[sym(clone)]`, and otherwise asserts it is
+/// implemented, so that a missing `Clone` is reported on the type itself.
+fn clone_impl(input: &DeriveInput, options: &SymOptions) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    if !options.clone {
+        return quote_spanned! {name.span()=>
+            const _: () = {
+                fn assert_clone<T: crate::absorb::RequiresClone>() {}
+                #[allow(dead_code)]
+                fn assert #impl_generics () #where_clause {
+                    assert_clone::<#name #ty_generics>();
+                }
+            };
+        };
+    }
+
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let fields = fields.named.iter().map(|field| {
+                    let field_name = field.ident.as_ref().unwrap();
+                    quote! { #field_name: ::core::clone::Clone::clone(&self.#field_name) }
+                });
+                quote! { Self { #(#fields),* } }
+            }
+            Fields::Unnamed(fields) => {
+                let fields = (0..fields.unnamed.len()).map(|i| {
+                    let index = syn::Index::from(i);
+                    quote! { ::core::clone::Clone::clone(&self.#index) }
+                });
+                quote! { Self(#(#fields),*) }
+            }
+            Fields::Unit => quote! { Self },
+        },
+        // rejected by the derives
+        Data::Enum(_) | Data::Union(_) => return quote! {},
+    };
+
+    quote! {
+        impl #impl_generics ::core::clone::Clone for #name #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                #body
+            }
+        }
+    }
+}
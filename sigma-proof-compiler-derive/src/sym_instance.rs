@@ -2,16 +2,6 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, Data, DeriveInput, Fields, Type};
 
-fn is_sym_instance_type(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            let ident = &segment.ident;
-            return ident == "SymScalar" || ident == "SymPoint" || ident == "SymInstance";
-        }
-    }
-    false
-}
-
 fn is_sym_type(ty: &Type) -> Option<&str> {
     if let Type::Path(type_path) = ty {
         if let Some(segment) = type_path.path.segments.last() {
@@ -30,136 +20,98 @@ pub fn derive_sym_instance_impl(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let options = match crate::parse_sym_options(&input.attrs) {
+        Ok(options) => options,
+        Err(err) => return err.to_compile_error().into(),
+    };
 
     match &input.data {
         Data::Struct(data) => {
-            match &data.fields {
-                Fields::Named(fields) => {
-                    for field in &fields.named {
-                        if !is_sym_instance_type(&field.ty) {
-                            let field_name = field.ident.as_ref().unwrap();
-                            let ty = &field.ty;
-                            panic!(
-                                "Field '{}' has type '{}' which is not SymScalar, SymPoint, or SymInstance",
-                                field_name,
-                                quote!(#ty)
-                            );
-                        }
-                    }
-                }
-                Fields::Unnamed(fields) => {
-                    for (i, field) in fields.unnamed.iter().enumerate() {
-                        if !is_sym_instance_type(&field.ty) {
-                            let ty = &field.ty;
-                            panic!(
-                                "Field {} has type '{}' which is not SymScalar, SymPoint, or SymInstance",
-                                i,
-                                quote!(#ty)
-                            );
-                        }
-                    }
-                }
-                Fields::Unit => {}
-            }
-
-            // Generate num_scalars() method body
-            let num_scalars_body = match &data.fields {
-                Fields::Named(fields) => {
-                    let field_counts = fields.named.iter().map(|field| {
-                        let field_type = &field.ty;
-                        match is_sym_type(field_type) {
-                            Some("scalar") => quote! { 1 },
-                            Some("point") => quote! { 0 },
-                            _ => quote! { #field_type::num_scalars() },
-                        }
-                    });
-
-                    quote! {
-                        0 #(+ #field_counts)*
-                    }
-                }
-                Fields::Unnamed(fields) => {
-                    let field_counts = fields.unnamed.iter().map(|field| {
-                        let field_type = &field.ty;
-                        match is_sym_type(field_type) {
-                            Some("scalar") => quote! { 1 },
-                            Some("point") => quote! { 0 },
-                            _ => quote! { #field_type::num_scalars() },
-                        }
-                    });
-
-                    quote! {
-                        0 #(+ #field_counts)*
-                    }
-                }
-                Fields::Unit => {
-                    quote! { 0 }
-                }
+            let field_options = match data
+                .fields
+                .iter()
+                .map(crate::parse_field_options)
+                .collect::<syn::Result<Vec<_>>>()
+            {
+                Ok(field_options) => field_options,
+                Err(err) => return err.to_compile_error().into(),
             };
 
-            // Generate num_points() method body
-            let num_points_body = match &data.fields {
-                Fields::Named(fields) => {
-                    let field_counts = fields.named.iter().map(|field| {
-                        let field_type = &field.ty;
-                        match is_sym_type(field_type) {
-                            Some("scalar") => quote! { 0 },
-                            Some("point") => quote! { 1 },
-                            _ => quote! { #field_type::num_points() },
-                        }
-                    });
-
-                    quote! {
-                        0 #(+ #field_counts)*
+            // fields are SymScalars, SymPoints, or nested instances marked
+            // `#[sym(nested)]`, whose SymInstance impl the generated code uses
+            for (i, (field, options)) in data.fields.iter().zip(&field_options).enumerate() {
+                let ty = &field.ty;
+                let msg = match (is_sym_type(ty), options.nested) {
+                    (Some(_), false) | (None, true) => continue,
+                    (Some(_), true) => {
+                        "`nested` only applies to fields that are not SymScalar or SymPoint"
+                            .to_string()
                     }
-                }
-                Fields::Unnamed(fields) => {
-                    let field_counts = fields.unnamed.iter().map(|field| {
-                        let field_type = &field.ty;
-                        match is_sym_type(field_type) {
-                            Some("scalar") => quote! { 0 },
-                            Some("point") => quote! { 1 },
-                            _ => quote! { #field_type::num_points() },
-                        }
-                    });
+                    (None, false) => {
+                        let field = match &field.ident {
+                            Some(ident) => format!("Field '{}'", ident),
+                            None => format!("Field {}", i),
+                        };
+                        format!(
+                            "{} has type '{}' which is not SymScalar or SymPoint, mark nested SymInstance fields with #[sym(nested)]",
+                            field,
+                            quote!(#ty)
+                        )
+                    }
+                };
+                return syn::Error::new_spanned(field, msg)
+                    .to_compile_error()
+                    .into();
+            }
 
-                    quote! {
-                        0 #(+ #field_counts)*
+            // Generate the NUM_SCALARS and NUM_POINTS constants, whose
+            // overflow fails to compile
+            let field_counts = |nested: &str, scalar: usize, point: usize| {
+                let nested = quote::format_ident!("{}", nested);
+                let counts = data.fields.iter().map(|field| {
+                    let field_type = &field.ty;
+                    match is_sym_type(field_type) {
+                        Some("scalar") => quote! { #scalar },
+                        Some("point") => quote! { #point },
+                        _ => quote! { <#field_type as crate::absorb::FixedLenInstance>::#nested },
                     }
-                }
-                Fields::Unit => {
-                    quote! { 0 }
-                }
+                });
+                quote! { 0usize #(+ #counts)* }
             };
+            let num_scalars_const = field_counts("NUM_SCALARS", 1, 0);
+            let num_points_const = field_counts("NUM_POINTS", 0, 1);
 
-            // Generate get_field_names() method body
-            let get_field_names_body = match &data.fields {
-                Fields::Named(fields) => {
-                    let field_names: Vec<String> = fields.named.iter().map(|field| {
-                        let field_name = field.ident.as_ref().unwrap();
-                        field_name.to_string()
-                    }).collect();
-
-                    let name_literals = field_names.iter().map(|n| quote! { #n });
-                    quote! {
-                        vec![#(#name_literals),*]
-                    }
-                }
-                Fields::Unnamed(fields) => {
-                    // For unnamed fields, generate generic names
-                    let field_names: Vec<String> = (0..fields.unnamed.len())
-                        .map(|i| format!("field_{}", i))
-                        .collect();
-                    let name_literals = field_names.iter().map(|n| quote! { #n });
-                    quote! {
-                        vec![#(#name_literals),*]
-                    }
-                }
-                Fields::Unit => {
-                    quote! {
-                        vec![]
-                    }
-                }
+            // Generate get_field_names() method body: like `from_values`, the
+            // names of scalars come first, then those of points, and the names
+            // in nested instances are qualified with the field name
+            let field_names: Vec<(String, &Type)> = data
+                .fields
+                .iter()
+                .zip(&field_options)
+                .enumerate()
+                .map(|(i, (field, options))| (crate::field_name(field, options, i), &field.ty))
+                .collect();
+            let name_pushes = field_names.iter().map(|(name, ty)| match is_sym_type(ty) {
+                Some("scalar") => quote! {
+                    scalar_names.push(alloc::string::String::from(#name));
+                },
+                Some("point") => quote! {
+                    point_names.push(alloc::string::String::from(#name));
+                },
+                _ => quote! {
+                    let names = <#ty as SymInstance>::get_field_names();
+                    let (inner_scalars, inner_points) =
+                        names.split_at(<#ty as crate::absorb::FixedLenInstance>::NUM_SCALARS);
+                    scalar_names.extend(inner_scalars.iter().map(|n| alloc::format!("{}.{}", #name, n)));
+                    point_names.extend(inner_points.iter().map(|n| alloc::format!("{}.{}", #name, n)));
+                },
+            });
+            let get_field_names_body = quote! {
+                let mut scalar_names: alloc::vec::Vec<alloc::string::String> = alloc::vec::Vec::new();
+                let mut point_names: alloc::vec::Vec<alloc::string::String> = alloc::vec::Vec::new();
+                #(#name_pushes)*
+                scalar_names.extend(point_names);
+                scalar_names
             };
 
             // Generate from_values() method body
@@ -170,37 +122,42 @@ pub fn derive_sym_instance_impl(input: TokenStream) -> TokenStream {
                         let field_type = &field.ty;
                         match is_sym_type(field_type) {
                             Some("scalar") => quote! {
-                                #field_name: {
-                                    if scalar_cursor >= scalars.len() {
-                                        return Err(crate::errors::SigmaProofError::InsufficientScalars);
-                                    }
-                                    let val = SymInstance::from_values(&scalars[scalar_cursor..scalar_cursor+1], &[])?;
-                                    scalar_cursor += 1;
-                                    val
-                                }
+                                #field_name: SymInstance::from_values(
+                                        crate::absorb::take_values(
+                                            scalars,
+                                            &mut scalar_cursor,
+                                            1,
+                                            crate::errors::SigmaProofError::InsufficientScalars,
+                                        )?,
+                                        &[],
+                                    )?
                             },
                             Some("point") => quote! {
-                                #field_name: {
-                                    if point_cursor >= points.len() {
-                                        return Err(crate::errors::SigmaProofError::InsufficientPoints);
-                                    }
-                                    let val = SymInstance::from_values(&[], &points[point_cursor..point_cursor+1])?;
-                                    point_cursor += 1;
-                                    val
-                                }
+                                #field_name: SymInstance::from_values(
+                                        &[],
+                                        crate::absorb::take_values(
+                                            points,
+                                            &mut point_cursor,
+                                            1,
+                                            crate::errors::SigmaProofError::InsufficientPoints,
+                                        )?,
+                                    )?
                             },
                             _ => quote! {
-                                #field_name: {
-                                    let field_scalars = #field_type::num_scalars();
-                                    let field_points = #field_type::num_points();
-                                    let val = #field_type::from_values(
-                                        &scalars[scalar_cursor..scalar_cursor+field_scalars],
-                                        &points[point_cursor..point_cursor+field_points]
-                                    )?;
-                                    scalar_cursor += field_scalars;
-                                    point_cursor += field_points;
-                                    val
-                                }
+                                #field_name: <#field_type as SymInstance>::from_values(
+                                    crate::absorb::take_values(
+                                        scalars,
+                                        &mut scalar_cursor,
+                                        <#field_type as crate::absorb::FixedLenInstance>::NUM_SCALARS,
+                                        crate::errors::SigmaProofError::InsufficientScalars,
+                                    )?,
+                                    crate::absorb::take_values(
+                                        points,
+                                        &mut point_cursor,
+                                        <#field_type as crate::absorb::FixedLenInstance>::NUM_POINTS,
+                                        crate::errors::SigmaProofError::InsufficientPoints,
+                                    )?,
+                                )?
                             },
                         }
                     });
@@ -227,37 +184,42 @@ pub fn derive_sym_instance_impl(input: TokenStream) -> TokenStream {
                         let field_type = &field.ty;
                         match is_sym_type(field_type) {
                             Some("scalar") => quote! {
-                                {
-                                    if scalar_cursor >= scalars.len() {
-                                        return Err(crate::errors::SigmaProofError::InsufficientScalars);
-                                    }
-                                    let val = SymInstance::from_values(&scalars[scalar_cursor..scalar_cursor+1], &[])?;
-                                    scalar_cursor += 1;
-                                    val
-                                }
+                                SymInstance::from_values(
+                                        crate::absorb::take_values(
+                                            scalars,
+                                            &mut scalar_cursor,
+                                            1,
+                                            crate::errors::SigmaProofError::InsufficientScalars,
+                                        )?,
+                                        &[],
+                                    )?
                             },
                             Some("point") => quote! {
-                                {
-                                    if point_cursor >= points.len() {
-                                        return Err(crate::errors::SigmaProofError::InsufficientPoints);
-                                    }
-                                    let val = SymInstance::from_values(&[], &points[point_cursor..point_cursor+1])?;
-                                    point_cursor += 1;
-                                    val
-                                }
+                                SymInstance::from_values(
+                                        &[],
+                                        crate::absorb::take_values(
+                                            points,
+                                            &mut point_cursor,
+                                            1,
+                                            crate::errors::SigmaProofError::InsufficientPoints,
+                                        )?,
+                                    )?
                             },
                             _ => quote! {
-                                {
-                                    let field_scalars = #field_type::num_scalars();
-                                    let field_points = #field_type::num_points();
-                                    let val = #field_type::from_values(
-                                        &scalars[scalar_cursor..scalar_cursor+field_scalars],
-                                        &points[point_cursor..point_cursor+field_points]
-                                    )?;
-                                    scalar_cursor += field_scalars;
-                                    point_cursor += field_points;
-                                    val
-                                }
+                                <#field_type as SymInstance>::from_values(
+                                    crate::absorb::take_values(
+                                        scalars,
+                                        &mut scalar_cursor,
+                                        <#field_type as crate::absorb::FixedLenInstance>::NUM_SCALARS,
+                                        crate::errors::SigmaProofError::InsufficientScalars,
+                                    )?,
+                                    crate::absorb::take_values(
+                                        points,
+                                        &mut point_cursor,
+                                        <#field_type as crate::absorb::FixedLenInstance>::NUM_POINTS,
+                                        crate::errors::SigmaProofError::InsufficientPoints,
+                                    )?,
+                                )?
                             },
                         }
                     });
@@ -309,7 +271,7 @@ pub fn derive_sym_instance_impl(input: TokenStream) -> TokenStream {
                     });
 
                     quote! {
-                        let mut result = Vec::new();
+                        let mut result = alloc::vec::Vec::new();
                         #(#field_extractions)*
                         result
                     }
@@ -330,13 +292,13 @@ pub fn derive_sym_instance_impl(input: TokenStream) -> TokenStream {
                     });
 
                     quote! {
-                        let mut result = Vec::new();
+                        let mut result = alloc::vec::Vec::new();
                         #(#field_extractions)*
                         result
                     }
                 }
                 Fields::Unit => {
-                    quote! { Vec::new() }
+                    quote! { alloc::vec::Vec::new() }
                 }
             };
 
@@ -358,7 +320,7 @@ pub fn derive_sym_instance_impl(input: TokenStream) -> TokenStream {
                     });
 
                     quote! {
-                        let mut result = Vec::new();
+                        let mut result = alloc::vec::Vec::new();
                         #(#field_extractions)*
                         result
                     }
@@ -379,53 +341,183 @@ pub fn derive_sym_instance_impl(input: TokenStream) -> TokenStream {
                     });
 
                     quote! {
-                        let mut result = Vec::new();
+                        let mut result = alloc::vec::Vec::new();
                         #(#field_extractions)*
                         result
                     }
                 }
                 Fields::Unit => {
-                    quote! { Vec::new() }
+                    quote! { alloc::vec::Vec::new() }
+                }
+            };
+
+            // Generate absorbed_scalars() and absorbed_points() method bodies,
+            // which leave out the fields marked `#[sym(skip_absorb)]`
+            let absorbed_body = |kind: &str| {
+                let field_extractions = data.fields.iter().zip(&field_options).enumerate().map(
+                    |(i, (field, options))| {
+                        if options.skip_absorb {
+                            return quote! {};
+                        }
+                        let access = match &field.ident {
+                            Some(ident) => quote! { #ident },
+                            None => {
+                                let index = syn::Index::from(i);
+                                quote! { #index }
+                            }
+                        };
+                        match (is_sym_type(&field.ty), kind) {
+                            (Some(field_kind), _) if field_kind == kind => quote! {
+                                result.push(self.#access.clone());
+                            },
+                            (Some(_), _) => quote! {},
+                            (None, "scalar") => quote! {
+                                result.extend(SymInstance::absorbed_scalars(&self.#access));
+                            },
+                            (None, _) => quote! {
+                                result.extend(SymInstance::absorbed_points(&self.#access));
+                            },
+                        }
+                    },
+                );
+                quote! {
+                    #[allow(unused_mut)]
+                    let mut result = alloc::vec::Vec::new();
+                    #(#field_extractions)*
+                    result
+                }
+            };
+            let absorbed_scalars_body = absorbed_body("scalar");
+            let absorbed_points_body = absorbed_body("point");
+
+            // Generate a typed constructor: points and scalars can be given as
+            // concrete values, which are converted to constants
+            let constructor_arg_type = |field_type: &Type| match is_sym_type(field_type) {
+                Some("scalar") => quote! { impl Into<crate::equations::SymScalar> },
+                Some("point") => quote! { impl Into<crate::equations::SymPoint> },
+                _ => quote! { #field_type },
+            };
+            let (constructor_args, constructor_body) = match &data.fields {
+                Fields::Named(fields) => {
+                    let args = fields.named.iter().map(|field| {
+                        let field_name = field.ident.as_ref().unwrap();
+                        let arg_type = constructor_arg_type(&field.ty);
+                        quote! { #field_name: #arg_type }
+                    });
+                    let inits = fields.named.iter().map(|field| {
+                        let field_name = field.ident.as_ref().unwrap();
+                        quote! { #field_name: #field_name.into() }
+                    });
+                    (quote! { #(#args),* }, quote! { Self { #(#inits),* } })
+                }
+                Fields::Unnamed(fields) => {
+                    let arg_names: Vec<_> = (0..fields.unnamed.len())
+                        .map(|i| quote::format_ident!("field_{}", i))
+                        .collect();
+                    let args = fields.unnamed.iter().zip(&arg_names).map(|(field, arg)| {
+                        let arg_type = constructor_arg_type(&field.ty);
+                        quote! { #arg: #arg_type }
+                    });
+                    (
+                        quote! { #(#args),* },
+                        quote! { Self(#(#arg_names.into()),*) },
+                    )
                 }
+                Fields::Unit => (quote! {}, quote! { Self }),
             };
+            let vis = &input.vis;
+
+            let clone_impl = crate::clone_impl(&input, &options);
 
             let expanded = quote! {
+                #clone_impl
+
                 impl #impl_generics crate::absorb::sealed_instance::Sealed for #name #ty_generics #where_clause {}
 
                 impl #impl_generics SymInstance for #name #ty_generics #where_clause {
-                    fn num_scalars() -> usize {
-                        #num_scalars_body
+                    fn num_scalars() -> core::option::Option<usize> {
+                        core::option::Option::Some(<Self as crate::absorb::FixedLenInstance>::NUM_SCALARS)
                     }
 
-                    fn num_points() -> usize {
-                        #num_points_body
+                    fn num_points() -> core::option::Option<usize> {
+                        core::option::Option::Some(<Self as crate::absorb::FixedLenInstance>::NUM_POINTS)
                     }
 
                     fn from_values(scalars: &[curve25519_dalek::Scalar], points: &[curve25519_dalek::RistrettoPoint]) -> crate::errors::SigmaProofResult<Self> {
                         #from_values_body
                     }
 
-                    fn get_field_names() -> Vec<&'static str> {
+                    fn get_field_names() -> alloc::vec::Vec<alloc::string::String> {
                         #get_field_names_body
                     }
 
-                    fn scalars(&self) -> Vec<crate::equations::SymScalar> {
+                    fn scalars(&self) -> alloc::vec::Vec<crate::equations::SymScalar> {
                         #scalars_body
                     }
 
-                    fn points(&self) -> Vec<crate::equations::SymPoint> {
+                    fn points(&self) -> alloc::vec::Vec<crate::equations::SymPoint> {
                         #points_body
                     }
+
+                    fn absorbed_scalars(&self) -> alloc::vec::Vec<crate::equations::SymScalar> {
+                        #absorbed_scalars_body
+                    }
+
+                    fn absorbed_points(&self) -> alloc::vec::Vec<crate::equations::SymPoint> {
+                        #absorbed_points_body
+                    }
+                }
+
+                impl #impl_generics crate::absorb::FixedLenInstance for #name #ty_generics #where_clause {
+                    const NUM_SCALARS: usize = #num_scalars_const;
+                    const NUM_POINTS: usize = #num_points_const;
+                }
+
+                impl #impl_generics #name #ty_generics #where_clause {
+                    /// Builds the instance from its fields, where points and
+                    /// scalars can be given as concrete values.
+                    #[allow(dead_code, clippy::too_many_arguments)]
+                    #vis fn new(#constructor_args) -> Self {
+                        #constructor_body
+                    }
+
+                    /// Serializes the instance as its scalars followed by its
+                    /// compressed points, in field order.
+                    #[allow(dead_code)]
+                    #vis fn to_bytes(&self) -> crate::errors::SigmaProofResult<alloc::vec::Vec<u8>> {
+                        crate::proof::encode_instance(self)
+                    }
+
+                    /// Parses an instance serialized by `to_bytes`, rejecting
+                    /// non-canonical encodings.
+                    #[allow(dead_code)]
+                    #vis fn from_bytes(bytes: &[u8]) -> crate::errors::SigmaProofResult<Self> {
+                        crate::proof::decode_instance(bytes)
+                    }
+                }
+
+                impl #impl_generics TryFrom<&[u8]> for #name #ty_generics #where_clause {
+                    type Error = crate::errors::SigmaProofError;
+
+                    fn try_from(bytes: &[u8]) -> crate::errors::SigmaProofResult<Self> {
+                        Self::from_bytes(bytes)
+                    }
                 }
             };
 
             TokenStream::from(expanded)
         }
-        Data::Enum(_) => {
-            panic!("SymInstance derive macro does not support enums");
-        }
-        Data::Union(_) => {
-            panic!("SymInstance derive macro does not support unions");
-        }
+        Data::Enum(data) => syn::Error::new_spanned(
+            data.enum_token,
+            "SymInstance derive macro does not support enums",
+        )
+        .to_compile_error()
+        .into(),
+        Data::Union(data) => syn::Error::new_spanned(
+            data.union_token,
+            "SymInstance derive macro does not support unions",
+        )
+        .to_compile_error()
+        .into(),
     }
-}
\ No newline at end of file
+}
@@ -2,23 +2,54 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, Data, DeriveInput, Fields, Type};
 
+/// Fields are either `SymScalar`s or nested witnesses, which can be any named
+/// type implementing `SymWitness` (enforced by the generated code).
 fn is_sym_witness_type(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            let ident = &segment.ident;
-            return ident == "SymScalar" || ident == "SymWitness";
-        }
-    }
-    false
+    matches!(ty, Type::Path(type_path) if type_path.path.segments.last().is_some())
+}
+
+fn is_sym_scalar_type(ty: &Type) -> bool {
+    matches!(ty, Type::Path(type_path) if type_path.path.segments.last().is_some_and(|segment| segment.ident == "SymScalar"))
 }
 
 pub fn derive_sym_witness_impl(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let options = match crate::parse_sym_options(&input.attrs) {
+        Ok(options) => options,
+        Err(err) => return err.to_compile_error().into(),
+    };
 
     match &input.data {
         Data::Struct(data) => {
+            let field_options = match data
+                .fields
+                .iter()
+                .map(crate::parse_field_options)
+                .collect::<syn::Result<Vec<_>>>()
+            {
+                Ok(field_options) => field_options,
+                Err(err) => return err.to_compile_error().into(),
+            };
+            for (field, options) in data.fields.iter().zip(&field_options) {
+                // witnesses are never absorbed
+                let msg = if options.skip_absorb {
+                    "`skip_absorb` only applies to SymInstance fields"
+                } else if options.nested {
+                    // nested witnesses need no marker
+                    "`nested` only applies to SymInstance fields"
+                } else if options.name.is_some() && !is_sym_scalar_type(&field.ty) {
+                    // the names in a nested witness are its own
+                    "`name` only applies to SymScalar fields"
+                } else {
+                    continue;
+                };
+                return syn::Error::new_spanned(field, msg)
+                    .to_compile_error()
+                    .into();
+            }
+
             // Validate fields and generate rand() body
             let rand_body = match &data.fields {
                 Fields::Named(fields) => {
@@ -27,11 +58,14 @@ pub fn derive_sym_witness_impl(input: TokenStream) -> TokenStream {
                         if !is_sym_witness_type(&field.ty) {
                             let field_name = field.ident.as_ref().unwrap();
                             let ty = &field.ty;
-                            panic!(
+                            let msg = format!(
                                 "Field '{}' has type '{}' which is not SymScalar or SymWitness",
                                 field_name,
                                 quote!(#ty)
                             );
+                            return syn::Error::new_spanned(field, msg)
+                                .to_compile_error()
+                                .into();
                         }
                     }
 
@@ -39,7 +73,7 @@ pub fn derive_sym_witness_impl(input: TokenStream) -> TokenStream {
                     let field_inits = fields.named.iter().map(|field| {
                         let field_name = field.ident.as_ref().unwrap();
                         quote! {
-                            #field_name: SymWitness::rand(rng)
+                            #field_name: crate::absorb::FixedLenWitness::rand(rng)
                         }
                     });
 
@@ -54,17 +88,20 @@ pub fn derive_sym_witness_impl(input: TokenStream) -> TokenStream {
                     for (i, field) in fields.unnamed.iter().enumerate() {
                         if !is_sym_witness_type(&field.ty) {
                             let ty = &field.ty;
-                            panic!(
+                            let msg = format!(
                                 "Field {} has type '{}' which is not SymScalar or SymWitness",
                                 i,
                                 quote!(#ty)
                             );
+                            return syn::Error::new_spanned(field, msg)
+                                .to_compile_error()
+                                .into();
                         }
                     }
 
                     // Generate tuple struct initialization for rand()
                     let field_inits = (0..fields.unnamed.len()).map(|_| {
-                        quote! { SymWitness::rand(rng) }
+                        quote! { crate::absorb::FixedLenWitness::rand(rng) }
                     });
 
                     quote! {
@@ -76,76 +113,40 @@ pub fn derive_sym_witness_impl(input: TokenStream) -> TokenStream {
                 }
             };
 
-            // Generate values() method body
-            let values_body = match &data.fields {
-                Fields::Named(fields) => {
-                    let field_values = fields.named.iter().map(|field| {
-                        let field_name = field.ident.as_ref().unwrap();
-                        let field_type = &field.ty;
-
-                        if let syn::Type::Path(type_path) = field_type {
-                            if let Some(segment) = type_path.path.segments.last() {
-                                if segment.ident == "SymScalar" {
-                                    // For SymScalar, check if it's instantiated and use evaluate()
-                                    return quote! {
-                                        match &self.#field_name {
-                                            crate::equations::SymScalar::Var(None) => return Err(crate::errors::SigmaProofError::UninstantiatedScalar),
-                                            _ => values.push(self.#field_name.evaluate()?),
-                                        }
-                                    };
-                                }
-                            }
-                        }
-
-                        // For SymWitness types, call values() recursively
-                        quote! {
-                            values.extend(self.#field_name.values()?);
-                        }
-                    });
-
-                    quote! {
-                        let mut values = Vec::new();
-                        #(#field_values)*
-                        Ok(values)
+            // Generate values() method body, which attributes the first
+            // uninstantiated scalar to its field like validate()
+            let field_values = data.fields.iter().enumerate().map(|(i, field)| {
+                let (access, field_name) = match &field.ident {
+                    Some(ident) => (quote! { #ident }, ident.to_string()),
+                    None => {
+                        let index = syn::Index::from(i);
+                        (quote! { #index }, format!("field_{}", i))
                     }
+                };
+                quote! {
+                    values.extend(crate::absorb::qualify_field(SymWitness::values(&self.#access), #field_name)?);
                 }
-                Fields::Unnamed(fields) => {
-                    let field_values = fields.unnamed.iter().enumerate().map(|(i, field)| {
-                        let index = syn::Index::from(i);
-                        let field_type = &field.ty;
-
-                        if let syn::Type::Path(type_path) = field_type {
-                            if let Some(segment) = type_path.path.segments.last() {
-                                if segment.ident == "SymScalar" {
-                                    // For SymScalar, check if it's instantiated and use evaluate()
-                                    return quote! {
-                                        match &self.#index {
-                                            crate::equations::SymScalar::Var(None) => return Err(crate::errors::SigmaProofError::UninstantiatedScalar),
-                                            _ => values.push(self.#index.evaluate()?),
-                                        }
-                                    };
-                                }
-                            }
-                        }
-
-                        // For SymWitness types, call values() recursively
-                        quote! {
-                            values.extend(self.#index.values()?);
-                        }
-                    });
+            });
+            let values_body = quote! {
+                let mut values = alloc::vec::Vec::new();
+                #(#field_values)*
+                Ok(values)
+            };
 
-                    quote! {
-                        let mut values = Vec::new();
-                        #(#field_values)*
-                        Ok(values)
-                    }
+            // Generate from_values() method body, which checks the number of
+            // scalars before taking any of them
+            let check_len = quote! {
+                let expected = <Self as crate::absorb::FixedLenWitness>::NUM_SCALARS;
+                if scalars.len() < expected {
+                    return Err(crate::errors::SigmaProofError::InsufficientScalars);
                 }
-                Fields::Unit => {
-                    quote! { Ok(Vec::new()) }
+                if scalars.len() > expected {
+                    return Err(crate::errors::SigmaProofError::TooManyScalars {
+                        expected,
+                        actual: scalars.len(),
+                    });
                 }
             };
-
-            // Generate from_values() method body
             let from_values_body = match &data.fields {
                 Fields::Named(fields) => {
                     let field_assignments = fields.named.iter().map(|field| {
@@ -156,92 +157,70 @@ pub fn derive_sym_witness_impl(input: TokenStream) -> TokenStream {
                             if let Some(segment) = type_path.path.segments.last() {
                                 if segment.ident == "SymScalar" {
                                     return quote! {
-                                        #field_name: {
-                                            if cursor.position() >= scalars.len() as u64 {
-                                                return Err(crate::errors::SigmaProofError::InsufficientScalars);
-                                            }
-                                            let field_values = SymWitness::from_values(&scalars[cursor.position() as usize..cursor.position() as usize + 1])?;
-                                            cursor.set_position(cursor.position() + 1);
-                                            field_values
-                                        }
+                                        #field_name: SymWitness::from_values(crate::absorb::take_values(
+                                            scalars,
+                                            &mut offset,
+                                            1,
+                                            crate::errors::SigmaProofError::InsufficientScalars,
+                                        )?)?
                                     };
                                 }
                             }
                         }
 
-                        // For SymWitness types, try to consume from remaining buffer
+                        // For SymWitness types, consume exactly as many scalars as the field has
                         quote! {
-                            #field_name: {
-                                let remaining = &scalars[cursor.position() as usize..];
-                                let field_values = SymWitness::from_values(remaining)?;
-                                let field_scalar_count = field_values.values()?.len();
-                                cursor.set_position(cursor.position() + field_scalar_count as u64);
-                                field_values
-                            }
+                            #field_name: <#field_type as SymWitness>::from_values(crate::absorb::take_values(
+                                scalars,
+                                &mut offset,
+                                <#field_type as crate::absorb::FixedLenWitness>::NUM_SCALARS,
+                                crate::errors::SigmaProofError::InsufficientScalars,
+                            )?)?
                         }
                     });
 
                     quote! {
-                        let mut cursor = std::io::Cursor::new(());
-                        cursor.set_position(0);
-                        let result = Self {
+                        #check_len
+                        let mut offset: usize = 0;
+                        Ok(Self {
                             #(#field_assignments),*
-                        };
-                        if cursor.position() == scalars.len() as u64 {
-                            Ok(result)
-                        } else {
-                            Err(crate::errors::SigmaProofError::TooManyScalars {
-                                expected: cursor.position() as usize,
-                                actual: scalars.len(),
-                            })
-                        }
+                        })
                     }
                 }
                 Fields::Unnamed(fields) => {
-                    let field_assignments = fields.unnamed.iter().enumerate().map(|(_i, field)| {
+                    let field_assignments = fields.unnamed.iter().map(|field| {
                         let field_type = &field.ty;
 
                         if let syn::Type::Path(type_path) = field_type {
                             if let Some(segment) = type_path.path.segments.last() {
                                 if segment.ident == "SymScalar" {
                                     return quote! {
-                                        {
-                                            if cursor.position() >= scalars.len() as u64 {
-                                                return Err(crate::errors::SigmaProofError::InsufficientScalars);
-                                            }
-                                            let field_values = SymWitness::from_values(&scalars[cursor.position() as usize..cursor.position() as usize + 1])?;
-                                            cursor.set_position(cursor.position() + 1);
-                                            field_values
-                                        }
+                                        SymWitness::from_values(crate::absorb::take_values(
+                                            scalars,
+                                            &mut offset,
+                                            1,
+                                            crate::errors::SigmaProofError::InsufficientScalars,
+                                        )?)?
                                     };
                                 }
                             }
                         }
 
-                        // For SymWitness types, try to consume from remaining buffer
+                        // For SymWitness types, consume exactly as many scalars as the field has
                         quote! {
-                            {
-                                let remaining = &scalars[cursor.position() as usize..];
-                                let field_values = SymWitness::from_values(remaining)?;
-                                let field_scalar_count = field_values.values()?.len();
-                                cursor.set_position(cursor.position() + field_scalar_count as u64);
-                                field_values
-                            }
+                            <#field_type as SymWitness>::from_values(crate::absorb::take_values(
+                                scalars,
+                                &mut offset,
+                                <#field_type as crate::absorb::FixedLenWitness>::NUM_SCALARS,
+                                crate::errors::SigmaProofError::InsufficientScalars,
+                            )?)?
                         }
                     });
 
                     quote! {
-                        let mut cursor = std::io::Cursor::new(());
-                        cursor.set_position(0);
-                        let result = Self(#(#field_assignments),*);
-                        if cursor.position() == scalars.len() as u64 {
-                            Ok(result)
-                        } else {
-                            Err(crate::errors::SigmaProofError::TooManyScalars {
-                                expected: cursor.position() as usize,
-                                actual: scalars.len(),
-                            })
-                        }
+                        #check_len
+                        let mut offset: usize = 0;
+                        Ok(Self(#(#field_assignments),*))
                     }
                 }
                 Fields::Unit => {
@@ -258,48 +237,22 @@ pub fn derive_sym_witness_impl(input: TokenStream) -> TokenStream {
                 }
             };
 
-            // Generate get_var_name() method body
+            // Generate get_var_name() method body, walking the fields with the
+            // offset of their first scalar and recursing into nested witnesses
             let get_var_name_body = match &data.fields {
-                Fields::Named(fields) => {
-                    let match_arms = fields.named.iter().enumerate().map(|(i, field)| {
-                        let field_name = field.ident.as_ref().unwrap();
-                        let field_name_str = field_name.to_string();
+                Fields::Named(_) | Fields::Unnamed(_) => {
+                    let field_names = data.fields.iter().zip(&field_options).enumerate().map(|(i, (field, options))| {
+                        let field_name = crate::field_name(field, options, i);
                         let field_type = &field.ty;
 
                         if let syn::Type::Path(type_path) = field_type {
                             if let Some(segment) = type_path.path.segments.last() {
                                 if segment.ident == "SymScalar" {
                                     return quote! {
-                                        #i => #field_name_str,
-                                    };
-                                }
-                            }
-                        }
-
-                        // For SymWitness types, we need to handle recursively
-                        // This is complex, so for now we'll generate a placeholder
-                        quote! {
-                            #i => "nested_field",
-                        }
-                    });
-
-                    quote! {
-                        match index {
-                            #(#match_arms)*
-                            _ => "unknown",
-                        }
-                    }
-                }
-                Fields::Unnamed(fields) => {
-                    let match_arms = fields.unnamed.iter().enumerate().map(|(i, field)| {
-                        let field_type = &field.ty;
-
-                        if let syn::Type::Path(type_path) = field_type {
-                            if let Some(segment) = type_path.path.segments.last() {
-                                if segment.ident == "SymScalar" {
-                                    let field_name = format!("field_{}", i);
-                                    return quote! {
-                                        #i => #field_name,
+                                        if index == offset {
+                                            return #field_name.into();
+                                        }
+                                        offset += 1;
                                     };
                                 }
                             }
@@ -307,76 +260,87 @@ pub fn derive_sym_witness_impl(input: TokenStream) -> TokenStream {
 
                         // For SymWitness types
                         quote! {
-                            #i => "nested_field",
+                            let len = <#field_type as crate::absorb::FixedLenWitness>::NUM_SCALARS;
+                            if index - offset < len {
+                                return <#field_type as SymWitness>::get_var_name(index - offset);
+                            }
+                            offset += len;
                         }
                     });
 
                     quote! {
-                        match index {
-                            #(#match_arms)*
-                            _ => "unknown",
-                        }
+                        let mut offset = 0usize;
+                        #(#field_names)*
+                        let _ = offset;
+                        "unknown".into()
                     }
                 }
                 Fields::Unit => {
                     quote! {
-                        "unit"
+                        "unit".into()
+                    }
+                }
+            };
+
+            // Generate the NUM_SCALARS constant, whose overflow fails to
+            // compile
+            let field_counts = data.fields.iter().map(|field| {
+                if is_sym_scalar_type(&field.ty) {
+                    return quote! { 1 };
+                }
+                // For SymWitness types
+                let field_type = &field.ty;
+                quote! { <#field_type as crate::absorb::FixedLenWitness>::NUM_SCALARS }
+            });
+            let num_scalars_const = quote! { 0usize #(+ #field_counts)* };
+
+            // Generate validate() method body, which attributes the first
+            // uninstantiated scalar to its field
+            let validations = data.fields.iter().enumerate().map(|(i, field)| {
+                let (access, field_name) = match &field.ident {
+                    Some(ident) => (quote! { #ident }, ident.to_string()),
+                    None => {
+                        let index = syn::Index::from(i);
+                        (quote! { #index }, format!("field_{}", i))
                     }
+                };
+                quote! {
+                    crate::absorb::qualify_field(SymWitness::validate(&self.#access), #field_name)?;
                 }
+            });
+            let validate_body = quote! {
+                #(#validations)*
+                Ok(())
             };
 
-            // Generate num_scalars() method body
-            let num_scalars_body = match &data.fields {
+            // Generate zeroize() method body
+            let zeroize_body = match &data.fields {
                 Fields::Named(fields) => {
-                    let field_counts = fields.named.iter().map(|field| {
-                        let field_type = &field.ty;
-                        if let syn::Type::Path(type_path) = field_type {
-                            if let Some(segment) = type_path.path.segments.last() {
-                                if segment.ident == "SymScalar" {
-                                    return quote! { 1 };
-                                }
-                            }
-                        }
-                        // For SymWitness types
-                        quote! { #field_type::num_scalars() }
+                    let field_zeroizes = fields.named.iter().map(|field| {
+                        let field_name = field.ident.as_ref().unwrap();
+                        quote! { SymWitness::zeroize(&mut self.#field_name); }
                     });
-
-                    quote! {
-                        0 #(+ #field_counts)*
-                    }
+                    quote! { #(#field_zeroizes)* }
                 }
                 Fields::Unnamed(fields) => {
-                    let field_counts = fields.unnamed.iter().map(|field| {
-                        let field_type = &field.ty;
-                        if let syn::Type::Path(type_path) = field_type {
-                            if let Some(segment) = type_path.path.segments.last() {
-                                if segment.ident == "SymScalar" {
-                                    return quote! { 1 };
-                                }
-                            }
-                        }
-                        // For SymWitness types
-                        quote! { #field_type::num_scalars() }
+                    let field_zeroizes = (0..fields.unnamed.len()).map(|i| {
+                        let index = syn::Index::from(i);
+                        quote! { SymWitness::zeroize(&mut self.#index); }
                     });
-
-                    quote! {
-                        0 #(+ #field_counts)*
-                    }
-                }
-                Fields::Unit => {
-                    quote! { 0 }
+                    quote! { #(#field_zeroizes)* }
                 }
+                Fields::Unit => quote! {},
             };
 
+            let clone_impl = crate::clone_impl(&input, &options);
+
             let expanded = quote! {
+                #clone_impl
+
                 impl #impl_generics crate::absorb::sealed_witness::Sealed for #name #ty_generics #where_clause {}
 
                 impl #impl_generics SymWitness for #name #ty_generics #where_clause {
-                    fn rand<R: rand_core::CryptoRngCore + ?Sized>(rng: &mut R) -> Self {
-                        #rand_body
-                    }
-
-                    fn values(&self) -> crate::errors::SigmaProofResult<Vec<curve25519_dalek::Scalar>> {
+                    fn values(&self) -> crate::errors::SigmaProofResult<alloc::vec::Vec<curve25519_dalek::Scalar>> {
                         #values_body
                     }
 
@@ -384,23 +348,61 @@ pub fn derive_sym_witness_impl(input: TokenStream) -> TokenStream {
                         #from_values_body
                     }
 
-                    fn num_scalars() -> usize {
-                        #num_scalars_body
+                    fn num_scalars() -> core::option::Option<usize> {
+                        core::option::Option::Some(<Self as crate::absorb::FixedLenWitness>::NUM_SCALARS)
                     }
 
-                    fn get_var_name(index: usize) -> &'static str {
+                    fn get_var_name(index: usize) -> alloc::borrow::Cow<'static, str> {
                         #get_var_name_body
                     }
+
+                    fn validate(&self) -> crate::errors::SigmaProofResult<()> {
+                        #validate_body
+                    }
+
+                    #[cfg(feature = "zeroize")]
+                    fn zeroize(&mut self) {
+                        #zeroize_body
+                    }
+
+                    fn scalar_len(&self) -> usize {
+                        <Self as crate::absorb::FixedLenWitness>::NUM_SCALARS
+                    }
+
+                    fn rand_like<R: rand_core::CryptoRngCore + ?Sized>(&self, rng: &mut R) -> Self {
+                        crate::absorb::FixedLenWitness::rand(rng)
+                    }
+                }
+
+                impl #impl_generics crate::absorb::FixedLenWitness for #name #ty_generics #where_clause {
+                    const NUM_SCALARS: usize = #num_scalars_const;
+
+                    fn rand<R: rand_core::CryptoRngCore + ?Sized>(rng: &mut R) -> Self {
+                        #rand_body
+                    }
+                }
+
+                #[cfg(feature = "zeroize")]
+                impl #impl_generics ::zeroize::Zeroize for #name #ty_generics #where_clause {
+                    fn zeroize(&mut self) {
+                        SymWitness::zeroize(self);
+                    }
                 }
             };
 
             TokenStream::from(expanded)
         }
-        Data::Enum(_) => {
-            panic!("SymWitness derive macro does not support enums");
-        }
-        Data::Union(_) => {
-            panic!("SymWitness derive macro does not support unions");
-        }
+        Data::Enum(data) => syn::Error::new_spanned(
+            data.enum_token,
+            "SymWitness derive macro does not support enums",
+        )
+        .to_compile_error()
+        .into(),
+        Data::Union(data) => syn::Error::new_spanned(
+            data.union_token,
+            "SymWitness derive macro does not support unions",
+        )
+        .to_compile_error()
+        .into(),
     }
 }
@@ -1,44 +1,209 @@
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 use curve25519_dalek::{RistrettoPoint, Scalar};
 use rand_core::CryptoRngCore;
 
-use crate::errors::{SigmaProofError, SigmaProofResult};
+use crate::{
+    compiler::{PreparedInstance, SigmaProof},
+    errors::{SigmaProofError, SigmaProofResult},
+};
 
 //
-// Traits only available in this crate
+// Traits only implemented in this crate
+//
+// They are public, but hidden, so that the expansion of the derives resolves
+// wherever they are used; implementing them is not part of the API.
 //
 
-pub(crate) mod sealed_witness {
+#[doc(hidden)]
+pub mod sealed_witness {
     pub trait Sealed {}
 
     impl Sealed for super::SymScalar {}
+    impl<const N: usize> Sealed for [super::SymScalar; N] {}
+    impl Sealed for super::VecWitness {}
 }
 
-pub(crate) mod sealed_instance {
+#[doc(hidden)]
+pub mod sealed_instance {
     pub trait Sealed {}
 
     impl Sealed for super::SymScalar {}
     impl Sealed for super::SymPoint {}
 }
 
+/// Asserted by the derives of [`SymWitness`] and [`SymInstance`], whose
+/// types protocols clone.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` must implement `Clone` to derive `SymWitness` or `SymInstance`",
+    label = "`{Self}` is not `Clone`",
+    note = "add `#[derive(Clone)]`, or `#[sym(clone)]` to have it derived"
+)]
+pub trait RequiresClone {}
+
+impl<T: Clone> RequiresClone for T {}
+
+/// Takes the next `len` values at `*cursor` and advances it, for the derives
+/// of [`SymWitness`] and [`SymInstance`], returning `err` instead of panicking
+/// if there are not enough values.
+#[doc(hidden)]
+pub fn take_values<'a, T>(
+    values: &'a [T],
+    cursor: &mut usize,
+    len: usize,
+    err: SigmaProofError,
+) -> SigmaProofResult<&'a [T]> {
+    let end = cursor
+        .checked_add(len)
+        .filter(|end| *end <= values.len())
+        .ok_or(err)?;
+    let taken = &values[*cursor..end];
+    *cursor = end;
+    Ok(taken)
+}
+
+/// The length of the values of runtime length in a sample instance or
+/// witness, where there is no instance to take the length from, e.g. in the
+/// dummy statement of [`crate::compiler::SigmaProof::statement`].
+pub const RUNTIME_SAMPLE_LEN: usize = 2;
+
 pub trait SymWitness: sealed_witness::Sealed {
-    fn rand<R: CryptoRngCore + ?Sized>(rng: &mut R) -> Self;
+    /// The scalars of the witness, in field order.
+    ///
+    /// Like [`SymWitness::validate`], derived witnesses fail with
+    /// [`SigmaProofError::UninstantiatedField`] naming the first
+    /// uninstantiated field.
     fn values(&self) -> SigmaProofResult<Vec<Scalar>>;
     fn from_values(scalars: &[Scalar]) -> SigmaProofResult<Self>
     where
         Self: Sized;
-    fn num_scalars() -> usize;
-    fn get_var_name(index: usize) -> &'static str;
+    /// The number of scalars of every witness of this type, or `None` for
+    /// witnesses of runtime length, see [`SymWitness::scalar_len`] and
+    /// [`FixedLenWitness::NUM_SCALARS`].
+    fn num_scalars() -> Option<usize>;
+    fn get_var_name(index: usize) -> Cow<'static, str>;
+
+    /// Overwrites every scalar of the witness with zero.
+    #[cfg(feature = "zeroize")]
+    fn zeroize(&mut self);
+
+    /// The number of scalars in this witness.
+    fn scalar_len(&self) -> usize;
+
+    /// Samples a random witness of the same length as this one.
+    fn rand_like<R: CryptoRngCore + ?Sized>(&self, rng: &mut R) -> Self
+    where
+        Self: Sized;
+
+    /// Checks that every scalar of the witness is instantiated, without
+    /// proving.
+    ///
+    /// Derived witnesses fail with [`SigmaProofError::UninstantiatedField`],
+    /// naming the first offending field, qualified by the fields of the
+    /// witnesses it is nested in, e.g. `inner.a`.
+    fn validate(&self) -> SigmaProofResult<()> {
+        self.values().map(drop)
+    }
+
+    /// Pairs the name of each scalar of the witness with its value, in the
+    /// order of [`SymWitness::values`].
+    fn named_values(&self) -> SigmaProofResult<Vec<(Cow<'static, str>, Scalar)>> {
+        Ok(self
+            .values()?
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| (Self::get_var_name(i), value))
+            .collect())
+    }
+}
+
+/// A witness all of whose values have [`FixedLenWitness::NUM_SCALARS`]
+/// scalars, i.e. one not of runtime length, which can be sampled without a
+/// witness to take the length from and nested in a derived witness.
+///
+/// Derived witnesses implement it, while [`VecWitness`] does not.
+pub trait FixedLenWitness: SymWitness + Sized {
+    /// The number of scalars of every witness of this type, which
+    /// [`SymWitness::num_scalars`] returns.
+    const NUM_SCALARS: usize;
+
+    /// Samples a random witness.
+    fn rand<R: CryptoRngCore + ?Sized>(rng: &mut R) -> Self;
 }
 
 pub trait SymInstance: sealed_instance::Sealed {
-    fn num_scalars() -> usize;
-    fn num_points() -> usize;
+    /// The number of scalars of every instance of this type, or `None` for
+    /// instances of runtime length, see [`SymInstance::scalars`] and
+    /// [`FixedLenInstance`].
+    fn num_scalars() -> Option<usize>;
+    /// Like [`SymInstance::num_scalars`], for the points.
+    fn num_points() -> Option<usize>;
     fn from_values(scalars: &[Scalar], points: &[RistrettoPoint]) -> SigmaProofResult<Self>
     where
         Self: Sized;
-    fn get_field_names() -> Vec<&'static str>;
+    /// The names of the instance values, scalars first then points, in the
+    /// order of [`SymInstance::scalars`] and [`SymInstance::points`].
+    fn get_field_names() -> Vec<String>;
     fn points(&self) -> Vec<SymPoint>;
     fn scalars(&self) -> Vec<SymScalar>;
+
+    /// The points absorbed into the transcript: [`SymInstance::points`]
+    /// without the fields marked `#[sym(skip_absorb)]` in a derived instance.
+    ///
+    /// A skipped field is not bound by the proof, which is only sound if it
+    /// is implied by the protocol's label, e.g. a fixed public generator. A
+    /// field the prover can choose must always be absorbed, or a proof could
+    /// be replayed against an instance with another value for it.
+    fn absorbed_points(&self) -> Vec<SymPoint> {
+        self.points()
+    }
+
+    /// Like [`SymInstance::absorbed_points`], for the scalars.
+    fn absorbed_scalars(&self) -> Vec<SymScalar> {
+        self.scalars()
+    }
+
+    /// Evaluates the instance and its image under `P::f` once, to verify many
+    /// proofs of `P` against it with [`SigmaProof::verify_prepared`].
+    fn prepare<P: SigmaProof<INSTANCE = Self>>(&self) -> SigmaProofResult<PreparedInstance<P>>
+    where
+        Self: Clone + Sized,
+    {
+        PreparedInstance::new(self.clone())
+    }
+}
+
+/// An instance all of whose values have [`FixedLenInstance::NUM_SCALARS`]
+/// scalars and [`FixedLenInstance::NUM_POINTS`] points, i.e. one not of
+/// runtime length, which can be nested in a derived instance.
+pub trait FixedLenInstance: SymInstance {
+    /// The number of scalars, which [`SymInstance::num_scalars`] returns.
+    const NUM_SCALARS: usize;
+    /// The number of points, which [`SymInstance::num_points`] returns.
+    const NUM_POINTS: usize;
+}
+
+/// Attributes a failed [`SymWitness::values`] or [`SymWitness::validate`] of
+/// the field `field` to it, for the derives.
+#[doc(hidden)]
+pub fn qualify_field<T>(result: SigmaProofResult<T>, field: &str) -> SigmaProofResult<T> {
+    result.map_err(|err| match err {
+        SigmaProofError::UninstantiatedField { field: inner } => {
+            SigmaProofError::UninstantiatedField {
+                field: format!("{field}.{inner}"),
+            }
+        }
+        SigmaProofError::UninstantiatedScalar => SigmaProofError::UninstantiatedField {
+            field: field.to_string(),
+        },
+        err => err,
+    })
 }
 
 //
@@ -49,8 +214,8 @@ pub use crate::equations::{SymPoint, SymScalar};
 pub use sigma_proof_compiler_derive::{SymInstance, SymWitness};
 
 impl SymWitness for SymScalar {
-    fn rand<R: CryptoRngCore + ?Sized>(rng: &mut R) -> Self {
-        SymScalar::Const(Scalar::random(rng))
+    fn validate(&self) -> SigmaProofResult<()> {
+        self.check_instantiated()
     }
 
     fn values(&self) -> SigmaProofResult<Vec<Scalar>> {
@@ -61,36 +226,169 @@ impl SymWitness for SymScalar {
     }
 
     fn from_values(scalars: &[Scalar]) -> SigmaProofResult<Self> {
-        if scalars.len() == 1 {
-            Ok(SymScalar::Var(Some(scalars[0])))
-        } else {
-            Err(SigmaProofError::TooManyScalars {
+        match scalars {
+            [] => Err(SigmaProofError::InsufficientScalars),
+            [scalar] => Ok(SymScalar::Var(Some(*scalar))),
+            _ => Err(SigmaProofError::TooManyScalars {
                 expected: 1,
                 actual: scalars.len(),
-            })
+            }),
         }
     }
 
-    fn num_scalars() -> usize {
-        1
+    fn num_scalars() -> Option<usize> {
+        Some(1)
     }
 
-    fn get_var_name(index: usize) -> &'static str {
+    fn get_var_name(index: usize) -> Cow<'static, str> {
         if index == 0 {
-            "s"
+            "s".into()
         } else {
-            "unknown"
+            "unknown".into()
         }
     }
+
+    #[cfg(feature = "zeroize")]
+    fn zeroize(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+
+    fn scalar_len(&self) -> usize {
+        1
+    }
+
+    fn rand_like<R: CryptoRngCore + ?Sized>(&self, rng: &mut R) -> Self {
+        Self::rand(rng)
+    }
+}
+
+impl FixedLenWitness for SymScalar {
+    const NUM_SCALARS: usize = 1;
+
+    fn rand<R: CryptoRngCore + ?Sized>(rng: &mut R) -> Self {
+        SymScalar::Const(Scalar::random(rng))
+    }
+}
+
+impl<const N: usize> SymWitness for [SymScalar; N] {
+    fn values(&self) -> SigmaProofResult<Vec<Scalar>> {
+        let mut values = Vec::with_capacity(N);
+        for scalar in self {
+            values.extend(scalar.values()?);
+        }
+        Ok(values)
+    }
+
+    fn from_values(scalars: &[Scalar]) -> SigmaProofResult<Self> {
+        if scalars.len() < N {
+            return Err(SigmaProofError::InsufficientScalars);
+        }
+        if scalars.len() > N {
+            return Err(SigmaProofError::TooManyScalars {
+                expected: N,
+                actual: scalars.len(),
+            });
+        }
+        Ok(core::array::from_fn(|i| SymScalar::Var(Some(scalars[i]))))
+    }
+
+    fn num_scalars() -> Option<usize> {
+        Some(N)
+    }
+
+    fn get_var_name(index: usize) -> Cow<'static, str> {
+        if index < N {
+            format!("w_{index}").into()
+        } else {
+            "unknown".into()
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    fn zeroize(&mut self) {
+        for scalar in self {
+            SymWitness::zeroize(scalar);
+        }
+    }
+
+    fn scalar_len(&self) -> usize {
+        N
+    }
+
+    fn rand_like<R: CryptoRngCore + ?Sized>(&self, rng: &mut R) -> Self {
+        Self::rand(rng)
+    }
+}
+
+impl<const N: usize> FixedLenWitness for [SymScalar; N] {
+    const NUM_SCALARS: usize = N;
+
+    fn rand<R: CryptoRngCore + ?Sized>(rng: &mut R) -> Self {
+        core::array::from_fn(|_| SymScalar::rand(rng))
+    }
+}
+
+/// A witness whose length is only known at runtime.
+///
+/// It is no [`FixedLenWitness`], so it cannot be nested in a derived witness,
+/// and protocols using it must override
+/// [`crate::compiler::SigmaProof::num_responses`].
+#[derive(Clone)]
+pub struct VecWitness(pub Vec<SymScalar>);
+
+impl SymWitness for VecWitness {
+    fn values(&self) -> SigmaProofResult<Vec<Scalar>> {
+        let mut values = Vec::with_capacity(self.0.len());
+        for scalar in &self.0 {
+            values.extend(scalar.values()?);
+        }
+        Ok(values)
+    }
+
+    fn from_values(scalars: &[Scalar]) -> SigmaProofResult<Self> {
+        Ok(VecWitness(
+            scalars.iter().map(|s| SymScalar::Var(Some(*s))).collect(),
+        ))
+    }
+
+    fn num_scalars() -> Option<usize> {
+        None
+    }
+
+    fn get_var_name(index: usize) -> Cow<'static, str> {
+        format!("w_{index}").into()
+    }
+
+    #[cfg(feature = "zeroize")]
+    fn zeroize(&mut self) {
+        for scalar in &mut self.0 {
+            SymWitness::zeroize(scalar);
+        }
+    }
+
+    fn scalar_len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn rand_like<R: CryptoRngCore + ?Sized>(&self, rng: &mut R) -> Self {
+        VecWitness(self.0.iter().map(|_| SymScalar::rand(rng)).collect())
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for VecWitness {
+    fn zeroize(&mut self) {
+        SymWitness::zeroize(self);
+    }
 }
 
 impl SymInstance for SymScalar {
-    fn num_scalars() -> usize {
-        1
+    fn num_scalars() -> Option<usize> {
+        Some(1)
     }
 
-    fn num_points() -> usize {
-        0
+    fn num_points() -> Option<usize> {
+        Some(0)
     }
 
     fn from_values(scalars: &[Scalar], points: &[RistrettoPoint]) -> SigmaProofResult<Self> {
@@ -104,8 +402,8 @@ impl SymInstance for SymScalar {
         }
     }
 
-    fn get_field_names() -> Vec<&'static str> {
-        vec!["scalar"]
+    fn get_field_names() -> Vec<String> {
+        vec!["scalar".to_string()]
     }
 
     fn points(&self) -> Vec<SymPoint> {
@@ -117,13 +415,18 @@ impl SymInstance for SymScalar {
     }
 }
 
+impl FixedLenInstance for SymScalar {
+    const NUM_SCALARS: usize = 1;
+    const NUM_POINTS: usize = 0;
+}
+
 impl SymInstance for SymPoint {
-    fn num_scalars() -> usize {
-        0
+    fn num_scalars() -> Option<usize> {
+        Some(0)
     }
 
-    fn num_points() -> usize {
-        1
+    fn num_points() -> Option<usize> {
+        Some(1)
     }
 
     fn from_values(scalars: &[Scalar], points: &[RistrettoPoint]) -> SigmaProofResult<Self> {
@@ -137,8 +440,8 @@ impl SymInstance for SymPoint {
         }
     }
 
-    fn get_field_names() -> Vec<&'static str> {
-        vec!["point"]
+    fn get_field_names() -> Vec<String> {
+        vec!["point".to_string()]
     }
 
     fn points(&self) -> Vec<SymPoint> {
@@ -149,3 +452,770 @@ impl SymInstance for SymPoint {
         vec![]
     }
 }
+
+impl FixedLenInstance for SymPoint {
+    const NUM_SCALARS: usize = 0;
+    const NUM_POINTS: usize = 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compiler::SigmaProof,
+        transcript::{CheckpointTranscript, ProofTranscript},
+    };
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    #[derive(SymWitness, Clone)]
+    struct InnerWitness {
+        a: SymScalar,
+        b: SymScalar,
+    }
+
+    #[derive(SymWitness, Clone)]
+    struct NestedWitness {
+        x: SymScalar,
+        inner: InnerWitness,
+        y: SymScalar,
+    }
+
+    #[derive(SymWitness, Clone)]
+    struct NestedTupleWitness(InnerWitness, SymScalar, InnerWitness);
+
+    fn scalars(n: u64) -> Vec<Scalar> {
+        (1..=n).map(Scalar::from).collect()
+    }
+
+    #[test]
+    fn test_nested_witness_offsets() {
+        assert_eq!(NestedWitness::num_scalars(), Some(4));
+
+        let values = scalars(4);
+        let witness = NestedWitness::from_values(&values).unwrap();
+        assert_eq!(witness.x.evaluate().unwrap(), values[0]);
+        assert_eq!(witness.inner.a.evaluate().unwrap(), values[1]);
+        assert_eq!(witness.inner.b.evaluate().unwrap(), values[2]);
+        assert_eq!(witness.y.evaluate().unwrap(), values[3]);
+        assert_eq!(witness.values().unwrap(), values);
+    }
+
+    #[test]
+    fn test_nested_tuple_witness_offsets() {
+        assert_eq!(NestedTupleWitness::num_scalars(), Some(5));
+
+        let values = scalars(5);
+        let witness = NestedTupleWitness::from_values(&values).unwrap();
+        assert_eq!(witness.1.evaluate().unwrap(), values[2]);
+        assert_eq!(witness.2.a.evaluate().unwrap(), values[3]);
+        assert_eq!(witness.values().unwrap(), values);
+    }
+
+    #[test]
+    fn test_named_values() {
+        let values = scalars(2);
+        let witness = InnerWitness::from_values(&values).unwrap();
+        assert_eq!(
+            witness.named_values().unwrap(),
+            vec![("a".into(), values[0]), ("b".into(), values[1])]
+        );
+
+        let names: Vec<_> = (0..5).map(NestedTupleWitness::get_var_name).collect();
+        assert_eq!(names, ["a", "b", "field_1", "a", "b"]);
+        let values = scalars(4);
+        let witness = NestedWitness::from_values(&values).unwrap();
+        let (names, named): (Vec<_>, Vec<_>) = witness.named_values().unwrap().into_iter().unzip();
+        assert_eq!(names, ["x", "a", "b", "y"]);
+        assert_eq!(named, values);
+        assert_eq!(NestedWitness::get_var_name(4), "unknown");
+    }
+
+    #[test]
+    fn test_validate_nested_witness() {
+        let mut witness = NestedWitness::from_values(&scalars(4)).unwrap();
+        witness.validate().unwrap();
+
+        witness.inner.b = SymScalar::Var(None);
+        assert!(matches!(
+            witness.validate(),
+            Err(SigmaProofError::UninstantiatedField { field }) if field == "inner.b"
+        ));
+        // the first offending field is reported
+        witness.y = SymScalar::Const(Scalar::ONE) + SymScalar::Var(None);
+        witness.x = SymScalar::Var(None);
+        assert!(matches!(
+            witness.validate(),
+            Err(SigmaProofError::UninstantiatedField { field }) if field == "x"
+        ));
+
+        let mut witness = NestedTupleWitness::from_values(&scalars(5)).unwrap();
+        witness.validate().unwrap();
+        witness.2.a = SymScalar::Var(None);
+        assert!(matches!(
+            witness.validate(),
+            Err(SigmaProofError::UninstantiatedField { field }) if field == "field_2.a"
+        ));
+
+        // proving reports the field too
+        let mut witness = RenamedWitness::from_values(&scalars(2)).unwrap();
+        witness.nonce = SymScalar::Var(None);
+        let instance = RenamedInstance::new(crate::sigmas::G, crate::sigmas::G);
+        assert!(matches!(
+            RenamedSchnorr::prove(&witness, &instance),
+            Err(SigmaProofError::UninstantiatedField { field }) if field == "nonce"
+        ));
+    }
+
+    #[derive(SymWitness, Clone)]
+    struct ThreeFieldWitness {
+        first: SymScalar,
+        second: SymScalar,
+        third: SymScalar,
+    }
+
+    #[test]
+    fn test_values_names_uninstantiated_field() {
+        let mut witness = ThreeFieldWitness::from_values(&scalars(3)).unwrap();
+        assert_eq!(witness.values().unwrap(), scalars(3));
+
+        witness.second = SymScalar::Var(None);
+        assert!(matches!(
+            witness.values(),
+            Err(SigmaProofError::UninstantiatedField { field }) if field == "second"
+        ));
+        witness.third = SymScalar::Var(None);
+        assert!(matches!(
+            witness.values(),
+            Err(SigmaProofError::UninstantiatedField { field }) if field == "second"
+        ));
+
+        let mut witness = NestedWitness::from_values(&scalars(4)).unwrap();
+        witness.inner.a = SymScalar::Var(None);
+        assert!(matches!(
+            witness.values(),
+            Err(SigmaProofError::UninstantiatedField { field }) if field == "inner.a"
+        ));
+    }
+
+    #[test]
+    fn test_from_values_checks_length_up_front() {
+        assert!(matches!(
+            ThreeFieldWitness::from_values(&scalars(2)),
+            Err(SigmaProofError::InsufficientScalars)
+        ));
+        assert!(matches!(
+            ThreeFieldWitness::from_values(&[]),
+            Err(SigmaProofError::InsufficientScalars)
+        ));
+        assert!(matches!(
+            ThreeFieldWitness::from_values(&scalars(4)),
+            Err(SigmaProofError::TooManyScalars {
+                expected: 3,
+                actual: 4
+            })
+        ));
+        assert!(matches!(
+            NestedTupleWitness::from_values(&scalars(7)),
+            Err(SigmaProofError::TooManyScalars {
+                expected: 5,
+                actual: 7
+            })
+        ));
+        assert!(matches!(
+            <SymScalar as SymWitness>::from_values(&[]),
+            Err(SigmaProofError::InsufficientScalars)
+        ));
+    }
+
+    #[test]
+    fn test_nested_witness_wrong_length() {
+        assert!(matches!(
+            NestedWitness::from_values(&scalars(2)),
+            Err(SigmaProofError::InsufficientScalars)
+        ));
+        assert!(matches!(
+            NestedWitness::from_values(&scalars(5)),
+            Err(SigmaProofError::TooManyScalars {
+                expected: 4,
+                actual: 5
+            })
+        ));
+    }
+
+    #[derive(SymWitness)]
+    #[sym(clone)]
+    struct DerivedCloneWitness(SymScalar, InnerWitness);
+
+    #[derive(SymInstance)]
+    #[sym(clone)]
+    struct DerivedCloneInstance {
+        s: SymScalar,
+        point: SymPoint,
+    }
+
+    #[test]
+    fn test_derived_clone() {
+        let witness = DerivedCloneWitness::from_values(&scalars(3)).unwrap();
+        assert_eq!(witness.clone().values().unwrap(), scalars(3));
+
+        let point = RistrettoPoint::random(&mut rand::rngs::OsRng);
+        let instance = DerivedCloneInstance::new(Scalar::ONE, point).clone();
+        assert_eq!(instance.s.evaluate().unwrap(), Scalar::ONE);
+        assert_eq!(instance.point.evaluate().unwrap(), point);
+    }
+
+    #[derive(SymWitness, Clone)]
+    struct RenamedWitness {
+        #[sym(name = "x")]
+        privatekey: SymScalar,
+        nonce: SymScalar,
+    }
+
+    #[derive(SymInstance, Clone)]
+    struct RenamedInstance {
+        #[sym(name = "X")]
+        publickey: SymPoint,
+        #[sym(name = "R")]
+        commitment: SymPoint,
+    }
+
+    /// Proves `x*G = X` and `k*G = R` with renamed fields.
+    struct RenamedSchnorr;
+
+    impl SigmaProof for RenamedSchnorr {
+        const LABEL: &'static [u8] = b"renamed-schnorr-protocol";
+
+        type WITNESS = RenamedWitness;
+        type INSTANCE = RenamedInstance;
+
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![instance.publickey.clone(), instance.commitment.clone()]
+        }
+
+        fn psi(witness: &Self::WITNESS, _instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![
+                &witness.privatekey * crate::sigmas::G,
+                &witness.nonce * crate::sigmas::G,
+            ]
+        }
+    }
+
+    #[test]
+    fn test_renamed_fields() {
+        assert_eq!(RenamedWitness::get_var_name(0), "x");
+        assert_eq!(RenamedWitness::get_var_name(1), "nonce");
+        assert_eq!(RenamedInstance::get_field_names(), ["X", "R"]);
+
+        let spec = RenamedSchnorr::spec();
+        assert!(spec.contains(r"\texttt{x} \cdot G"));
+        assert!(spec.contains(r"\texttt{nonce} \cdot G"));
+        assert!(spec.contains(r"\{ \texttt{X}, \texttt{R} \}"));
+        assert!(!spec.contains("privatekey") && !spec.contains("publickey"));
+    }
+
+    #[derive(SymInstance, Clone)]
+    struct BaseInstance {
+        base: SymPoint,
+        point: SymPoint,
+    }
+
+    /// An instance whose base is not absorbed, which is only sound if the
+    /// verifier fixes it, e.g. to the generator `G`.
+    #[derive(SymInstance, Clone)]
+    struct SkippedBaseInstance {
+        #[sym(name = "G", skip_absorb)]
+        base: SymPoint,
+        point: SymPoint,
+    }
+
+    #[derive(SymInstance, Clone)]
+    struct NestedSkippedInstance(
+        #[sym(skip_absorb)] SymScalar,
+        #[sym(nested)] SkippedBaseInstance,
+    );
+
+    /// Schnorr with respect to a base that is not absorbed, which must be
+    /// fixed by the verifier.
+    struct SkippedBaseSchnorr;
+
+    impl SigmaProof for SkippedBaseSchnorr {
+        const LABEL: &'static [u8] = b"skipped-base-schnorr-protocol";
+
+        type WITNESS = SymScalar;
+        type INSTANCE = SkippedBaseInstance;
+
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![instance.point.clone()]
+        }
+
+        fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![witness.clone() * instance.base.clone()]
+        }
+    }
+
+    fn absorbed_state<I: SymInstance>(instance: &I) -> Vec<u8> {
+        let transcript: ProofTranscript<CheckpointTranscript> =
+            crate::compiler::start_transcript(b"skip-absorb", None, b"", instance).unwrap();
+        transcript.export_state()
+    }
+
+    #[test]
+    fn test_skip_absorb() {
+        let rng = &mut rand::rngs::OsRng;
+        let (x, base) = (Scalar::random(rng), RistrettoPoint::random(rng));
+        let full = BaseInstance::new(base, x * base);
+        let skipped = SkippedBaseInstance::new(base, x * base);
+
+        assert_eq!(skipped.points().len(), 2);
+        assert_eq!(skipped.absorbed_points().len(), 1);
+        assert_eq!(full.absorbed_points().len(), 2);
+        assert_ne!(absorbed_state(&full), absorbed_state(&skipped));
+        assert_eq!(
+            absorbed_state(&skipped),
+            absorbed_state(&SymPoint::Const(x * base))
+        );
+        // the base only changes the transcript when it is absorbed
+        let other_base = RistrettoPoint::random(rng);
+        assert_eq!(
+            absorbed_state(&skipped),
+            absorbed_state(&SkippedBaseInstance::new(other_base, x * base))
+        );
+        assert_ne!(
+            absorbed_state(&full),
+            absorbed_state(&BaseInstance::new(other_base, x * base))
+        );
+
+        let nested = NestedSkippedInstance::new(Scalar::ONE, skipped.clone());
+        assert_eq!(nested.scalars().len(), 1);
+        assert!(nested.absorbed_scalars().is_empty());
+        assert_eq!(nested.absorbed_points().len(), 1);
+
+        assert_eq!(SkippedBaseInstance::get_field_names(), ["G", "point"]);
+
+        // the skipped base is still used by psi, and fixed to G by the verifier
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let instance = SkippedBaseInstance::new(g, x * g);
+        let witness = SymScalar::Const(x);
+        let proof = SkippedBaseSchnorr::prove(&witness, &instance).unwrap();
+        SkippedBaseSchnorr::verify(&instance, &proof).unwrap();
+        let prepared = instance.prepare::<SkippedBaseSchnorr>().unwrap();
+        SkippedBaseSchnorr::verify_prepared(&prepared, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_skip_absorb_forgery() {
+        let rng = &mut rand::rngs::OsRng;
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let instance = SkippedBaseInstance::new(g, RistrettoPoint::random(rng));
+
+        // without the discrete log of the point, pick the commitment and the
+        // response first: the challenge does not depend on the base, which can
+        // then be solved for so that z*base = A + e*point
+        let (big_a, z) = (RistrettoPoint::random(rng), Scalar::random(rng));
+        let forged = [big_a.compress().to_bytes(), z.to_bytes()].concat();
+        let e = SkippedBaseSchnorr::recover_challenge(&instance, &forged).unwrap();
+        let point = instance.point.evaluate().unwrap();
+        let forged_base = z.invert() * (big_a + e * point);
+
+        // so a verifier taking the base from the prover accepts the forgery,
+        // while one fixing it to G rejects it
+        let chosen = SkippedBaseInstance::new(forged_base, point);
+        SkippedBaseSchnorr::verify(&chosen, &forged).unwrap();
+        assert!(matches!(
+            SkippedBaseSchnorr::verify(&instance, &forged),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[derive(SymInstance, Clone)]
+    struct InnerInstance {
+        point1: SymPoint,
+        s: SymScalar,
+    }
+
+    #[derive(SymInstance, Clone)]
+    struct NestedInstance {
+        k: SymScalar,
+        #[sym(nested)]
+        inner: InnerInstance,
+        point2: SymPoint,
+    }
+
+    #[test]
+    fn test_nested_instance_field_names() {
+        let names = NestedInstance::get_field_names();
+        assert_eq!(names, ["k", "inner.s", "inner.point1", "point2"]);
+        assert_eq!(
+            names.len(),
+            NestedInstance::num_scalars().unwrap() + NestedInstance::num_points().unwrap()
+        );
+
+        // the names follow the order of the values
+        let (p, q) = (
+            RistrettoPoint::random(&mut rand::rngs::OsRng),
+            RistrettoPoint::default(),
+        );
+        let instance = NestedInstance::from_values(&scalars(2), &[p, q]).unwrap();
+        assert_eq!(instance.inner.s.evaluate().unwrap(), Scalar::from(2u64));
+        assert_eq!(instance.inner.point1.evaluate().unwrap(), p);
+        let values: Vec<_> = instance
+            .points()
+            .iter()
+            .map(|point| point.evaluate().unwrap())
+            .collect();
+        assert_eq!(values, [p, q]);
+    }
+
+    #[test]
+    fn test_nested_instance_wrong_length() {
+        let points = [RistrettoPoint::default(); 2];
+        for len in 0..2 {
+            assert!(matches!(
+                NestedInstance::from_values(&scalars(len), &points),
+                Err(SigmaProofError::InsufficientScalars)
+            ));
+        }
+        for len in 0..2 {
+            assert!(matches!(
+                NestedInstance::from_values(&scalars(2), &points[..len]),
+                Err(SigmaProofError::InsufficientPoints)
+            ));
+        }
+        for len in 0..4 {
+            assert!(matches!(
+                NestedTupleWitness::from_values(&scalars(len)),
+                Err(SigmaProofError::InsufficientScalars)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_take_values() {
+        let values = scalars(3);
+        let mut cursor = 1;
+        let taken = take_values(
+            &values,
+            &mut cursor,
+            2,
+            SigmaProofError::InsufficientScalars,
+        );
+        assert_eq!(taken.unwrap(), &values[1..]);
+        assert_eq!(cursor, 3);
+
+        // the cursor is left unchanged on failure, even if the end overflows
+        for len in [1, usize::MAX] {
+            assert!(matches!(
+                take_values(
+                    &values,
+                    &mut cursor,
+                    len,
+                    SigmaProofError::InsufficientScalars
+                ),
+                Err(SigmaProofError::InsufficientScalars)
+            ));
+            assert_eq!(cursor, 3);
+        }
+    }
+
+    /// Proves knowledge of `w` such that `target = sum_i w_i * bases_i`, for
+    /// any number of bases.
+    struct VecRelation;
+
+    #[derive(Clone)]
+    struct VecRelationInstance {
+        bases: Vec<SymPoint>,
+        target: SymPoint,
+    }
+
+    impl sealed_instance::Sealed for VecRelationInstance {}
+
+    impl SymInstance for VecRelationInstance {
+        fn num_scalars() -> Option<usize> {
+            Some(0)
+        }
+
+        fn num_points() -> Option<usize> {
+            None
+        }
+
+        fn from_values(_scalars: &[Scalar], points: &[RistrettoPoint]) -> SigmaProofResult<Self> {
+            let (target, bases) = points
+                .split_last()
+                .ok_or(SigmaProofError::InsufficientPoints)?;
+            Ok(Self {
+                bases: bases.iter().map(|p| SymPoint::Const(*p)).collect(),
+                target: SymPoint::Const(*target),
+            })
+        }
+
+        fn get_field_names() -> Vec<String> {
+            vec!["target".to_string()]
+        }
+
+        fn points(&self) -> Vec<SymPoint> {
+            let mut points = self.bases.clone();
+            points.push(self.target.clone());
+            points
+        }
+
+        fn scalars(&self) -> Vec<SymScalar> {
+            vec![]
+        }
+    }
+
+    impl SigmaProof for VecRelation {
+        const LABEL: &'static [u8] = b"vec-relation-protocol";
+
+        type WITNESS = VecWitness;
+        type INSTANCE = VecRelationInstance;
+
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![instance.target.clone()]
+        }
+
+        fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            let sum = witness
+                .0
+                .iter()
+                .zip(&instance.bases)
+                .map(|(w, base)| w * base.clone())
+                .reduce(|acc, term| acc + term)
+                .unwrap_or(SymPoint::Identity);
+            vec![sum]
+        }
+
+        fn num_responses(instance: &Self::INSTANCE) -> SigmaProofResult<usize> {
+            Ok(instance.bases.len())
+        }
+    }
+
+    #[test]
+    fn test_vec_witness() {
+        let rng = &mut rand::rngs::OsRng;
+        for len in [0, 1, 5] {
+            let values: Vec<Scalar> = (0..len).map(|_| Scalar::random(rng)).collect();
+            let witness = VecWitness::from_values(&values).unwrap();
+            assert_eq!(witness.scalar_len(), len);
+            assert_eq!(witness.values().unwrap(), values);
+            assert_eq!(witness.rand_like(rng).scalar_len(), len);
+
+            let bases: Vec<RistrettoPoint> =
+                (0..len).map(|_| RistrettoPoint::random(rng)).collect();
+            let target: RistrettoPoint = values.iter().zip(&bases).map(|(w, b)| w * b).sum();
+            let instance = VecRelationInstance {
+                bases: bases.iter().map(|b| SymPoint::Const(*b)).collect(),
+                target: SymPoint::Const(target),
+            };
+
+            let proof = VecRelation::prove(&witness, &instance).unwrap();
+            assert_eq!(proof.len(), 32 * (1 + len));
+            VecRelation::verify(&instance, &proof).unwrap();
+
+            let compact = VecRelation::prove_compact(&witness, &instance).unwrap();
+            VecRelation::verify_compact(&instance, &compact).unwrap();
+
+            // a proof for one more base than the instance has is rejected
+            let mut long = proof.clone();
+            long.extend_from_slice(Scalar::ONE.as_bytes());
+            assert!(VecRelation::verify(&instance, &long).is_err());
+        }
+    }
+
+    /// [`VecRelation`] without its `num_responses` override.
+    struct UncountedVecRelation;
+
+    impl SigmaProof for UncountedVecRelation {
+        const LABEL: &'static [u8] = b"uncounted-vec-relation-protocol";
+
+        type WITNESS = VecWitness;
+        type INSTANCE = VecRelationInstance;
+
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            VecRelation::f(instance)
+        }
+
+        fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            VecRelation::psi(witness, instance)
+        }
+    }
+
+    #[test]
+    fn test_vec_witness_requires_num_responses() {
+        let rng = &mut rand::rngs::OsRng;
+        let instance = VecRelationInstance {
+            bases: vec![SymPoint::Const(RistrettoPoint::random(rng))],
+            target: SymPoint::Const(RistrettoPoint::random(rng)),
+        };
+        assert!(matches!(
+            UncountedVecRelation::num_responses(&instance),
+            Err(SigmaProofError::UnknownResponseCount)
+        ));
+        assert!(matches!(
+            UncountedVecRelation::verify(&instance, &[0u8; 64]),
+            Err(SigmaProofError::UnknownResponseCount)
+        ));
+    }
+
+    #[derive(SymWitness, Clone)]
+    struct UnitWitness;
+
+    #[derive(SymInstance, Clone)]
+    struct UnitInstance;
+
+    #[derive(SymInstance, Clone)]
+    struct PointInstance(SymPoint);
+
+    /// Proves knowledge of the discrete log of `H`, a statement without an
+    /// instance.
+    struct DlogOfH;
+
+    impl SigmaProof for DlogOfH {
+        const LABEL: &'static [u8] = b"dlog-of-h-protocol";
+
+        type WITNESS = [SymScalar; 1];
+        type INSTANCE = UnitInstance;
+
+        fn f(_instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![crate::sigmas::H.clone()]
+        }
+
+        fn psi(witness: &Self::WITNESS, _instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![witness[0].clone() * crate::sigmas::G]
+        }
+    }
+
+    /// Checks that `point` is the identity, a statement without a witness.
+    struct IsIdentity;
+
+    impl SigmaProof for IsIdentity {
+        const LABEL: &'static [u8] = b"is-identity-protocol";
+
+        type WITNESS = UnitWitness;
+        type INSTANCE = PointInstance;
+
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![instance.0.clone()]
+        }
+
+        fn psi(_witness: &Self::WITNESS, _instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![SymPoint::Identity]
+        }
+    }
+
+    /// A statement without equations.
+    struct Vacuous;
+
+    impl SigmaProof for Vacuous {
+        const LABEL: &'static [u8] = b"vacuous-protocol";
+
+        type WITNESS = UnitWitness;
+        type INSTANCE = UnitInstance;
+
+        fn f(_instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![]
+        }
+
+        fn psi(_witness: &Self::WITNESS, _instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![]
+        }
+    }
+
+    #[test]
+    fn test_unit_instance() {
+        assert_eq!(UnitInstance::num_scalars(), Some(0));
+        assert_eq!(UnitInstance::num_points(), Some(0));
+        assert!(UnitInstance::get_field_names().is_empty());
+        assert!(UnitInstance::from_values(&[], &[]).is_ok());
+
+        // nobody knows the discrete log of H, so only prove the statement with
+        // the wrong witness
+        let witness = [SymScalar::Const(Scalar::random(&mut rand::rngs::OsRng))];
+        let proof = DlogOfH::prove(&witness, &UnitInstance).unwrap();
+        assert_eq!(proof.len(), 32 * 2);
+        assert!(matches!(
+            DlogOfH::verify(&UnitInstance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_unit_witness() {
+        assert_eq!(UnitWitness::num_scalars(), Some(0));
+        assert!(UnitWitness::from_values(&[])
+            .unwrap()
+            .values()
+            .unwrap()
+            .is_empty());
+
+        // the proof is the identity commitment alone
+        let identity = PointInstance(SymPoint::Identity);
+        let proof = IsIdentity::prove(&UnitWitness, &identity).unwrap();
+        assert_eq!(proof.len(), 32);
+        IsIdentity::verify(&identity, &proof).unwrap();
+
+        let other = PointInstance::new(RistrettoPoint::random(&mut rand::rngs::OsRng));
+        assert!(IsIdentity::verify(&other, &proof).is_err());
+    }
+
+    #[test]
+    fn test_empty_statement() {
+        assert!(matches!(
+            Vacuous::prove(&UnitWitness, &UnitInstance),
+            Err(SigmaProofError::EmptyStatement)
+        ));
+        assert!(matches!(
+            Vacuous::prove_compact(&UnitWitness, &UnitInstance),
+            Err(SigmaProofError::EmptyStatement)
+        ));
+        for verify in [
+            Vacuous::verify,
+            Vacuous::verify_streaming,
+            Vacuous::verify_compact,
+        ] {
+            assert!(matches!(
+                verify(&UnitInstance, &[]),
+                Err(SigmaProofError::EmptyStatement)
+            ));
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_zeroize_nested_witness() {
+        let mut witness = NestedWitness::from_values(&scalars(4)).unwrap();
+        witness.inner.a = &witness.inner.a * SymScalar::Const(Scalar::from(3u64));
+        SymWitness::zeroize(&mut witness);
+        assert!(witness.values().unwrap().iter().all(|s| *s == Scalar::ZERO));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_zeroize_on_drop() {
+        use core::cell::RefCell;
+        use zeroize::Zeroizing;
+
+        // records the scalars left in the witness once `Zeroizing` is done with it
+        struct Probe<'a> {
+            witness: NestedWitness,
+            seen: &'a RefCell<Vec<Scalar>>,
+        }
+
+        impl zeroize::Zeroize for Probe<'_> {
+            fn zeroize(&mut self) {
+                zeroize::Zeroize::zeroize(&mut self.witness);
+                *self.seen.borrow_mut() = self.witness.values().unwrap();
+            }
+        }
+
+        let seen = RefCell::new(Vec::new());
+        let probe = Zeroizing::new(Probe {
+            witness: NestedWitness::from_values(&scalars(4)).unwrap(),
+            seen: &seen,
+        });
+        drop(probe);
+
+        let seen = seen.into_inner();
+        assert_eq!(seen.len(), 4);
+        assert!(seen.iter().all(|s| *s == Scalar::ZERO));
+    }
+}
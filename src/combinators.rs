@@ -0,0 +1,423 @@
+//! Protocols built out of other protocols.
+
+use core::marker::PhantomData;
+
+use alloc::{borrow::Cow, format, string::String, vec, vec::Vec};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use rand_core::CryptoRngCore;
+
+use crate::{
+    absorb::{
+        sealed_instance, sealed_witness, FixedLenInstance, FixedLenWitness, SymInstance, SymPoint,
+        SymScalar, SymWitness,
+    },
+    compiler::SigmaProof,
+    errors::{SigmaProofError, SigmaProofResult},
+};
+
+/// Proves that the prover knows a witness for `L` or for `R`, without
+/// revealing which.
+///
+/// This is the disjunction of Cramer, Damgård and Schoenmakers, written as a
+/// single linear relation so that it can be compiled like any other protocol.
+/// Its witness is `(c, w_L, w_R)` and its equations are
+///
+/// ```text
+/// psi_L(w_L) + c*X_L = X_L
+/// psi_R(w_R) - c*X_R = 0
+/// ```
+///
+/// A prover knowing `w_L` uses `c = 0` and `w_R = 0`, one knowing `w_R` uses
+/// `c = 1` and `w_L = 0`. In the proof, the response for `c` is the share of
+/// the challenge answered by the right branch. Conversely, from any solution
+/// either `c != 1` and `w_L / (1 - c)` is a witness for `L`, or `w_R` is a
+/// witness for `R`.
+///
+/// This requires `psi` of both branches to be linear in the witness, with no
+/// constant terms, and both witnesses to have a fixed length.
+pub struct SigmaOr<L, R>(PhantomData<(L, R)>);
+
+#[derive(Clone)]
+pub struct OrWitness<L, R> {
+    choice: SymScalar,
+    left: L,
+    right: R,
+}
+
+impl<L: FixedLenWitness, R: FixedLenWitness> OrWitness<L, R> {
+    /// A witness for the left branch.
+    pub fn left(witness: L) -> SigmaProofResult<Self> {
+        Ok(Self {
+            choice: SymScalar::Const(Scalar::ZERO),
+            left: witness,
+            right: R::from_values(&vec![Scalar::ZERO; R::NUM_SCALARS])?,
+        })
+    }
+
+    /// A witness for the right branch.
+    pub fn right(witness: R) -> SigmaProofResult<Self> {
+        Ok(Self {
+            choice: SymScalar::Const(Scalar::ONE),
+            left: L::from_values(&vec![Scalar::ZERO; L::NUM_SCALARS])?,
+            right: witness,
+        })
+    }
+
+    /// The choice `c`: zero for a witness of the left branch, one for the
+    /// right.
+    pub(crate) fn choice(&self) -> &SymScalar {
+        &self.choice
+    }
+}
+
+impl<L: FixedLenWitness, R: FixedLenWitness> sealed_witness::Sealed for OrWitness<L, R> {}
+
+impl<L: FixedLenWitness, R: FixedLenWitness> SymWitness for OrWitness<L, R> {
+    fn values(&self) -> SigmaProofResult<Vec<Scalar>> {
+        let mut values = self.choice.values()?;
+        values.extend(self.left.values()?);
+        values.extend(self.right.values()?);
+        Ok(values)
+    }
+
+    /// Expects `c`, followed by the scalars of the left and right witnesses.
+    fn from_values(scalars: &[Scalar]) -> SigmaProofResult<Self> {
+        let expected = Self::NUM_SCALARS;
+        if scalars.len() < expected {
+            return Err(SigmaProofError::InsufficientScalars);
+        }
+        if scalars.len() > expected {
+            return Err(SigmaProofError::TooManyScalars {
+                expected,
+                actual: scalars.len(),
+            });
+        }
+        let (left, right) = scalars[1..].split_at(L::NUM_SCALARS);
+        Ok(Self {
+            choice: SymWitness::from_values(&scalars[..1])?,
+            left: L::from_values(left)?,
+            right: R::from_values(right)?,
+        })
+    }
+
+    fn num_scalars() -> Option<usize> {
+        Some(Self::NUM_SCALARS)
+    }
+
+    fn get_var_name(index: usize) -> Cow<'static, str> {
+        let left_len = L::NUM_SCALARS;
+        match index {
+            0 => "c".into(),
+            i if i <= left_len => L::get_var_name(i - 1),
+            i => R::get_var_name(i - 1 - left_len),
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    fn zeroize(&mut self) {
+        SymWitness::zeroize(&mut self.choice);
+        SymWitness::zeroize(&mut self.left);
+        SymWitness::zeroize(&mut self.right);
+    }
+
+    fn scalar_len(&self) -> usize {
+        Self::NUM_SCALARS
+    }
+
+    fn rand_like<Rng: CryptoRngCore + ?Sized>(&self, rng: &mut Rng) -> Self {
+        Self::rand(rng)
+    }
+}
+
+impl<L: FixedLenWitness, R: FixedLenWitness> FixedLenWitness for OrWitness<L, R> {
+    const NUM_SCALARS: usize = 1 + L::NUM_SCALARS + R::NUM_SCALARS;
+
+    fn rand<Rng: CryptoRngCore + ?Sized>(rng: &mut Rng) -> Self {
+        Self {
+            choice: SymScalar::rand(rng),
+            left: L::rand(rng),
+            right: R::rand(rng),
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<L: FixedLenWitness, R: FixedLenWitness> zeroize::Zeroize for OrWitness<L, R> {
+    fn zeroize(&mut self) {
+        SymWitness::zeroize(self);
+    }
+}
+
+/// The instances of two protocols, as the instance of a combination of them.
+#[derive(Clone)]
+pub struct PairInstance<L, R> {
+    left: L,
+    right: R,
+}
+
+pub type OrInstance<L, R> = PairInstance<L, R>;
+pub type AndInstance<L, R> = PairInstance<L, R>;
+
+impl<L: FixedLenInstance, R: SymInstance> PairInstance<L, R> {
+    pub fn new(left: L, right: R) -> Self {
+        Self { left, right }
+    }
+}
+
+impl<L: FixedLenInstance, R: SymInstance> sealed_instance::Sealed for PairInstance<L, R> {}
+
+impl<L: FixedLenInstance, R: SymInstance> SymInstance for PairInstance<L, R> {
+    fn num_scalars() -> Option<usize> {
+        L::NUM_SCALARS.checked_add(R::num_scalars()?)
+    }
+
+    fn num_points() -> Option<usize> {
+        L::NUM_POINTS.checked_add(R::num_points()?)
+    }
+
+    /// Expects the scalars and points of the left instance first. Only the
+    /// right instance may be of runtime length.
+    fn from_values(scalars: &[Scalar], points: &[RistrettoPoint]) -> SigmaProofResult<Self> {
+        if scalars.len() < L::NUM_SCALARS {
+            return Err(SigmaProofError::InsufficientScalars);
+        }
+        if points.len() < L::NUM_POINTS {
+            return Err(SigmaProofError::InsufficientPoints);
+        }
+        let (left_scalars, right_scalars) = scalars.split_at(L::NUM_SCALARS);
+        let (left_points, right_points) = points.split_at(L::NUM_POINTS);
+        Ok(Self {
+            left: L::from_values(left_scalars, left_points)?,
+            right: R::from_values(right_scalars, right_points)?,
+        })
+    }
+
+    fn get_field_names() -> Vec<String> {
+        let left = L::get_field_names();
+        let right = R::get_field_names();
+        let (left_scalars, left_points) = left.split_at(L::NUM_SCALARS);
+        // the names of a right instance of runtime length are all listed
+        // with the scalars
+        let (right_scalars, right_points) =
+            right.split_at(R::num_scalars().unwrap_or(right.len()).min(right.len()));
+
+        let qualify = |prefix, names: &[String]| -> Vec<String> {
+            names.iter().map(|n| format!("{prefix}.{n}")).collect()
+        };
+        [
+            qualify("left", left_scalars),
+            qualify("right", right_scalars),
+            qualify("left", left_points),
+            qualify("right", right_points),
+        ]
+        .concat()
+    }
+
+    fn points(&self) -> Vec<SymPoint> {
+        let mut points = self.left.points();
+        points.extend(self.right.points());
+        points
+    }
+
+    fn scalars(&self) -> Vec<SymScalar> {
+        let mut scalars = self.left.scalars();
+        scalars.extend(self.right.scalars());
+        scalars
+    }
+
+    fn absorbed_points(&self) -> Vec<SymPoint> {
+        let mut points = self.left.absorbed_points();
+        points.extend(self.right.absorbed_points());
+        points
+    }
+
+    fn absorbed_scalars(&self) -> Vec<SymScalar> {
+        let mut scalars = self.left.absorbed_scalars();
+        scalars.extend(self.right.absorbed_scalars());
+        scalars
+    }
+}
+
+impl<L: FixedLenInstance, R: FixedLenInstance> FixedLenInstance for PairInstance<L, R> {
+    const NUM_SCALARS: usize = L::NUM_SCALARS + R::NUM_SCALARS;
+    const NUM_POINTS: usize = L::NUM_POINTS + R::NUM_POINTS;
+}
+
+impl<L, R> SigmaProof for SigmaOr<L, R>
+where
+    L: SigmaProof,
+    R: SigmaProof,
+    L::WITNESS: FixedLenWitness,
+    R::WITNESS: FixedLenWitness,
+    L::INSTANCE: FixedLenInstance,
+{
+    const LABEL: &'static [u8] = b"sigma-or";
+
+    type WITNESS = OrWitness<L::WITNESS, R::WITNESS>;
+    type INSTANCE = OrInstance<L::INSTANCE, R::INSTANCE>;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let mut f = L::f(&instance.left);
+        f.extend(vec![SymPoint::Identity; R::f(&instance.right).len()]);
+        f
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let c = &witness.choice;
+        let left = L::psi(&witness.left, &instance.left)
+            .into_iter()
+            .zip(L::f(&instance.left))
+            .map(|(psi_i, x_i)| psi_i + c * x_i);
+        let right = R::psi(&witness.right, &instance.right)
+            .into_iter()
+            .zip(R::f(&instance.right))
+            .map(|(psi_i, x_i)| psi_i - c * x_i);
+        left.chain(right).collect()
+    }
+}
+
+/// Proves two statements about the same witness, e.g. Chaum-Pedersen as
+/// `x*G = X` and `x*H = Y` for one `x`, under a single challenge.
+///
+/// Unlike proving `L` and `R` separately, the same responses answer both
+/// statements, so the proof shows that one witness satisfies both.
+pub struct SameWitnessAnd<L, R>(PhantomData<(L, R)>);
+
+impl<L, R> SigmaProof for SameWitnessAnd<L, R>
+where
+    L: SigmaProof,
+    R: SigmaProof<WITNESS = L::WITNESS>,
+    L::INSTANCE: FixedLenInstance,
+{
+    const LABEL: &'static [u8] = b"same-witness-and";
+
+    type WITNESS = L::WITNESS;
+    type INSTANCE = AndInstance<L::INSTANCE, R::INSTANCE>;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let mut f = L::f(&instance.left);
+        f.extend(R::f(&instance.right));
+        f
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let mut psi = L::psi(witness, &instance.left);
+        psi.extend(R::psi(witness, &instance.right));
+        psi
+    }
+
+    fn num_responses(instance: &Self::INSTANCE) -> SigmaProofResult<usize> {
+        L::num_responses(&instance.left)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sigmas::{
+        schnorr::{GenericSchnorr, GenericSchnorrInstance, SchnorrWitness},
+        G, H,
+    };
+
+    type SchnorrOr = SigmaOr<GenericSchnorr, GenericSchnorr>;
+
+    fn schnorr(base: RistrettoPoint, sk: Scalar) -> (SchnorrWitness, GenericSchnorrInstance) {
+        (
+            SchnorrWitness::from_values(&[sk]).unwrap(),
+            GenericSchnorrInstance::from_values(&[], &[base, sk * base]).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_sigma_or_either_branch() {
+        let rng = &mut rand::rngs::OsRng;
+        let (g, h) = (G.evaluate().unwrap(), H.evaluate().unwrap());
+        let (left_witness, left) = schnorr(g, Scalar::random(rng));
+        let (right_witness, right) = schnorr(h, Scalar::random(rng));
+        let instance = OrInstance::new(left, right);
+
+        let witness = OrWitness::left(left_witness).unwrap();
+        let proof = SchnorrOr::prove(&witness, &instance).unwrap();
+        SchnorrOr::verify(&instance, &proof).unwrap();
+
+        let witness = OrWitness::right(right_witness).unwrap();
+        let proof = SchnorrOr::prove(&witness, &instance).unwrap();
+        SchnorrOr::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_sigma_or_no_branch() {
+        let rng = &mut rand::rngs::OsRng;
+        let (g, h) = (G.evaluate().unwrap(), H.evaluate().unwrap());
+        let (left_witness, _) = schnorr(g, Scalar::random(rng));
+        let (right_witness, _) = schnorr(h, Scalar::random(rng));
+        let (_, left) = schnorr(g, Scalar::random(rng));
+        let (_, right) = schnorr(h, Scalar::random(rng));
+        let instance = OrInstance::new(left, right);
+
+        let witness = OrWitness::left(left_witness).unwrap();
+        let proof = SchnorrOr::prove(&witness, &instance).unwrap();
+        assert!(SchnorrOr::verify(&instance, &proof).is_err());
+
+        let witness = OrWitness::right(right_witness).unwrap();
+        let proof = SchnorrOr::prove(&witness, &instance).unwrap();
+        assert!(SchnorrOr::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_sigma_or_spec_generation() {
+        let spec = SchnorrOr::spec();
+        println!("{spec}");
+    }
+
+    type SchnorrAnd = SameWitnessAnd<GenericSchnorr, GenericSchnorr>;
+
+    #[test]
+    fn test_same_witness_and_as_chaum() {
+        use crate::sigmas::chaum::{Chaum, ChaumInstance, ChaumWitness};
+
+        let rng = &mut rand::rngs::OsRng;
+        let x = Scalar::random(rng);
+        let (g, h) = (G.evaluate().unwrap(), H.evaluate().unwrap());
+        let (witness, over_g) = schnorr(g, x);
+        let (_, over_h) = schnorr(h, x);
+        let instance = AndInstance::new(over_g, over_h);
+
+        let proof = SchnorrAnd::prove(&witness, &instance).unwrap();
+        assert_eq!(proof.len(), 32 * 3);
+        SchnorrAnd::verify(&instance, &proof).unwrap();
+
+        // the same statement as Chaum
+        let chaum = ChaumInstance::new(x * g, x * h);
+        let chaum_witness = ChaumWitness::from_values(&[x]).unwrap();
+        let evaluate = |points: Vec<SymPoint>| -> Vec<RistrettoPoint> {
+            points.iter().map(|p| p.evaluate().unwrap()).collect()
+        };
+        assert_eq!(
+            evaluate(SchnorrAnd::f(&instance)),
+            evaluate(Chaum::f(&chaum))
+        );
+        assert_eq!(
+            evaluate(SchnorrAnd::psi(&witness, &instance)),
+            evaluate(Chaum::psi(&chaum_witness, &chaum))
+        );
+    }
+
+    #[test]
+    fn test_same_witness_and_different_witnesses() {
+        let rng = &mut rand::rngs::OsRng;
+        let (g, h) = (G.evaluate().unwrap(), H.evaluate().unwrap());
+        let (witness, over_g) = schnorr(g, Scalar::random(rng));
+        let (other_witness, over_h) = schnorr(h, Scalar::random(rng));
+        let instance = AndInstance::new(over_g, over_h);
+
+        // each statement holds, but not for the same witness
+        for witness in [witness, other_witness] {
+            let proof = SchnorrAnd::prove(&witness, &instance).unwrap();
+            assert!(matches!(
+                SchnorrAnd::verify(&instance, &proof),
+                Err(SigmaProofError::EquationCheckFailed)
+            ));
+        }
+    }
+}
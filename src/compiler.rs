@@ -1,10 +1,22 @@
 use crate::{
-    absorb::{SymInstance, SymWitness},
+    absorb::{SymInstance, SymWitness, RUNTIME_SAMPLE_LEN},
     equations::{SymPoint, SymScalar},
     errors::{SigmaProofError, SigmaProofResult},
-    transcript::ProofTranscript,
+    statement::Statement,
+    transcript::{mask_top_byte, ProofTranscript, TranscriptBackend, TRANSCRIPT_VERSION},
+    wire::{point_from_wire, point_to_wire, scalar_from_wire, scalar_to_wire, Endianness},
 };
-use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, RistrettoPoint, Scalar};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, traits::Identity, RistrettoPoint, Scalar,
+};
+use rand_core::CryptoRngCore;
+use subtle::{Choice, ConstantTimeEq};
 
 /// Escape a variable name for LaTeX and wrap in texttt
 fn latex_var(name: &str) -> String {
@@ -12,329 +24,1600 @@ fn latex_var(name: &str) -> String {
     format!("\\texttt{{{}}}", escaped)
 }
 
-/// Convert a SymPoint expression to LaTeX notation with context
-fn sympoint_to_latex_with_context(
-    point: &SymPoint,
-    var_names: &[&str],
-    is_instance: bool,
-) -> String {
-    match point {
-        SymPoint::WellKnownConst(name, _) => name.to_string(),
-        SymPoint::Const(p) => {
-            if *p == RISTRETTO_BASEPOINT_POINT {
-                // The base point G is always G unless we're in a specific context
-                "G".to_string()
-            } else {
-                panic!("use SymPoint::WellKnownConst instead for known constants");
-            }
+/// Renders the expressions of a [`Statement`] in LaTeX.
+///
+/// Witness variables, instance fields and generators are rendered by name.
+/// Constant points that are none of these are numbered `P_1`, `P_2`, ... in
+/// order of first appearance in `psi` then `f`.
+struct LatexContext<'a> {
+    statement: &'a Statement,
+    anonymous_points: Vec<RistrettoPoint>,
+}
+
+impl<'a> LatexContext<'a> {
+    fn new(statement: &'a Statement) -> Self {
+        let mut generators = Vec::new();
+        for point in statement.psi.iter().chain(&statement.f) {
+            point.collect_generators_into(&mut generators);
         }
-        SymPoint::Var(Some(_)) => "??".to_string(), // Variable point
-        SymPoint::Var(None) => "?".to_string(),     // Uninstantiated variable point
-        SymPoint::Add(p1, p2) => {
-            format!(
-                "({} + {})",
-                sympoint_to_latex_with_context(p1, var_names, is_instance),
-                sympoint_to_latex_with_context(p2, var_names, is_instance)
-            )
+        let anonymous_points = generators
+            .into_iter()
+            .filter(|(name, point)| {
+                name.is_none()
+                    && *point != RISTRETTO_BASEPOINT_POINT
+                    && !statement.instance_points.iter().any(|(p, _)| p == point)
+            })
+            .map(|(_, point)| point)
+            .collect();
+        Self {
+            statement,
+            anonymous_points,
         }
-        SymPoint::Sub(p1, p2) => {
-            format!(
-                "({} - {})",
-                sympoint_to_latex_with_context(p1, var_names, is_instance),
-                sympoint_to_latex_with_context(p2, var_names, is_instance)
-            )
+    }
+
+    fn point(&self, point: &SymPoint) -> String {
+        match point {
+            SymPoint::Identity => "\\mathcal{O}".to_string(),
+            SymPoint::WellKnownConst(name, _) => name.to_string(),
+            SymPoint::Const(p) | SymPoint::Var(Some(p)) => self.constant_point(p),
+            SymPoint::Var(None) => "?".to_string(), // Uninstantiated variable point
+            SymPoint::Add(p1, p2) => format!("({} + {})", self.point(p1), self.point(p2)),
+            SymPoint::Sub(p1, p2) => format!("({} - {})", self.point(p1), self.point(p2)),
+            SymPoint::Neg(p) => format!("(-{})", self.point(p)),
+            SymPoint::Scale(s, p) => format!("{} \\cdot {}", self.scalar(s), self.point(p)),
         }
-        SymPoint::Neg(p) => {
-            format!(
-                "(-{})",
-                sympoint_to_latex_with_context(p, var_names, is_instance)
-            )
+    }
+
+    /// An instance point by name, the base point as `G`, and any other point
+    /// by its index among the anonymous points.
+    fn constant_point(&self, p: &RistrettoPoint) -> String {
+        if let Some((_, name)) = self.statement.instance_points.iter().find(|(q, _)| q == p) {
+            latex_var(name)
+        } else if *p == RISTRETTO_BASEPOINT_POINT {
+            "G".to_string()
+        } else {
+            match self.anonymous_points.iter().position(|q| q == p) {
+                Some(i) => format!("P_{{{}}}", i + 1),
+                None => "P".to_string(),
+            }
         }
-        SymPoint::Scale(s, p) => {
-            // Check if p is one of our dummy instance points
-            let point_str = match p.as_ref() {
-                SymPoint::Const(pt) if *pt == Scalar::from(2u64) * RISTRETTO_BASEPOINT_POINT => {
-                    latex_var("pubkey")
-                }
-                SymPoint::Const(pt) if *pt == Scalar::from(3u64) * RISTRETTO_BASEPOINT_POINT => {
-                    latex_var("commitment")
-                }
-                SymPoint::Const(pt) if *pt == Scalar::from(4u64) * RISTRETTO_BASEPOINT_POINT => {
-                    latex_var("handle")
+    }
+
+    fn scalar(&self, scalar: &SymScalar) -> String {
+        match scalar {
+            SymScalar::Const(s) => {
+                // Try to match against instance scalars, then common small values
+                if let Some((_, name)) =
+                    self.statement.instance_scalars.iter().find(|(t, _)| t == s)
+                {
+                    latex_var(name)
+                } else if let Some(small) = (1..=5u64).find(|i| Scalar::from(*i) == *s) {
+                    small.to_string()
+                } else {
+                    "c".to_string() // Some constant
                 }
-                _ => sympoint_to_latex_with_context(p, var_names, is_instance),
-            };
-            format!("{} \\cdot {}", symscalar_to_latex(s, var_names), point_str)
-        }
-    }
-}
-
-/// Convert a SymPoint expression to LaTeX notation (wrapper for backwards compatibility)
-fn sympoint_to_latex(point: &SymPoint, var_names: &[&str]) -> String {
-    sympoint_to_latex_with_context(point, var_names, false)
-}
-
-/// Convert a SymScalar expression to LaTeX notation
-fn symscalar_to_latex(scalar: &SymScalar, var_names: &[&str]) -> String {
-    match scalar {
-        SymScalar::Const(s) => {
-            // Try to match against common small values
-            if *s == Scalar::from(1u64) {
-                "1".to_string()
-            } else if *s == Scalar::from(2u64) {
-                "2".to_string()
-            } else if *s == Scalar::from(3u64) {
-                "3".to_string()
-            } else if *s == Scalar::from(4u64) {
-                "4".to_string()
-            } else if *s == Scalar::from(5u64) {
-                "5".to_string()
-            } else {
-                "c".to_string() // Some constant
             }
-        }
-        SymScalar::Var(Some(s)) => {
-            // Try to match against dummy values 1, 2, 3, etc.
-            if *s == Scalar::from(1u64) && !var_names.is_empty() {
-                latex_var(var_names[0])
-            } else if *s == Scalar::from(2u64) && var_names.len() > 1 {
-                latex_var(var_names[1])
-            } else if *s == Scalar::from(3u64) && var_names.len() > 2 {
-                latex_var(var_names[2])
-            } else if *s == Scalar::from(4u64) && var_names.len() > 3 {
-                latex_var(var_names[3])
-            } else if *s == Scalar::from(5u64) && var_names.len() > 4 {
-                latex_var(var_names[4])
-            } else {
-                "v".to_string() // Some variable
+            SymScalar::Var(Some(s)) => {
+                // Try to match against dummy values 1, 2, 3, etc.
+                self.statement
+                    .witness
+                    .iter()
+                    .enumerate()
+                    .find(|(i, _)| Scalar::from((i + 1) as u64) == *s)
+                    .map(|(_, name)| latex_var(name))
+                    .unwrap_or_else(|| "v".to_string()) // Some variable
+            }
+            SymScalar::Var(None) => "?".to_string(), // Uninstantiated
+            SymScalar::Add(s1, s2) => format!("({} + {})", self.scalar(s1), self.scalar(s2)),
+            SymScalar::Sub(s1, s2) => format!("({} - {})", self.scalar(s1), self.scalar(s2)),
+            SymScalar::Neg(s) => format!("(-{})", self.scalar(s)),
+            SymScalar::Mul(s1, s2) => {
+                format!("({} \\cdot {})", self.scalar(s1), self.scalar(s2))
             }
+            SymScalar::Inv(s) => format!("\\frac{{1}}{{{}}}", self.scalar(s)),
+            SymScalar::Pow(s, exp) => format!("{}^{{{exp}}}", self.scalar(s)),
         }
-        SymScalar::Var(None) => "?".to_string(), // Uninstantiated
-        SymScalar::Add(s1, s2) => {
-            format!(
-                "({} + {})",
-                symscalar_to_latex(s1, var_names),
-                symscalar_to_latex(s2, var_names)
-            )
+    }
+}
+
+/// Starts the transcript of a proof, as the prover if `proof` is `None` and
+/// as the verifier of `proof` otherwise, and absorbs what both absorb before
+/// the first message of the prover: the `context`, then the instance.
+///
+/// Every prover and verifier starts with this, so that they cannot disagree
+/// on the order or the labels of these values.
+pub(crate) fn start_transcript<I: SymInstance, B: TranscriptBackend>(
+    label: &'static [u8],
+    proof: Option<&[u8]>,
+    context: &[u8],
+    instance: &I,
+) -> SigmaProofResult<ProofTranscript<B>> {
+    start_transcript_with(label, proof, context, |transcript| {
+        absorb_instance(transcript, instance)
+    })
+}
+
+/// Like [`start_transcript`], with the instance absorbed by `absorb`.
+fn start_transcript_with<B: TranscriptBackend>(
+    label: &'static [u8],
+    proof: Option<&[u8]>,
+    context: &[u8],
+    absorb: impl FnOnce(&mut ProofTranscript<B>) -> SigmaProofResult<()>,
+) -> SigmaProofResult<ProofTranscript<B>> {
+    let mut transcript = match proof {
+        None => ProofTranscript::prover(label),
+        Some(proof) => ProofTranscript::verifier(label, proof),
+    };
+    transcript.common_absorb_bytes(b"context", context);
+
+    // absorb instance, not f(instance)
+    absorb(&mut transcript)?;
+    Ok(transcript)
+}
+
+/// Absorbs the instance (not f(instance)) into the transcript: points first,
+/// then scalars.
+fn absorb_instance<I: SymInstance, B: TranscriptBackend>(
+    transcript: &mut ProofTranscript<B>,
+    instance: &I,
+) -> SigmaProofResult<()> {
+    let points = instance
+        .absorbed_points()
+        .iter()
+        .map(|point| Ok(point_to_wire(&point.evaluate()?)))
+        .collect::<SigmaProofResult<Vec<_>>>()?;
+    let scalars = instance
+        .absorbed_scalars()
+        .iter()
+        .map(SymScalar::evaluate)
+        .collect::<SigmaProofResult<Vec<_>>>()?;
+    absorb_instance_encoding(transcript, &points, &scalars);
+    Ok(())
+}
+
+/// Absorbs an instance from its compressed points and its scalars, for
+/// [`absorb_instance`] and [`PreparedInstance`] alike.
+fn absorb_instance_encoding<B: TranscriptBackend>(
+    transcript: &mut ProofTranscript<B>,
+    points: &[[u8; 32]],
+    scalars: &[Scalar],
+) {
+    for point in points {
+        transcript.common_absorb_bytes(b"", point);
+    }
+    for scalar in scalars {
+        transcript.common_absorb_scalar(b"", scalar);
+    }
+}
+
+/// Checks that `proof` holds exactly `num_commitments` points followed by
+/// `num_responses` scalars.
+pub(crate) fn check_proof_shape(
+    proof: &[u8],
+    num_commitments: usize,
+    num_responses: usize,
+) -> SigmaProofResult<()> {
+    if proof.len() % 32 != 0 {
+        return Err(SigmaProofError::ProofLengthInvalid { len: proof.len() });
+    }
+    // compared in chunks of 32 bytes, which cannot overflow
+    let chunks = proof.len() / 32;
+    if chunks < num_commitments {
+        return Err(SigmaProofError::MissingCommitments);
+    }
+    if chunks - num_commitments < num_responses {
+        return Err(SigmaProofError::MissingResponses);
+    }
+    if chunks - num_commitments > num_responses {
+        return Err(SigmaProofError::ProofLengthInvalid { len: proof.len() });
+    }
+    Ok(())
+}
+
+/// Absorbs what is bound to the challenge after the commitments: the
+/// `message` of a signature, if any, then [`SigmaProof::bind_extra`].
+fn bind_challenge<P: SigmaProof + ?Sized, B: TranscriptBackend>(
+    transcript: &mut ProofTranscript<B>,
+    message: Option<&[u8]>,
+) {
+    if let Some(message) = message {
+        transcript.common_absorb_bytes(b"message", message);
+    }
+    P::bind_extra(transcript);
+}
+
+/// Draws the challenge of `P` from the transcript.
+fn draw_challenge<P: SigmaProof + ?Sized, B: TranscriptBackend>(
+    transcript: &mut ProofTranscript<B>,
+) -> Scalar {
+    transcript.challenge_bits(P::CHALLENGE_LABEL, P::CHALLENGE_BITS)
+}
+
+/// The length of the challenge of `P` in a compact proof.
+fn compact_challenge_len<P: SigmaProof + ?Sized>() -> usize {
+    P::CHALLENGE_BITS.min(256).div_ceil(8)
+}
+
+/// Runs the prover of `P`, sampling the nonces from the RNG `nonce_rng`
+/// returns for the transcript once the instance is absorbed and for
+/// `message`, and signing `message` if any.
+fn prove_with_nonces<P, B, R>(
+    witness: &P::WITNESS,
+    instance: &P::INSTANCE,
+    context: &[u8],
+    message: Option<&[u8]>,
+    nonce_rng: impl FnOnce(&ProofTranscript<B>, Option<&[u8]>) -> SigmaProofResult<R>,
+) -> SigmaProofResult<Vec<u8>>
+where
+    P: SigmaProof + ?Sized,
+    B: TranscriptBackend,
+    R: CryptoRngCore,
+{
+    witness.validate()?;
+
+    // init transcript
+    let mut transcript = start_transcript::<_, B>(P::LABEL, None, context, instance)?;
+
+    let mut rng = nonce_rng(&transcript, message)?;
+    prove_rounds::<P, B, _>(
+        &mut transcript,
+        core::slice::from_ref(witness),
+        instance,
+        message,
+        &mut rng,
+        Commitments::Sent,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Whether the prover writes its commitments to the proof.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Commitments {
+    /// The commitments are sent, as in [`SigmaProof::prove`].
+    Sent,
+    /// The commitments are only absorbed, and the verifier recomputes them
+    /// from the challenge, as in [`SigmaProof::prove_compact`].
+    Recomputed,
+}
+
+/// Runs the three rounds of the prover of `P` on `transcript`, once the
+/// instance is absorbed, for every witness of `witnesses` under a single
+/// challenge: the commitments to nonces sampled from `rng`, witness by
+/// witness, then `message` and the challenge, then the responses, witness by
+/// witness. Returns the challenge.
+///
+/// Every prover goes through this, so that they all validate the witnesses,
+/// guard against nonce reuse and clear the nonces alike.
+fn prove_rounds<P, B, R>(
+    transcript: &mut ProofTranscript<B>,
+    witnesses: &[P::WITNESS],
+    instance: &P::INSTANCE,
+    message: Option<&[u8]>,
+    rng: &mut R,
+    commitments: Commitments,
+) -> SigmaProofResult<Scalar>
+where
+    P: SigmaProof + ?Sized,
+    B: TranscriptBackend,
+    R: CryptoRngCore + ?Sized,
+{
+    if witnesses.is_empty() {
+        return Err(SigmaProofError::EmptyStatement);
+    }
+    for witness in witnesses {
+        witness.validate()?;
+    }
+    if cfg!(debug_assertions) {
+        debug_check_num_equations::<P>("f", P::f(instance).len());
+    }
+
+    let alphas: Vec<_> = witnesses.iter().map(|w| w.rand_like(rng)).collect();
+    let result = (|| {
+        // round 1
+        let mut big_a = Vec::new();
+        for alpha in &alphas {
+            let big_a_j = P::psi(alpha, instance)
+                .iter()
+                .map(SymPoint::evaluate)
+                .collect::<Result<Vec<_>, _>>()?;
+            debug_check_num_equations::<P>("psi", big_a_j.len());
+            if big_a_j.is_empty() {
+                return Err(SigmaProofError::EmptyStatement);
+            }
+            big_a.extend(big_a_j);
         }
-        SymScalar::Sub(s1, s2) => {
-            format!(
-                "({} - {})",
-                symscalar_to_latex(s1, var_names),
-                symscalar_to_latex(s2, var_names)
-            )
+        for point in &big_a {
+            match commitments {
+                Commitments::Sent => transcript.prover_absorb_point(P::COMMIT_LABEL, point),
+                Commitments::Recomputed => transcript.common_absorb_point(P::COMMIT_LABEL, point),
+            }
         }
-        SymScalar::Neg(s) => {
-            format!("(-{})", symscalar_to_latex(s, var_names))
+
+        // round 2
+        bind_challenge::<P, B>(transcript, message);
+        let e = draw_challenge::<P, _>(transcript);
+        #[cfg(feature = "nonce-guard")]
+        crate::nonce_guard::record::<P>(instance, &big_a, &e)?;
+
+        // round 3
+        for (witness, alpha) in witnesses.iter().zip(&alphas) {
+            for z_i in responses(witness, alpha, e)? {
+                transcript.prover_absorb_scalar(P::RESPONSE_LABEL, &z_i);
+            }
         }
-        SymScalar::Mul(s1, s2) => {
-            format!(
-                "({} \\cdot {})",
-                symscalar_to_latex(s1, var_names),
-                symscalar_to_latex(s2, var_names)
-            )
+        Ok(e)
+    })();
+
+    // the nonces reveal the witnesses together with the proof
+    #[cfg(feature = "zeroize")]
+    for mut alpha in alphas {
+        SymWitness::zeroize(&mut alpha);
+    }
+
+    result
+}
+
+/// Runs the verifier of `P`, returning the challenge of the proof if it is
+/// accepted.
+fn verify_and_recover_challenge<P: SigmaProof + ?Sized, B: TranscriptBackend>(
+    instance: &P::INSTANCE,
+    proof: &[u8],
+    context: &[u8],
+    message: Option<&[u8]>,
+) -> SigmaProofResult<Scalar> {
+    let received = receive_commitments::<P, B>(instance, proof, context, message)?;
+    check_responses::<P, B>(instance, received)
+}
+
+/// Runs the verifier of `P` from the responses on, once the commitments are
+/// received, and returns the challenge.
+fn check_responses<P: SigmaProof + ?Sized, B: TranscriptBackend>(
+    instance: &P::INSTANCE,
+    received: ReceivedCommitments<B>,
+) -> SigmaProofResult<Scalar> {
+    let ReceivedCommitments {
+        mut transcript,
+        big_x_points,
+        big_a,
+        e,
+    } = received;
+    reject_identity_commitments::<P>(&big_a)?;
+
+    // -> sigma
+    let sigmas = transcript
+        .verifier_receive_scalars(P::RESPONSE_LABEL, P::num_responses(instance)?)
+        .ok_or(SigmaProofError::TranscriptError)?;
+
+    // checks
+    check_equations::<P>(instance, &big_x_points, &big_a, e, &sigmas)?;
+    Ok(e)
+}
+
+/// The scalars of `witness`, cleared on drop with the `zeroize` feature.
+#[cfg(feature = "zeroize")]
+fn secret_values<W: SymWitness>(witness: &W) -> SigmaProofResult<zeroize::Zeroizing<Vec<Scalar>>> {
+    witness.values().map(zeroize::Zeroizing::new)
+}
+
+/// The scalars of `witness`, cleared on drop with the `zeroize` feature.
+#[cfg(not(feature = "zeroize"))]
+fn secret_values<W: SymWitness>(witness: &W) -> SigmaProofResult<Vec<Scalar>> {
+    witness.values()
+}
+
+/// The responses `z = w*e + alpha` for the witness `w` and the nonces
+/// `alpha`. The copies of their scalars are cleared even if this fails.
+fn responses<W: SymWitness>(witness: &W, alphas: &W, e: Scalar) -> SigmaProofResult<Vec<Scalar>> {
+    let values = secret_values(witness)?;
+    let nonces = secret_values(alphas)?;
+    Ok(values
+        .iter()
+        .zip(nonces.iter())
+        .map(|(s, a)| s * e + a)
+        .collect())
+}
+
+/// Checks that `psi` has as many outputs as `f`, and that there is at least
+/// one, before the shape of the proof is checked against them.
+///
+/// The prover sends one commitment per output of `psi` while the verifier
+/// expects one per output of `f`, so a protocol whose `f` and `psi` disagree
+/// would otherwise only fail with a confusing length error. The outputs of
+/// `psi` are counted from [`SigmaProof::NUM_EQUATIONS`] if it is set, and by
+/// evaluating `psi` on a zero witness otherwise. `commitments` is the number
+/// of commitments per witness the proof appears to hold, for the error.
+fn check_equation_counts<P: SigmaProof + ?Sized>(
+    instance: &P::INSTANCE,
+    commitments: usize,
+    num_f: usize,
+    num_responses: usize,
+) -> SigmaProofResult<()> {
+    let num_psi = match P::NUM_EQUATIONS {
+        Some(num_equations) => num_equations,
+        None => {
+            let zeros = P::WITNESS::from_values(&vec![Scalar::ZERO; num_responses])?;
+            P::psi(&zeros, instance).len()
         }
+    };
+    if num_psi == 0 && num_f == 0 {
+        return Err(SigmaProofError::EmptyStatement);
+    }
+    if num_psi != num_f {
+        return Err(SigmaProofError::EquationCountMismatch {
+            commitments,
+            f: num_f,
+            psi: num_psi,
+        });
+    }
+    Ok(())
+}
+
+/// Runs the verifier of `P` up to the challenge: checks the shape of the
+/// proof, absorbs the instance and the commitments `A`, and draws `e`.
+///
+/// The returned transcript is positioned at the responses.
+fn receive_commitments<P: SigmaProof + ?Sized, B: TranscriptBackend>(
+    instance: &P::INSTANCE,
+    proof: &[u8],
+    context: &[u8],
+    message: Option<&[u8]>,
+) -> SigmaProofResult<ReceivedCommitments<B>> {
+    // evaluate f(instance)
+    let big_x_points = evaluate_f::<P>(instance)?;
+
+    receive_commitments_with::<P, B>(
+        instance,
+        big_x_points,
+        |transcript| absorb_instance(transcript, instance),
+        proof,
+        context,
+        message,
+    )
+}
+
+/// Like [`receive_commitments`], with `f(instance)` already evaluated and the
+/// instance absorbed by `absorb`.
+fn receive_commitments_with<P: SigmaProof + ?Sized, B: TranscriptBackend>(
+    instance: &P::INSTANCE,
+    big_x_points: Vec<RistrettoPoint>,
+    absorb: impl FnOnce(&mut ProofTranscript<B>) -> SigmaProofResult<()>,
+    proof: &[u8],
+    context: &[u8],
+    message: Option<&[u8]>,
+) -> SigmaProofResult<ReceivedCommitments<B>> {
+    let num_commitments = big_x_points.len();
+    let mut transcript = start_verifier::<P, B>(instance, num_commitments, absorb, proof, context)?;
+
+    // -> A
+    let big_a = transcript
+        .verifier_receive_points(P::COMMIT_LABEL, num_commitments)
+        .ok_or(SigmaProofError::TranscriptError)?;
+
+    // <- challenge
+    bind_challenge::<P, B>(&mut transcript, message);
+    let e = draw_challenge::<P, _>(&mut transcript);
+
+    Ok(ReceivedCommitments {
+        transcript,
+        big_x_points,
+        big_a,
+        e,
+    })
+}
+
+/// Checks that `proof` has the shape of a proof for `instance` with
+/// `num_commitments` commitments, and starts the verifier transcript with the
+/// instance absorbed by `absorb`.
+fn start_verifier<P: SigmaProof + ?Sized, B: TranscriptBackend>(
+    instance: &P::INSTANCE,
+    num_commitments: usize,
+    absorb: impl FnOnce(&mut ProofTranscript<B>) -> SigmaProofResult<()>,
+    proof: &[u8],
+    context: &[u8],
+) -> SigmaProofResult<ProofTranscript<B>> {
+    // sanity check
+    let num_responses = P::num_responses(instance)?;
+    let commitments = (proof.len() / 32).saturating_sub(num_responses);
+    check_equation_counts::<P>(instance, commitments, num_commitments, num_responses)?;
+    check_proof_shape(proof, num_commitments, num_responses)?;
+
+    // init transcript
+    start_transcript_with(P::LABEL, Some(proof), context, absorb)
+}
+
+/// Rejects identity commitments if [`SigmaProof::REJECT_IDENTITY_COMMITMENTS`]
+/// is set.
+fn reject_identity_commitments<P: SigmaProof + ?Sized>(
+    big_a: &[RistrettoPoint],
+) -> SigmaProofResult<()> {
+    if P::REJECT_IDENTITY_COMMITMENTS && big_a.contains(&RistrettoPoint::identity()) {
+        return Err(SigmaProofError::IdentityCommitment);
+    }
+    Ok(())
+}
+
+/// Checks `psi(z) = A + e*X` for every equation, in constant time with
+/// respect to which equations hold.
+///
+/// Every equation is evaluated and the per-equation results are combined with
+/// `subtle`, so a failure does not reveal through timing which equation broke.
+/// The results are not combined by summing the differences `psi_i - A_i -
+/// e*X_i`, as differences of failing equations could cancel out.
+fn check_equations<P: SigmaProof + ?Sized>(
+    instance: &P::INSTANCE,
+    big_x_points: &[RistrettoPoint],
+    big_a: &[RistrettoPoint],
+    e: Scalar,
+    sigmas: &[Scalar],
+) -> SigmaProofResult<()> {
+    let psi_output = psi_of_responses::<P>(instance, sigmas)?;
+    check_psi_output(big_x_points, big_a, e, &psi_output)
+}
+
+/// Evaluates `f(instance)`, rejecting expressions deeper than
+/// [`SigmaProof::MAX_EXPRESSION_DEPTH`].
+fn evaluate_f<P: SigmaProof + ?Sized>(
+    instance: &P::INSTANCE,
+) -> SigmaProofResult<Vec<RistrettoPoint>> {
+    let f_output = P::f(instance);
+    debug_check_num_equations::<P>("f", f_output.len());
+    check_expression_depth::<P>(&f_output)?;
+    f_output.iter().map(SymPoint::evaluate).collect()
+}
+
+/// Computes `psi(z)` for the responses `sigmas`, rejecting expressions deeper
+/// than [`SigmaProof::MAX_EXPRESSION_DEPTH`].
+fn psi_of_responses<P: SigmaProof + ?Sized>(
+    instance: &P::INSTANCE,
+    sigmas: &[Scalar],
+) -> SigmaProofResult<Vec<SymPoint>> {
+    let sigmas_as_input = P::WITNESS::from_values(sigmas)?;
+    let psi_output = P::psi(&sigmas_as_input, instance);
+    debug_check_num_equations::<P>("psi", psi_output.len());
+    check_expression_depth::<P>(&psi_output)?;
+    Ok(psi_output)
+}
+
+/// Rejects expressions deeper than [`SigmaProof::MAX_EXPRESSION_DEPTH`].
+fn check_expression_depth<P: SigmaProof + ?Sized>(points: &[SymPoint]) -> SigmaProofResult<()> {
+    let Some(max) = P::MAX_EXPRESSION_DEPTH else {
+        return Ok(());
+    };
+    match points.iter().map(SymPoint::depth).max() {
+        Some(depth) if depth > max => Err(SigmaProofError::ExpressionTooComplex { depth, max }),
+        _ => Ok(()),
+    }
+}
+
+/// Asserts in debug builds that `map`, i.e. `f` or `psi`, returned the
+/// [`SigmaProof::NUM_EQUATIONS`] points declared by `P`, if any.
+fn debug_check_num_equations<P: SigmaProof + ?Sized>(map: &str, len: usize) {
+    if let Some(expected) = P::NUM_EQUATIONS {
+        debug_assert_eq!(
+            len,
+            expected,
+            "{}::{map} returned {len} points, but {}::NUM_EQUATIONS is {expected}",
+            core::any::type_name::<P>(),
+            core::any::type_name::<P>(),
+        );
+    }
+}
+
+/// The part of [`check_equations`] that does not depend on the protocol, for
+/// callers that have already computed `psi(z)`.
+pub(crate) fn check_psi_output(
+    big_x_points: &[RistrettoPoint],
+    big_a: &[RistrettoPoint],
+    e: Scalar,
+    psi_output: &[SymPoint],
+) -> SigmaProofResult<()> {
+    if big_x_points.len() != psi_output.len() {
+        return Err(SigmaProofError::PsiOutputLengthMismatch);
+    }
+
+    // Folding the equations into one multiscalar multiplication with random
+    // weights saves the scalar multiplications e*X_i, but evaluating psi
+    // dominates and it is only about 15% faster on 1000 equations, see
+    // `tests::bench_equation_check`. Comparing each equation keeps the check
+    // deterministic, without weights to draw, and constant time.
+    let mut valid = Choice::from(1);
+    for ((big_x_i, big_a_i), psi_i) in big_x_points.iter().zip(big_a).zip(psi_output) {
+        let rhs = big_a_i + e * big_x_i;
+        valid &= psi_i.evaluate()?.ct_eq(&rhs);
+    }
+
+    if bool::from(valid) {
+        Ok(())
+    } else {
+        Err(SigmaProofError::EquationCheckFailed)
+    }
+}
+
+struct ReceivedCommitments<B> {
+    transcript: ProofTranscript<B>,
+    big_x_points: Vec<RistrettoPoint>,
+    big_a: Vec<RistrettoPoint>,
+    e: Scalar,
+}
+
+/// An instance of `P` together with its encoding and `f(instance)`, computed
+/// once by [`SymInstance::prepare`] for [`SigmaProof::verify_prepared`].
+///
+/// Evaluating `f` and compressing the instance points for the transcript cost
+/// a few group operations per point, which [`SigmaProof::verify`] repeats for
+/// every proof. Verifying `n` proofs against a prepared instance pays them
+/// once instead of `n` times, which matters when the instance has many points
+/// compared to the number of equations.
+pub struct PreparedInstance<P: SigmaProof + ?Sized> {
+    instance: P::INSTANCE,
+    points: Vec<[u8; 32]>,
+    scalars: Vec<Scalar>,
+    big_x_points: Vec<RistrettoPoint>,
+}
+
+impl<P: SigmaProof + ?Sized> PreparedInstance<P> {
+    pub fn new(instance: P::INSTANCE) -> SigmaProofResult<Self> {
+        let points = instance
+            .absorbed_points()
+            .iter()
+            .map(|point| Ok(point_to_wire(&point.evaluate()?)))
+            .collect::<SigmaProofResult<_>>()?;
+        let scalars = instance
+            .absorbed_scalars()
+            .iter()
+            .map(SymScalar::evaluate)
+            .collect::<SigmaProofResult<_>>()?;
+        let big_x_points = evaluate_f::<P>(&instance)?;
+        Ok(Self {
+            instance,
+            points,
+            scalars,
+            big_x_points,
+        })
+    }
+
+    pub fn instance(&self) -> &P::INSTANCE {
+        &self.instance
+    }
+
+    /// Absorbs the instance like [`absorb_instance`], from its cached encoding.
+    fn absorb<B: TranscriptBackend>(&self, transcript: &mut ProofTranscript<B>) {
+        absorb_instance_encoding(transcript, &self.points, &self.scalars);
     }
 }
 
+/// The ways in which an instance can be degenerate, see
+/// [`SigmaProof::validate_instance`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DegeneracyKind {
+    /// One of the instance points is the identity.
+    IdentityPoint,
+    /// Two of the instance points are equal.
+    DuplicatePoints,
+}
+
+impl DegeneracyKind {
+    pub const ALL: [DegeneracyKind; 2] = [
+        DegeneracyKind::IdentityPoint,
+        DegeneracyKind::DuplicatePoints,
+    ];
+}
+
 pub trait SigmaProof {
     const LABEL: &'static [u8];
 
+    /// Whether [`SigmaProof::verify`] rejects proofs in which a commitment
+    /// `A_i` is the identity, with [`SigmaProofError::IdentityCommitment`].
+    ///
+    /// An honest prover only sends the identity with negligible probability,
+    /// but protocols that legitimately allow it must leave this unset.
+    const REJECT_IDENTITY_COMMITMENTS: bool = false;
+
+    /// The transcript labels of the commitments `A`, of the challenge `e` and
+    /// of the responses `z`.
+    ///
+    /// Protocols sharing a transcript, or run inside another protocol, can set
+    /// distinct labels so that their messages cannot be mistaken for one
+    /// another. Changing them changes the proofs.
+    const COMMIT_LABEL: &'static [u8] = b"r";
+    const CHALLENGE_LABEL: &'static [u8] = b"e";
+    const RESPONSE_LABEL: &'static [u8] = b"z";
+
+    /// Whether `f` returns the points of the instance unchanged, in which case
+    /// the default [`SigmaProof::f`] can be used instead of writing it out.
+    const F_IS_IDENTITY: bool = false;
+
+    /// The number of equations of the protocol, i.e. of points returned by
+    /// both `f` and `psi`, if it does not depend on the instance.
+    ///
+    /// Debug builds of the prover and the verifier assert that `f` and `psi`
+    /// return exactly this many points, which catches a mismatch between them
+    /// before it surfaces as a [`SigmaProofError::PsiOutputLengthMismatch`].
+    const NUM_EQUATIONS: Option<usize> = None;
+
+    /// The number of bits of the Fiat-Shamir output used as the challenge,
+    /// which is zero-extended to a scalar.
+    ///
+    /// **This is the soundness of the protocol.** A cheating prover who can
+    /// guess the challenge forges a proof, and with `CHALLENGE_BITS = k` it
+    /// succeeds with probability `2^-k` per attempt, so offline attacks
+    /// against a non-interactive proof cost about `2^k` hashes. The default
+    /// of 256 draws a full scalar; 128 bits is the smallest value to consider
+    /// for proofs that must stand on their own. Smaller values are only fit
+    /// for interactive or rate-limited settings.
+    ///
+    /// The challenge is not sent in regular proofs, so their size does not
+    /// change. Compact proofs send it in `ceil(CHALLENGE_BITS / 8)` bytes
+    /// instead of 32. Changing it changes the proofs.
+    const CHALLENGE_BITS: usize = 256;
+
+    /// The maximum depth, see [`SymPoint::depth`], of the expressions of `f`
+    /// and `psi` the verifier evaluates, if any.
+    ///
+    /// Deeper expressions are rejected with
+    /// [`SigmaProofError::ExpressionTooComplex`] before they are evaluated,
+    /// which bounds the work of verifying protocols whose expressions grow
+    /// with the instance.
+    const MAX_EXPRESSION_DEPTH: Option<usize> = None;
+
     type WITNESS: SymWitness;
     type INSTANCE: SymInstance;
 
-    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint>;
+    /// Maps the instance to the points `X` that `psi(w)` must equal.
+    ///
+    /// The default returns `instance.points()`, and is only available to
+    /// protocols that set [`SigmaProof::F_IS_IDENTITY`].
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        const {
+            assert!(
+                Self::F_IS_IDENTITY,
+                "SigmaProof::f must be implemented unless F_IS_IDENTITY is set"
+            )
+        };
+        instance.points()
+    }
 
     fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint>;
 
+    /// The number of responses in a proof for `instance`, i.e. the number of
+    /// witness scalars.
+    ///
+    /// Protocols whose witness length is only known at runtime, such as those
+    /// using [`crate::absorb::VecWitness`], must derive it from the instance,
+    /// or fail with [`SigmaProofError::UnknownResponseCount`].
+    fn num_responses(_instance: &Self::INSTANCE) -> SigmaProofResult<usize> {
+        Self::WITNESS::num_scalars().ok_or(SigmaProofError::UnknownResponseCount)
+    }
+
+    /// Absorbs extra public data (e.g. a nonce or an epoch) into the
+    /// transcript, right before the challenge is drawn. It is called by the
+    /// prover and the verifier alike, and absorbs nothing by default.
+    fn bind_extra<B: TranscriptBackend>(_transcript: &mut ProofTranscript<B>) {}
+
     fn prove(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> SigmaProofResult<Vec<u8>> {
-        // init transcript
-        let mut transcript = ProofTranscript::new_prover(Self::LABEL);
+        Self::prove_with_context(witness, instance, b"")
+    }
+
+    /// Like [`SigmaProof::prove`], but binds the proof to an
+    /// application-specific `context` (e.g. a session ID or a message), which
+    /// the verifier must supply to [`SigmaProof::verify_with_context`].
+    fn prove_with_context(
+        witness: &Self::WITNESS,
+        instance: &Self::INSTANCE,
+        context: &[u8],
+    ) -> SigmaProofResult<Vec<u8>> {
+        Self::prove_with_backend::<merlin::Transcript>(witness, instance, context)
+    }
+
+    /// Like [`SigmaProof::prove_with_context`], with the transcript backend
+    /// `B` instead of the default Merlin one. The proof must be verified with
+    /// [`SigmaProof::verify_with_backend`] and the same backend.
+    fn prove_with_backend<B: TranscriptBackend>(
+        witness: &Self::WITNESS,
+        instance: &Self::INSTANCE,
+        context: &[u8],
+    ) -> SigmaProofResult<Vec<u8>> {
+        prove_with_nonces::<Self, B, _>(witness, instance, context, None, |_, _| {
+            Ok(rand::rngs::OsRng)
+        })
+    }
+
+    /// Like [`SigmaProof::prove`], but derives the nonces from the witness,
+    /// the instance and the label instead of sampling them, in the spirit of
+    /// RFC 6979.
+    ///
+    /// Proofs of the same statement with the same witness are identical, and
+    /// do not depend on system entropy. They are otherwise indistinguishable
+    /// from those of [`SigmaProof::prove`].
+    ///
+    /// The nonces also depend on what [`SigmaProof::bind_extra`] absorbs,
+    /// although it comes after the commitments in the transcript, so proofs
+    /// binding different extra data use different nonces.
+    fn prove_deterministic(
+        witness: &Self::WITNESS,
+        instance: &Self::INSTANCE,
+    ) -> SigmaProofResult<Vec<u8>> {
+        prove_with_nonces::<Self, merlin::Transcript, _>(
+            witness,
+            instance,
+            b"",
+            None,
+            |transcript, message| {
+                // key the nonces by everything the challenge is bound to, on
+                // a fork of the transcript
+                let mut bound = transcript.clone();
+                bind_challenge::<Self, _>(&mut bound, message);
+                Ok(bound.nonce_rng(&witness.values()?))
+            },
+        )
+    }
+
+    /// Like [`SigmaProof::prove`], but also binds `message` to the challenge,
+    /// which turns the proof into a signature of `message` under the instance,
+    /// in the manner of Schnorr signatures. The signature must be verified
+    /// with [`SigmaProof::verify_signature`] and the same message.
+    ///
+    /// The message is absorbed after the commitments, right before the
+    /// challenge. The nonces are sampled from the system RNG.
+    fn prove_signature(
+        witness: &Self::WITNESS,
+        instance: &Self::INSTANCE,
+        message: &[u8],
+    ) -> SigmaProofResult<Vec<u8>> {
+        prove_with_nonces::<Self, merlin::Transcript, _>(
+            witness,
+            instance,
+            b"",
+            Some(message),
+            |_, _| Ok(rand::rngs::OsRng),
+        )
+    }
 
-        // absorb instance, not f(instance)
-        for point in instance.points() {
-            transcript.common_absorb_point(b"", &point.evaluate()?);
+    /// Like [`SigmaProof::prove`], but first checks that the witness satisfies
+    /// the statement, returning [`SigmaProofError::InstanceConsistency`] if
+    /// `psi(witness) != f(instance)`.
+    ///
+    /// [`SigmaProof::prove`] does not check this, and produces a proof that
+    /// fails to verify instead.
+    fn prove_checked(
+        witness: &Self::WITNESS,
+        instance: &Self::INSTANCE,
+    ) -> SigmaProofResult<Vec<u8>> {
+        let psi_output = Self::psi(witness, instance);
+        let f_output = Self::f(instance);
+        if psi_output.len() != f_output.len() {
+            return Err(SigmaProofError::PsiOutputLengthMismatch);
         }
-        for scalar in instance.scalars() {
-            transcript.common_absorb_scalar(b"", &scalar.evaluate()?);
+        for (psi_i, f_i) in psi_output.iter().zip(&f_output) {
+            if psi_i.evaluate()? != f_i.evaluate()? {
+                return Err(SigmaProofError::InstanceConsistency);
+            }
         }
 
-        // round 1
-        let rng = &mut rand::rngs::OsRng;
-        let alphas = Self::WITNESS::rand(rng);
-        let commited_alphas = Self::psi(&alphas, instance);
-        for point in &commited_alphas {
-            transcript.prover_absorb_point(b"r", &point.evaluate()?);
+        Self::prove(witness, instance)
+    }
+
+    /// Proves every `(witness, instance)` pair with [`SigmaProof::prove`] on
+    /// the rayon thread pool, returning the proofs in the same order, or the
+    /// first error.
+    ///
+    /// Each proof samples its own nonces from the operating system, which is
+    /// safe to call from any thread, so no RNG is shared between the proofs.
+    #[cfg(feature = "rayon")]
+    fn prove_many(pairs: &[(Self::WITNESS, Self::INSTANCE)]) -> SigmaProofResult<Vec<Vec<u8>>>
+    where
+        Self::WITNESS: Sync,
+        Self::INSTANCE: Sync,
+    {
+        use rayon::prelude::*;
+
+        pairs
+            .par_iter()
+            .map(|(witness, instance)| Self::prove(witness, instance))
+            .collect()
+    }
+
+    /// Verifies a proof produced by [`SigmaProof::prove`].
+    ///
+    /// A proof of the wrong shape is rejected before any group operation:
+    /// * [`SigmaProofError::ProofLengthInvalid`] if its length is not a
+    ///   multiple of 32 or it has trailing bytes,
+    /// * [`SigmaProofError::MissingCommitments`] if it is too short to hold
+    ///   one commitment per equation,
+    /// * [`SigmaProofError::MissingResponses`] if it is too short to hold one
+    ///   response per witness scalar.
+    ///
+    /// [`SigmaProofError::EquationCheckFailed`] means the proof is well-formed
+    /// but does not prove the statement.
+    fn verify(instance: &Self::INSTANCE, proof: &[u8]) -> Result<(), SigmaProofError> {
+        Self::verify_with_context(instance, proof, b"")
+    }
+
+    /// Like [`SigmaProof::prove`], but prefixes the proof with a header
+    /// holding the [`TRANSCRIPT_VERSION`], then the number of commitments and
+    /// of responses, each as a `u32` in little-endian, so that the proof is
+    /// self-describing.
+    fn prove_framed(
+        witness: &Self::WITNESS,
+        instance: &Self::INSTANCE,
+    ) -> SigmaProofResult<Vec<u8>> {
+        let num_commitments = Self::f(instance).len() as u32;
+        let num_responses = Self::num_responses(instance)? as u32;
+
+        let mut proof = vec![TRANSCRIPT_VERSION];
+        proof.extend(num_commitments.to_le_bytes());
+        proof.extend(num_responses.to_le_bytes());
+        proof.extend(Self::prove(witness, instance)?);
+        Ok(proof)
+    }
+
+    /// Verifies a proof produced by [`SigmaProof::prove_framed`].
+    ///
+    /// The header is checked before the body is read, so a proof made with
+    /// another transcript version is rejected with
+    /// [`SigmaProofError::TranscriptVersionMismatch`], and a proof for a
+    /// statement of another shape with [`SigmaProofError::ProofShapeMismatch`].
+    fn verify_framed(instance: &Self::INSTANCE, proof: &[u8]) -> SigmaProofResult<()> {
+        let (header, body) = proof
+            .split_first_chunk::<9>()
+            .ok_or(SigmaProofError::MalformedProof)?;
+        let [version, c0, c1, c2, c3, r0, r1, r2, r3] = *header;
+        if version != TRANSCRIPT_VERSION {
+            return Err(SigmaProofError::TranscriptVersionMismatch {
+                expected: TRANSCRIPT_VERSION,
+                got: version,
+            });
         }
+        let got = (
+            u32::from_le_bytes([c0, c1, c2, c3]) as usize,
+            u32::from_le_bytes([r0, r1, r2, r3]) as usize,
+        );
 
-        // round 2
-        let e = transcript.challenge(b"e");
+        let expected = (Self::f(instance).len(), Self::num_responses(instance)?);
+        if got != expected {
+            return Err(SigmaProofError::ProofShapeMismatch { expected, got });
+        }
 
-        // round 3
-        for z_i in witness
-            .values()?
-            .into_iter()
-            .zip(alphas.values()?)
-            .map(|(s, a)| s * e + a)
+        Self::verify(instance, body)
+    }
+
+    /// Verifies a proof produced by [`SigmaProof::prove_with_context`] with
+    /// the same `context`.
+    fn verify_with_context(
+        instance: &Self::INSTANCE,
+        proof: &[u8],
+        context: &[u8],
+    ) -> SigmaProofResult<()> {
+        Self::verify_with_backend::<merlin::Transcript>(instance, proof, context)
+    }
+
+    /// Verifies a proof produced by [`SigmaProof::prove_with_backend`] with
+    /// the same backend and `context`.
+    fn verify_with_backend<B: TranscriptBackend>(
+        instance: &Self::INSTANCE,
+        proof: &[u8],
+        context: &[u8],
+    ) -> SigmaProofResult<()> {
+        verify_and_recover_challenge::<Self, B>(instance, proof, context, None).map(|_| ())
+    }
+
+    /// Verifies a signature of `message` produced by
+    /// [`SigmaProof::prove_signature`].
+    ///
+    /// A signature of another message, and a plain proof, are rejected with
+    /// [`SigmaProofError::EquationCheckFailed`].
+    fn verify_signature(
+        instance: &Self::INSTANCE,
+        proof: &[u8],
+        message: &[u8],
+    ) -> SigmaProofResult<()> {
+        verify_and_recover_challenge::<Self, merlin::Transcript>(
+            instance,
+            proof,
+            b"",
+            Some(message),
+        )
+        .map(|_| ())
+    }
+
+    /// Like [`SigmaProof::verify`], against an instance prepared with
+    /// [`SymInstance::prepare`] so that verifying many proofs of the same
+    /// statement does not evaluate and encode the instance every time.
+    fn verify_prepared(prepared: &PreparedInstance<Self>, proof: &[u8]) -> SigmaProofResult<()> {
+        let received = receive_commitments_with::<Self, merlin::Transcript>(
+            &prepared.instance,
+            prepared.big_x_points.clone(),
+            |transcript| {
+                prepared.absorb(transcript);
+                Ok(())
+            },
+            proof,
+            b"",
+            None,
+        )?;
+        check_responses::<Self, merlin::Transcript>(&prepared.instance, received).map(|_| ())
+    }
+
+    /// Like [`SigmaProof::verify`], but returns the Fiat-Shamir challenge `e`
+    /// of the proof once it is verified, e.g. to chain it into a larger
+    /// protocol.
+    fn verify_with_challenge(instance: &Self::INSTANCE, proof: &[u8]) -> SigmaProofResult<Scalar> {
+        verify_and_recover_challenge::<Self, merlin::Transcript>(instance, proof, b"", None)
+    }
+
+    /// Like [`SigmaProof::verify`], but never holds all the decoded
+    /// commitments or all of `f(instance)` evaluated in memory: the
+    /// commitments are absorbed as raw bytes, then each commitment and each
+    /// `f_i(X)` is decoded and checked in turn, stopping at the first
+    /// equation that fails. `psi(z)` is still built in full, as symbolic
+    /// expressions.
+    ///
+    /// This lowers peak memory for statements with many equations, but unlike
+    /// [`SigmaProof::verify`] the time it takes to reject a proof depends on
+    /// which equation fails.
+    fn verify_streaming(instance: &Self::INSTANCE, proof: &[u8]) -> SigmaProofResult<()> {
+        let f_output = Self::f(instance);
+        debug_check_num_equations::<Self>("f", f_output.len());
+        check_expression_depth::<Self>(&f_output)?;
+        let num_commitments = f_output.len();
+        let mut transcript = start_verifier::<Self, merlin::Transcript>(
+            instance,
+            num_commitments,
+            |transcript| absorb_instance(transcript, instance),
+            proof,
+            b"",
+        )?;
+
+        // -> A, decoded later
+        transcript
+            .verifier_absorb_point_bytes(Self::COMMIT_LABEL, num_commitments)
+            .ok_or(SigmaProofError::MissingCommitments)?;
+
+        // <- challenge
+        bind_challenge::<Self, _>(&mut transcript, None);
+        let e = draw_challenge::<Self, _>(&mut transcript);
+
+        // -> sigma
+        let sigmas = transcript
+            .verifier_receive_scalars(Self::RESPONSE_LABEL, Self::num_responses(instance)?)
+            .ok_or(SigmaProofError::TranscriptError)?;
+        let psi_output = psi_of_responses::<Self>(instance, &sigmas)?;
+        if psi_output.len() != num_commitments {
+            return Err(SigmaProofError::PsiOutputLengthMismatch);
+        }
+
+        // checks, one equation at a time
+        for ((big_a_bytes, big_x_i), psi_i) in
+            proof.chunks_exact(32).zip(&f_output).zip(&psi_output)
         {
-            transcript.prover_absorb_scalar(b"z", &z_i);
+            let big_a_i = big_a_bytes
+                .try_into()
+                .ok()
+                .and_then(point_from_wire)
+                .ok_or(SigmaProofError::TranscriptError)?;
+            reject_identity_commitments::<Self>(&[big_a_i])?;
+            check_psi_output(
+                &[big_x_i.evaluate()?],
+                &[big_a_i],
+                e,
+                core::slice::from_ref(psi_i),
+            )?;
         }
 
-        Ok(transcript.finalize())
+        Ok(())
     }
 
-    fn verify(instance: &Self::INSTANCE, proof: &[u8]) -> Result<(), SigmaProofError> {
-        // sanity check
-        if proof.len() % 32 != 0 {
-            return Err(SigmaProofError::TranscriptFinalizationFailed);
+    /// Like [`SigmaProof::prove`], but returns the commitments `A`, the
+    /// challenge `e` and the responses `z` of the proof as typed values, e.g.
+    /// to embed them in a larger transcript.
+    ///
+    /// The proof is the concatenation of the encodings of `A` and `z`.
+    fn prove_parts(
+        witness: &Self::WITNESS,
+        instance: &Self::INSTANCE,
+    ) -> SigmaProofResult<(Vec<RistrettoPoint>, Scalar, Vec<Scalar>)> {
+        let proof = Self::prove(witness, instance)?;
+        let ReceivedCommitments {
+            mut transcript,
+            big_a,
+            e,
+            ..
+        } = receive_commitments::<Self, merlin::Transcript>(instance, &proof, b"", None)?;
+        let responses = transcript
+            .verifier_receive_scalars(Self::RESPONSE_LABEL, Self::num_responses(instance)?)
+            .ok_or(SigmaProofError::TranscriptError)?;
+        Ok((big_a, e, responses))
+    }
+
+    /// Verifies the parts `(A, e, z)` of a proof, as returned by
+    /// [`SigmaProof::prove_parts`].
+    ///
+    /// This only checks the equations `psi(z) = A + e*f(X)`, not that `e` was
+    /// derived from `A` and the instance: callers driving Fiat-Shamir
+    /// themselves must draw `e` from a transcript that absorbed both, or the
+    /// proof is trivially forgeable with [`SigmaProof::simulate`].
+    fn verify_parts(
+        instance: &Self::INSTANCE,
+        commitments: &[RistrettoPoint],
+        e: Scalar,
+        responses: &[Scalar],
+    ) -> SigmaProofResult<()> {
+        reject_identity_commitments::<Self>(commitments)?;
+        Self::check_transcript(instance, commitments, e, responses)
+    }
+
+    /// Checks a transcript `(A, e, z)` against the instance with the same
+    /// equations `psi(z) = A + e*f(X)` as [`SigmaProof::verify`].
+    fn check_transcript(
+        instance: &Self::INSTANCE,
+        commitments: &[RistrettoPoint],
+        e: Scalar,
+        responses: &[Scalar],
+    ) -> SigmaProofResult<()> {
+        let big_x_points = evaluate_f::<Self>(instance)?;
+        if commitments.len() != big_x_points.len() {
+            return Err(SigmaProofError::MissingCommitments);
+        }
+        check_equations::<Self>(instance, &big_x_points, commitments, e, responses)
+    }
+
+    /// The honest-verifier zero-knowledge simulator: produces a transcript
+    /// `(A, z)` for the challenge `e` without the witness, by sampling `z` and
+    /// setting `A_i = psi_i(z) - e*f_i(X)`.
+    fn simulate<R: CryptoRngCore + ?Sized>(
+        instance: &Self::INSTANCE,
+        e: Scalar,
+        rng: &mut R,
+    ) -> SigmaProofResult<(Vec<RistrettoPoint>, Vec<Scalar>)> {
+        let responses: Vec<Scalar> = (0..Self::num_responses(instance)?)
+            .map(|_| Scalar::random(rng))
+            .collect();
+        let psi_output = Self::psi(&Self::WITNESS::from_values(&responses)?, instance);
+        let big_x_points = Self::f(instance);
+        if big_x_points.len() != psi_output.len() {
+            return Err(SigmaProofError::PsiOutputLengthMismatch);
         }
 
+        let commitments = psi_output
+            .iter()
+            .zip(&big_x_points)
+            .map(|(psi_i, big_x_i)| Ok(psi_i.evaluate()? - e * big_x_i.evaluate()?))
+            .collect::<SigmaProofResult<Vec<_>>>()?;
+        Ok((commitments, responses))
+    }
+
+    /// Recomputes the Fiat-Shamir challenge `e` of a proof, exactly as
+    /// [`SigmaProof::verify`] derives it, without checking the responses.
+    ///
+    /// This is meant for debugging proofs that fail to verify.
+    fn recover_challenge(instance: &Self::INSTANCE, proof: &[u8]) -> SigmaProofResult<Scalar> {
+        Ok(receive_commitments::<Self, merlin::Transcript>(instance, proof, b"", None)?.e)
+    }
+
+    /// Like [`SigmaProof::prove`], but sends the challenge `e` instead of the
+    /// commitments `A`, which the verifier recomputes as `A_i = psi_i(z) - e*X_i`.
+    ///
+    /// A regular proof is `32 * (equations + witness scalars)` bytes, a compact
+    /// one is `32 * (1 + witness scalars)` bytes, so this saves space whenever
+    /// the statement has more than one equation. A shorter
+    /// [`SigmaProof::CHALLENGE_BITS`] shrinks the challenge further, e.g. a
+    /// 128-bit challenge takes 16 bytes instead of 32.
+    fn prove_compact(
+        witness: &Self::WITNESS,
+        instance: &Self::INSTANCE,
+    ) -> SigmaProofResult<Vec<u8>> {
         // init transcript
-        let mut transcript = ProofTranscript::new_verifier(Self::LABEL, proof);
+        let mut transcript: ProofTranscript = start_transcript(Self::LABEL, None, b"", instance)?;
+
+        // commitments are absorbed but not sent
+        let e = prove_rounds::<Self, _, _>(
+            &mut transcript,
+            core::slice::from_ref(witness),
+            instance,
+            None,
+            &mut rand::rngs::OsRng,
+            Commitments::Recomputed,
+        )?;
+
+        let mut proof =
+            scalar_to_wire(&e, Endianness::Little)[..compact_challenge_len::<Self>()].to_vec();
+        proof.extend(transcript.finalize());
+        Ok(proof)
+    }
 
+    /// Verifies a proof produced by [`SigmaProof::prove_compact`].
+    fn verify_compact(instance: &Self::INSTANCE, proof: &[u8]) -> SigmaProofResult<()> {
         // evaluate f(instance)
-        let big_x_points: Vec<_> = Self::f(instance)
-            .into_iter()
-            .map(|p| p.evaluate())
-            .collect::<Result<Vec<_>, _>>()?;
+        let big_x_points = evaluate_f::<Self>(instance)?;
 
-        // absorb instance, not f(instance)
-        for point in instance.points() {
-            transcript.common_absorb_point(b"", &point.evaluate()?);
+        // sanity check: the challenge is followed by the responses
+        let challenge_len = compact_challenge_len::<Self>();
+        let num_responses = Self::num_responses(instance)?;
+        check_equation_counts::<Self>(instance, 0, big_x_points.len(), num_responses)?;
+        let Some(responses) = proof.get(challenge_len..) else {
+            return Err(SigmaProofError::MissingResponses);
+        };
+        if responses.len() % 32 != 0 || responses.len() > 32 * num_responses {
+            return Err(SigmaProofError::ProofLengthInvalid { len: proof.len() });
         }
-        for scalar in instance.scalars() {
-            transcript.common_absorb_scalar(b"", &scalar.evaluate()?);
+        if responses.len() < 32 * num_responses {
+            return Err(SigmaProofError::MissingResponses);
         }
 
-        // -> A
+        // -> e, zero-extended and without bits above CHALLENGE_BITS
+        let mut e_bytes = [0u8; 32];
+        e_bytes[..challenge_len].copy_from_slice(&proof[..challenge_len]);
+        if Self::CHALLENGE_BITS < 256
+            && e_bytes[challenge_len - 1] & !mask_top_byte(Self::CHALLENGE_BITS) != 0
+        {
+            return Err(SigmaProofError::TranscriptError);
+        }
+        let e = scalar_from_wire(e_bytes, Endianness::Little)
+            .ok_or(SigmaProofError::TranscriptError)?;
+
+        // -> sigma
+        let sigmas = responses
+            .chunks_exact(32)
+            .map(|chunk| {
+                let mut buf = [0u8; 32];
+                buf.copy_from_slice(chunk);
+                scalar_from_wire(buf, Endianness::Little)
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(SigmaProofError::TranscriptError)?;
+
+        // evaluate psi(sigma)
+        let psi_output = psi_of_responses::<Self>(instance, &sigmas)?;
+        if big_x_points.len() != psi_output.len() {
+            return Err(SigmaProofError::PsiOutputLengthMismatch);
+        }
+
+        // recompute A_i = psi_i - e * X_i and re-derive the challenge
+        let big_a = psi_output
+            .iter()
+            .zip(&big_x_points)
+            .map(|(psi_i, big_x_i)| Ok(psi_i.evaluate()? - e * big_x_i))
+            .collect::<SigmaProofResult<Vec<_>>>()?;
+        reject_identity_commitments::<Self>(&big_a)?;
+        let mut transcript: ProofTranscript =
+            start_transcript(Self::LABEL, Some(&[]), b"", instance)?;
+        for big_a_i in &big_a {
+            transcript.common_absorb_point(Self::COMMIT_LABEL, big_a_i);
+        }
+
+        Self::bind_extra(&mut transcript);
+        if draw_challenge::<Self, _>(&mut transcript) != e {
+            return Err(SigmaProofError::EquationCheckFailed);
+        }
+
+        Ok(())
+    }
+
+    /// Proves `k` witnesses of the same instance in a single transcript,
+    /// which absorbs the instance once and draws one challenge `e` for all of
+    /// them. Unlike independent proofs checked together, the verifier makes a
+    /// single pass over the transcript.
+    ///
+    /// The transcript is laid out as
+    ///
+    /// ```text
+    /// context                   ""
+    /// instance                  absorbed as in `prove`
+    /// aggregate-size            k, as a u64 in little-endian
+    /// A_1, ..., A_k             the commitments of each witness, in order
+    /// e                         after `bind_extra`
+    /// z_1, ..., z_k             the responses of each witness, in order
+    /// ```
+    ///
+    /// and the proof holds `A_1 || ... || A_k || z_1 || ... || z_k`, i.e.
+    /// `32 * k * (equations + witness scalars)` bytes. Each `(A_j, e, z_j)` is
+    /// a transcript of the protocol for the `j`-th witness, so the proof
+    /// fails to verify if any of the witnesses does not satisfy the statement.
+    ///
+    /// Returns [`SigmaProofError::EmptyStatement`] if `witnesses` is empty.
+    fn prove_aggregated(
+        witnesses: &[Self::WITNESS],
+        instance: &Self::INSTANCE,
+    ) -> SigmaProofResult<Vec<u8>> {
+        if witnesses.is_empty() {
+            return Err(SigmaProofError::EmptyStatement);
+        }
+
+        // init transcript, with the instance absorbed once for all witnesses
+        let mut transcript: ProofTranscript = start_transcript(Self::LABEL, None, b"", instance)?;
+        transcript.common_absorb_bytes(b"aggregate-size", &(witnesses.len() as u64).to_le_bytes());
+
+        prove_rounds::<Self, _, _>(
+            &mut transcript,
+            witnesses,
+            instance,
+            None,
+            &mut rand::rngs::OsRng,
+            Commitments::Sent,
+        )?;
+        Ok(transcript.finalize())
+    }
+
+    /// Verifies a proof produced by [`SigmaProof::prove_aggregated`] for
+    /// `num_witnesses` witnesses.
+    ///
+    /// Every witness is checked, whether or not an earlier one failed, and
+    /// [`SigmaProofError::EquationCheckFailed`] is returned if any did. A
+    /// `num_witnesses` so large that the proof length would overflow is
+    /// rejected as [`SigmaProofError::MissingCommitments`].
+    fn verify_aggregated(
+        instance: &Self::INSTANCE,
+        num_witnesses: usize,
+        proof: &[u8],
+    ) -> SigmaProofResult<()> {
+        if num_witnesses == 0 {
+            return Err(SigmaProofError::EmptyStatement);
+        }
+
+        // evaluate f(instance)
+        let big_x_points = evaluate_f::<Self>(instance)?;
+
+        // sanity check
+        let num_commitments = big_x_points.len();
+        let num_responses = Self::num_responses(instance)?;
+        let commitments = (proof.len() / 32 / num_witnesses).saturating_sub(num_responses);
+        check_equation_counts::<Self>(instance, commitments, num_commitments, num_responses)?;
+        let total_commitments = num_witnesses
+            .checked_mul(num_commitments)
+            .ok_or(SigmaProofError::MissingCommitments)?;
+        let total_responses = num_witnesses
+            .checked_mul(num_responses)
+            .ok_or(SigmaProofError::MissingResponses)?;
+        check_proof_shape(proof, total_commitments, total_responses)?;
+
+        // init transcript
+        let mut transcript: ProofTranscript =
+            start_transcript(Self::LABEL, Some(proof), b"", instance)?;
+        transcript.common_absorb_bytes(b"aggregate-size", &(num_witnesses as u64).to_le_bytes());
+
+        // -> A_1, ..., A_k
         let big_a = transcript
-            .verifier_receive_points(b"r", big_x_points.len())
+            .verifier_receive_points(Self::COMMIT_LABEL, total_commitments)
             .ok_or(SigmaProofError::TranscriptError)?;
+        reject_identity_commitments::<Self>(&big_a)?;
 
         // <- challenge
-        let e = transcript.challenge(b"e");
+        Self::bind_extra(&mut transcript);
+        let e = draw_challenge::<Self, _>(&mut transcript);
 
-        // -> sigma
+        // -> z_1, ..., z_k
         let sigmas = transcript
-            .verifier_receives_all_scalars(b"z")
+            .verifier_receive_scalars(Self::RESPONSE_LABEL, total_responses)
             .ok_or(SigmaProofError::TranscriptError)?;
-        println!("sigmas received: {}", sigmas.len());
-        let sigmas_as_input = Self::WITNESS::from_values(&sigmas)?;
-
-        let psi_output = Self::psi(&sigmas_as_input, instance);
 
         // checks
-        if big_x_points.len() != psi_output.len() {
-            return Err(SigmaProofError::PsiOutputLengthMismatch);
+        let mut valid = true;
+        for j in 0..num_witnesses {
+            let big_a_j = &big_a[j * num_commitments..(j + 1) * num_commitments];
+            let sigmas_j = &sigmas[j * num_responses..(j + 1) * num_responses];
+            let psi_output = psi_of_responses::<Self>(instance, sigmas_j)?;
+            match check_psi_output(&big_x_points, big_a_j, e, &psi_output) {
+                Ok(()) => {}
+                Err(SigmaProofError::EquationCheckFailed) => valid = false,
+                Err(err) => return Err(err),
+            }
         }
+        if valid {
+            Ok(())
+        } else {
+            Err(SigmaProofError::EquationCheckFailed)
+        }
+    }
+
+    /// The number of random bytes [`SigmaProof::prove`] draws from the RNG
+    /// for `instance`: each nonce scalar is sampled from 64 bytes and reduced.
+    fn randomness_bytes(instance: &Self::INSTANCE) -> SigmaProofResult<usize> {
+        Ok(Self::num_responses(instance)? * 64)
+    }
+
+    /// Checks that the instance is not degenerate, i.e. that none of its
+    /// points is the identity and that its points are pairwise distinct.
+    fn validate_instance(instance: &Self::INSTANCE) -> SigmaProofResult<()> {
+        let points = instance
+            .points()
+            .iter()
+            .map(SymPoint::evaluate)
+            .collect::<SigmaProofResult<Vec<_>>>()?;
 
-        for ((big_x_i, big_a_i), psi_i) in big_x_points.iter().zip(&big_a).zip(&psi_output) {
-            let rhs = big_a_i + e * big_x_i;
-            if psi_i.evaluate()? != rhs {
-                return Err(SigmaProofError::EquationCheckFailed);
+        if points.contains(&RistrettoPoint::identity()) {
+            return Err(DegeneracyKind::IdentityPoint.into());
+        }
+        for (i, point) in points.iter().enumerate() {
+            if points[i + 1..].contains(point) {
+                return Err(DegeneracyKind::DuplicatePoints.into());
             }
         }
 
         Ok(())
     }
 
-    /// Generate a specification document in Markdown+LaTeX format
-    fn spec() -> String {
-        let psi_in_len = Self::WITNESS::num_scalars();
-        let f_scalars_in = Self::INSTANCE::num_scalars();
-        let f_points_in = Self::INSTANCE::num_points();
-
-        let protocol_name = String::from_utf8_lossy(Self::LABEL);
+    /// Samples a random instance that [`SigmaProof::validate_instance`]
+    /// rejects with `kind`, for negative testing.
+    ///
+    /// Returns `None` if the instance has too few points to be degenerate in
+    /// that way.
+    fn random_degenerate_instance<R: CryptoRngCore + ?Sized>(
+        rng: &mut R,
+        kind: DegeneracyKind,
+    ) -> Option<Self::INSTANCE> {
+        let num_scalars = Self::INSTANCE::num_scalars().unwrap_or(RUNTIME_SAMPLE_LEN);
+        let num_points = Self::INSTANCE::num_points().unwrap_or(RUNTIME_SAMPLE_LEN);
+        let scalars: Vec<Scalar> = (0..num_scalars).map(|_| Scalar::random(rng)).collect();
+        let mut points: Vec<RistrettoPoint> = (0..num_points)
+            .map(|_| RistrettoPoint::random(rng))
+            .collect();
 
-        // Generate dummy witness with sequential scalars 1, 2, 3, etc.
-        let dummy_scalars: Vec<Scalar> = (1..=psi_in_len).map(|i| Scalar::from(i as u64)).collect();
-        let dummy_witness = match Self::WITNESS::from_values(&dummy_scalars) {
-            Ok(w) => w,
-            Err(_) => {
-                // Fallback if we can't create dummy witness
-                return format!(
-                    r#"#### {}
-Error: Could not generate symbolic analysis for this protocol."#,
-                    protocol_name
-                );
+        match kind {
+            DegeneracyKind::IdentityPoint => {
+                *points.first_mut()? = RistrettoPoint::identity();
             }
-        };
+            DegeneracyKind::DuplicatePoints => {
+                let first = *points.first()?;
+                *points.get_mut(1)? = first;
+            }
+        }
+
+        Self::INSTANCE::from_values(&scalars, &points).ok()
+    }
 
-        // Generate dummy instance with sequential scalars and distinct points
-        let dummy_f_scalars_in: Vec<Scalar> =
-            (1..=f_scalars_in).map(|i| Scalar::from(i as u64)).collect();
+    /// Symbolically evaluates `f` and `psi` on placeholder values, so that the
+    /// statement can be rendered with the names of the witness and instance
+    /// fields.
+    fn statement() -> SigmaProofResult<Statement> {
+        // Generate dummy instance with scalars -1, -2, -3, etc., which cannot
+        // be mistaken for witness placeholders or small constants, and
+        // distinct points. Values of runtime length get RUNTIME_SAMPLE_LEN
+        // entries.
+        let num_instance_scalars = Self::INSTANCE::num_scalars().unwrap_or(RUNTIME_SAMPLE_LEN);
+        let num_instance_points = Self::INSTANCE::num_points().unwrap_or(RUNTIME_SAMPLE_LEN);
+        let dummy_instance_scalars: Vec<Scalar> = (1..=num_instance_scalars)
+            .map(|i| -Scalar::from(i as u64))
+            .collect();
         // Use different multiples of G for different instance points to distinguish them
-        let dummy_instance_points: Vec<RistrettoPoint> = (0..f_points_in)
+        let dummy_instance_points: Vec<RistrettoPoint> = (0..num_instance_points)
             .map(|i| Scalar::from((i + 2) as u64) * RISTRETTO_BASEPOINT_POINT)
             .collect();
         let dummy_instance =
-            match Self::INSTANCE::from_values(&dummy_f_scalars_in, &dummy_instance_points) {
-                Ok(i) => i,
-                Err(_) => {
-                    // Fallback if we can't create dummy instance
-                    return format!(
-                        r#"#### {}
-Error: Could not generate symbolic analysis for this protocol."#,
-                        protocol_name
-                    );
-                }
-            };
+            Self::INSTANCE::from_values(&dummy_instance_scalars, &dummy_instance_points)?;
 
-        // Get variable names for the witness
-        let var_names: Vec<&str> = (0..psi_in_len)
-            .map(|i| Self::WITNESS::get_var_name(i))
+        // Generate dummy witness with sequential scalars 1, 2, 3, etc., as
+        // many as the dummy instance expects
+        let num_witness_scalars = Self::num_responses(&dummy_instance)?;
+        let dummy_scalars: Vec<Scalar> = (1..=num_witness_scalars)
+            .map(|i| Scalar::from(i as u64))
             .collect();
+        let dummy_witness = Self::WITNESS::from_values(&dummy_scalars)?;
 
-        // Get instance field names for better output
+        // Pair each dummy instance value with its field name
         let instance_field_names = Self::INSTANCE::get_field_names();
-
-        // Symbolically evaluate f function (instance function)
-        let f_result = Self::f(&dummy_instance);
-
-        // Convert f result to LaTeX with field name tracking
-        let f_equations: Vec<String> = f_result
+        let instance_scalars = dummy_instance_scalars
             .iter()
-            .map(|point| {
-                // For each output, try to match it to an instance field
-                match point {
-                    SymPoint::Const(p) if *p == Scalar::from(2u64) * RISTRETTO_BASEPOINT_POINT => {
-                        // First instance point field
-                        if instance_field_names.len() > f_scalars_in {
-                            latex_var(&instance_field_names[f_scalars_in])
-                        } else {
-                            "P_1".to_string()
-                        }
-                    }
-                    SymPoint::Const(p) if *p == Scalar::from(3u64) * RISTRETTO_BASEPOINT_POINT => {
-                        // Second instance point field
-                        if instance_field_names.len() > f_scalars_in + 1 {
-                            latex_var(&instance_field_names[f_scalars_in + 1])
-                        } else {
-                            "P_2".to_string()
-                        }
-                    }
-                    SymPoint::Const(p) if *p == Scalar::from(4u64) * RISTRETTO_BASEPOINT_POINT => {
-                        // Third instance point field
-                        if instance_field_names.len() > f_scalars_in + 2 {
-                            latex_var(&instance_field_names[f_scalars_in + 2])
-                        } else {
-                            "P_3".to_string()
-                        }
-                    }
-                    _ => sympoint_to_latex_with_context(point, &var_names, true),
-                }
+            .enumerate()
+            .map(|(i, s)| {
+                let name = instance_field_names.get(i).cloned();
+                (*s, name.unwrap_or_else(|| "s".to_string()))
             })
             .collect();
-
-        // Symbolically evaluate psi function
-        let psi_result = Self::psi(&dummy_witness, &dummy_instance);
-
-        // Convert psi result to LaTeX
-        let psi_equations: Vec<String> = psi_result
+        let instance_points = dummy_instance_points
             .iter()
-            .map(|point| sympoint_to_latex(point, &var_names))
+            .enumerate()
+            .map(|(i, p)| {
+                let name = instance_field_names
+                    .get(dummy_instance_scalars.len() + i)
+                    .cloned()
+                    .unwrap_or_else(|| "P".to_string());
+                (*p, name)
+            })
             .collect();
 
+        Ok(Statement {
+            label: Self::LABEL,
+            witness: (0..num_witness_scalars)
+                .map(Self::WITNESS::get_var_name)
+                .collect(),
+            instance_scalars,
+            instance_points,
+            f: Self::f(&dummy_instance),
+            psi: Self::psi(&dummy_witness, &dummy_instance),
+        })
+    }
+
+    /// Exports the statement in the line-based DSL described in
+    /// [`crate::statement`].
+    fn to_dsl() -> SigmaProofResult<String> {
+        Ok(Self::statement()?.to_dsl())
+    }
+
+    /// A stable fingerprint of the protocol's shape, e.g. to key a registry
+    /// of protocols, see [`Statement::hash`].
+    fn statement_hash() -> SigmaProofResult<[u8; 32]> {
+        Ok(Self::statement()?.hash())
+    }
+
+    /// The named generators, i.e. the [`SymPoint::WellKnownConst`] bases,
+    /// that `f` and `psi` use, in order of first appearance in `psi` then
+    /// `f`.
+    ///
+    /// A verifier can compare them, e.g. by their compressed encodings, with
+    /// those of the prover to confirm that both use the same bases.
+    fn public_parameters() -> SigmaProofResult<Vec<(&'static str, RistrettoPoint)>> {
+        let statement = Self::statement()?;
+        let mut generators = Vec::new();
+        for point in statement.psi.iter().chain(&statement.f) {
+            point.collect_generators_into(&mut generators);
+        }
+        Ok(generators
+            .into_iter()
+            .filter_map(|(name, point)| Some((name?, point)))
+            .collect())
+    }
+
+    /// Renders the protocol's constraint graph in Graphviz DOT, see
+    /// [`Statement::to_dot`].
+    fn spec_dot() -> String {
+        match Self::statement() {
+            Ok(statement) => statement.to_dot(),
+            // fallback if we can't create a dummy witness or instance
+            Err(e) => format!("digraph {{\n  error [label=\"{e}\", shape=plaintext];\n}}\n"),
+        }
+    }
+
+    /// Generate a specification document in Markdown+LaTeX format
+    ///
+    /// If the statement cannot be generated, the document only holds the
+    /// error, see [`SigmaProof::try_spec`].
+    fn spec() -> String {
+        Self::try_spec().unwrap_or_else(|e| {
+            format!(
+                "#### {}\nError: Could not generate symbolic analysis for this protocol: {e}",
+                String::from_utf8_lossy(Self::LABEL)
+            )
+        })
+    }
+
+    /// Like [`SigmaProof::spec`], but returns the error that prevented the
+    /// statement from being generated, e.g. a witness or instance that cannot
+    /// be built from as many placeholders as it declares, or `f` and `psi`
+    /// with different numbers of outputs.
+    fn try_spec() -> SigmaProofResult<String> {
+        let protocol_name = String::from_utf8_lossy(Self::LABEL);
+
+        let statement = Self::statement()?;
+        if statement.f.len() != statement.psi.len() {
+            return Err(SigmaProofError::PsiOutputLengthMismatch);
+        }
+        let psi_in_len = statement.witness.len();
+        let f_scalars_in = statement.instance_scalars.len();
+        let f_points_in = statement.instance_points.len();
+        let var_names = &statement.witness;
+        let f_result = &statement.f;
+        let psi_result = &statement.psi;
+        let latex = LatexContext::new(&statement);
+
+        // Convert f and psi results to LaTeX
+        let f_equations: Vec<String> = f_result.iter().map(|point| latex.point(point)).collect();
+        let psi_equations: Vec<String> =
+            psi_result.iter().map(|point| latex.point(point)).collect();
+
+        // generators used by psi, named when well-known
+        let mut generators = Vec::new();
+        for point in psi_result {
+            point.collect_generators_into(&mut generators);
+        }
+        let generators = generators
+            .into_iter()
+            .map(|(name, point)| match name {
+                Some(name) => name.to_string(),
+                None => latex.constant_point(&point),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
         let checks = psi_equations
             .iter()
             .zip(f_equations.iter())
@@ -342,7 +1625,7 @@ Error: Could not generate symbolic analysis for this protocol."#,
             .collect::<Vec<_>>()
             .join("\n");
 
-        format!(
+        Ok(format!(
             r#"The Sigma protocol is labeled as `{protocol_name}`.
 
 The **witness** is defined as $\mathbf \omega = \{{ {witness_field_names} \}}$.
@@ -360,6 +1643,8 @@ $$
 \end{{aligned}}
 $$
 
+The generators used by $\psi$ are ${generators}$.
+
 The transformation $f$ is defined as:
 
 $$
@@ -386,6 +1671,119 @@ In other words, the following is being proven:
                 .map(|name| latex_var(name))
                 .collect::<Vec<_>>()
                 .join(", "),
-        )
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sigmas::G;
+
+    /// Random equations `psi_i = A_i + e*X_i`, with `psi_i` evaluated through
+    /// another sum than the right-hand side so that the points have
+    /// different internal representations.
+    fn random_equations(
+        n: usize,
+    ) -> (
+        Vec<RistrettoPoint>,
+        Vec<RistrettoPoint>,
+        Scalar,
+        Vec<SymPoint>,
+    ) {
+        let rng = &mut rand::rngs::OsRng;
+        let big_x: Vec<_> = (0..n).map(|_| RistrettoPoint::random(rng)).collect();
+        let big_a: Vec<_> = (0..n).map(|_| RistrettoPoint::random(rng)).collect();
+        let e = Scalar::random(rng);
+        let psi = big_x
+            .iter()
+            .zip(&big_a)
+            .map(|(x, a)| e * SymPoint::Const(*x) + SymPoint::Const(*a))
+            .collect();
+        (big_x, big_a, e, psi)
+    }
+
+    /// Checks the equations by comparing compressed encodings.
+    fn compressed_check(
+        big_x: &[RistrettoPoint],
+        big_a: &[RistrettoPoint],
+        e: Scalar,
+        psi: &[SymPoint],
+    ) -> bool {
+        big_x
+            .iter()
+            .zip(big_a)
+            .zip(psi)
+            .all(|((x, a), psi)| psi.evaluate().unwrap().compress() == (a + e * x).compress())
+    }
+
+    /// Checks the equations at once, as `sum_i w_i*(psi_i - A_i - e*X_i) = 0`
+    /// for random weights `w_i`.
+    fn folded_check(
+        big_x: &[RistrettoPoint],
+        big_a: &[RistrettoPoint],
+        e: Scalar,
+        psi: &[SymPoint],
+    ) -> bool {
+        use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+
+        let rng = &mut rand::rngs::OsRng;
+        let mut scalars = Vec::with_capacity(3 * psi.len());
+        let mut points = Vec::with_capacity(3 * psi.len());
+        for ((x, a), psi) in big_x.iter().zip(big_a).zip(psi) {
+            let w = Scalar::random(rng);
+            scalars.extend([w, -w, -w * e]);
+            points.extend([psi.evaluate().unwrap(), *a, *x]);
+        }
+        RistrettoPoint::vartime_multiscalar_mul(&scalars, &points).is_identity()
+    }
+
+    #[test]
+    fn test_equation_check_agrees_with_compressed_comparison() {
+        let (big_x, big_a, e, psi) = random_equations(4);
+        assert!(compressed_check(&big_x, &big_a, e, &psi));
+        assert!(folded_check(&big_x, &big_a, e, &psi));
+        check_psi_output(&big_x, &big_a, e, &psi).unwrap();
+
+        for i in 0..psi.len() {
+            let mut wrong = psi.clone();
+            wrong[i] = wrong[i].clone() + G;
+            assert!(!compressed_check(&big_x, &big_a, e, &wrong));
+            assert!(!folded_check(&big_x, &big_a, e, &wrong));
+            assert!(matches!(
+                check_psi_output(&big_x, &big_a, e, &wrong),
+                Err(SigmaProofError::EquationCheckFailed)
+            ));
+        }
+    }
+
+    /// Compares ways of checking the equations `psi_i = A_i + e*X_i`, from
+    /// the evaluated `A` and `X` and the symbolic `psi(z)` as the verifier
+    /// has them, run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_equation_check() {
+        use std::time::Instant;
+
+        let (big_x, big_a, e, psi) = random_equations(1000);
+
+        let start = Instant::now();
+        let points = check_psi_output(&big_x, &big_a, e, &psi).is_ok();
+        let points_time = start.elapsed();
+
+        let start = Instant::now();
+        let compressed = compressed_check(&big_x, &big_a, e, &psi);
+        let compressed_time = start.elapsed();
+
+        let start = Instant::now();
+        let folded = folded_check(&big_x, &big_a, e, &psi);
+        let folded_time = start.elapsed();
+
+        assert!(points && compressed && folded);
+        println!(
+            "{} equations: points {points_time:?}, compressed {compressed_time:?}, \
+             folded {folded_time:?}",
+            psi.len()
+        );
     }
 }
@@ -2,8 +2,17 @@
 // Symbolic
 //
 
-use curve25519_dalek::{RistrettoPoint, Scalar};
-use std::ops::{Add, Mul, Neg, Sub};
+use alloc::{boxed::Box, collections::BTreeMap, vec, vec::Vec};
+use core::{
+    marker::PhantomData,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+use curve25519_dalek::{
+    ristretto::CompressedRistretto,
+    traits::{Identity, MultiscalarMul},
+    RistrettoPoint, Scalar,
+};
+use sha2::Sha512;
 
 use crate::errors::SigmaProofError;
 
@@ -15,23 +24,196 @@ pub enum SymScalar {
     Sub(Box<SymScalar>, Box<SymScalar>),
     Neg(Box<SymScalar>),
     Mul(Box<SymScalar>, Box<SymScalar>),
+    Inv(Box<SymScalar>),
+    Pow(Box<SymScalar>, u32),
 }
 
+/// The message of the `expect`s of the iterative evaluations, which push the
+/// values of the operands of a node before applying it.
+const OPERAND: &str = "operands are evaluated before their node";
+
 impl SymScalar {
+    /// Evaluates the expression, with an explicit stack rather than
+    /// recursively, so that deep expressions do not overflow the stack.
     pub fn evaluate(&self) -> Result<Scalar, SigmaProofError> {
+        enum Step<'a> {
+            Visit(&'a SymScalar),
+            Apply(&'a SymScalar),
+        }
+
+        let mut steps = vec![Step::Visit(self)];
+        let mut values = Vec::new();
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Visit(node) => match node {
+                    SymScalar::Const(s) => values.push(*s),
+                    SymScalar::Var(s) => {
+                        values.push(s.ok_or(SigmaProofError::UninstantiatedScalar)?)
+                    }
+                    SymScalar::Add(s1, s2) | SymScalar::Sub(s1, s2) | SymScalar::Mul(s1, s2) => {
+                        steps.extend([Step::Apply(node), Step::Visit(s2), Step::Visit(s1)]);
+                    }
+                    SymScalar::Neg(s) | SymScalar::Inv(s) | SymScalar::Pow(s, _) => {
+                        steps.extend([Step::Apply(node), Step::Visit(s)]);
+                    }
+                },
+                Step::Apply(node) => {
+                    let rhs = values.pop().expect(OPERAND);
+                    let value = match node {
+                        SymScalar::Neg(_) => -rhs,
+                        SymScalar::Inv(_) => {
+                            if rhs == Scalar::ZERO {
+                                return Err(SigmaProofError::DivisionByZero);
+                            }
+                            rhs.invert()
+                        }
+                        SymScalar::Pow(_, exp) => pow(rhs, *exp),
+                        node => {
+                            let lhs = values.pop().expect(OPERAND);
+                            match node {
+                                SymScalar::Add(..) => lhs + rhs,
+                                SymScalar::Sub(..) => lhs - rhs,
+                                SymScalar::Mul(..) => lhs * rhs,
+                                _ => unreachable!("leaves are not applied"),
+                            }
+                        }
+                    };
+                    values.push(value);
+                }
+            }
+        }
+        Ok(values.pop().expect(OPERAND))
+    }
+
+    /// The operands of the node.
+    fn children(&self) -> [Option<&SymScalar>; 2] {
         match self {
-            SymScalar::Const(s) => Ok(*s),
-            SymScalar::Var(s) => s.ok_or(SigmaProofError::UninstantiatedScalar),
-            SymScalar::Add(s1, s2) => Ok(s1.evaluate()? + s2.evaluate()?),
-            SymScalar::Sub(s1, s2) => Ok(s1.evaluate()? - s2.evaluate()?),
-            SymScalar::Neg(s) => Ok(-s.evaluate()?),
-            SymScalar::Mul(s1, s2) => Ok(s1.evaluate()? * s2.evaluate()?),
+            SymScalar::Const(_) | SymScalar::Var(_) => [None, None],
+            SymScalar::Neg(s) | SymScalar::Inv(s) | SymScalar::Pow(s, _) => [Some(s), None],
+            SymScalar::Add(s1, s2) | SymScalar::Sub(s1, s2) | SymScalar::Mul(s1, s2) => {
+                [Some(s1), Some(s2)]
+            }
+        }
+    }
+
+    /// The number of nodes on the longest path from the root to a leaf, 1
+    /// for a leaf.
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut stack = vec![(self, 1)];
+        while let Some((node, d)) = stack.pop() {
+            depth = depth.max(d);
+            stack.extend(node.children().into_iter().flatten().map(|s| (s, d + 1)));
+        }
+        depth
+    }
+
+    /// The number of nodes of the expression, leaves included.
+    pub fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(node.children().into_iter().flatten());
+        }
+        count
+    }
+
+    /// The multiplicative inverse, which fails to evaluate if `self` is zero.
+    pub fn inverse(&self) -> SymScalar {
+        SymScalar::Inv(Box::new(self.clone()))
+    }
+
+    /// `self` raised to the power `exp`, with `x.pow(0) == 1` even for zero.
+    pub fn pow(self, exp: u32) -> SymScalar {
+        SymScalar::Pow(Box::new(self), exp)
+    }
+
+    /// Fails with [`SigmaProofError::UninstantiatedScalar`] if the expression
+    /// contains an uninstantiated variable.
+    pub fn check_instantiated(&self) -> Result<(), SigmaProofError> {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if matches!(node, SymScalar::Var(None)) {
+                return Err(SigmaProofError::UninstantiatedScalar);
+            }
+            stack.extend(node.children().into_iter().flatten());
+        }
+        Ok(())
+    }
+
+    /// Like `self + rhs`, but fails right away if an operand contains an
+    /// uninstantiated variable instead of when the sum is evaluated.
+    pub fn try_add(self, rhs: SymScalar) -> Result<SymScalar, SigmaProofError> {
+        self.check_instantiated()?;
+        rhs.check_instantiated()?;
+        Ok(self + rhs)
+    }
+
+    /// Like [`SymScalar::try_add`], for `self - rhs`.
+    pub fn try_sub(self, rhs: SymScalar) -> Result<SymScalar, SigmaProofError> {
+        self.check_instantiated()?;
+        rhs.check_instantiated()?;
+        Ok(self - rhs)
+    }
+
+    /// Like [`SymScalar::try_add`], for `self * rhs`.
+    pub fn try_mul(self, rhs: SymScalar) -> Result<SymScalar, SigmaProofError> {
+        self.check_instantiated()?;
+        rhs.check_instantiated()?;
+        Ok(self * rhs)
+    }
+
+    /// Like [`SymScalar::try_add`], for `self * point`.
+    pub fn try_scale(self, point: SymPoint) -> Result<SymPoint, SigmaProofError> {
+        self.check_instantiated()?;
+        point.check_instantiated()?;
+        Ok(self * point)
+    }
+
+    /// The constant `bytes` reduced modulo the group order `l`.
+    pub fn from_bytes_mod_order(bytes: [u8; 32]) -> SymScalar {
+        SymScalar::Const(Scalar::from_bytes_mod_order(bytes))
+    }
+
+    /// Hashes `bytes` to a constant with SHA-512, e.g. to derive a witness
+    /// from a secret seed.
+    pub fn hash_to_scalar(bytes: &[u8]) -> SymScalar {
+        SymScalar::Const(Scalar::hash_from_bytes::<Sha512>(bytes))
+    }
+}
+
+/// `base^exp` by square-and-multiply, from the most significant bit of `exp`.
+fn pow(base: Scalar, exp: u32) -> Scalar {
+    let mut result = Scalar::ONE;
+    for bit in (0..u32::BITS - exp.leading_zeros()).rev() {
+        result *= result;
+        if (exp >> bit) & 1 == 1 {
+            result *= base;
+        }
+    }
+    result
+}
+
+/// Zeroizes every scalar leaf of the expression.
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for SymScalar {
+    fn zeroize(&mut self) {
+        match self {
+            SymScalar::Const(s) | SymScalar::Var(Some(s)) => s.zeroize(),
+            SymScalar::Var(None) => {}
+            SymScalar::Neg(s) | SymScalar::Inv(s) | SymScalar::Pow(s, _) => s.zeroize(),
+            SymScalar::Add(s1, s2) | SymScalar::Sub(s1, s2) | SymScalar::Mul(s1, s2) => {
+                s1.zeroize();
+                s2.zeroize();
+            }
         }
     }
 }
 
 #[derive(Clone)]
 pub enum SymPoint {
+    Identity,
     WellKnownConst(&'static str, RistrettoPoint),
     Const(RistrettoPoint),
     Var(Option<RistrettoPoint>),
@@ -41,20 +223,470 @@ pub enum SymPoint {
     Scale(Box<SymScalar>, Box<SymPoint>),
 }
 
+/// Computes `s * point` for a [`SymPoint::WellKnownConst`], with a
+/// precomputed table if it is one of the generators of [`crate::sigmas`].
+fn scale_well_known(s: &Scalar, name: &str, point: &RistrettoPoint) -> RistrettoPoint {
+    crate::sigmas::mul_generator(name, point, s).unwrap_or_else(|| s * point)
+}
+
 impl SymPoint {
+    /// Evaluates the expression, with an explicit stack rather than
+    /// recursively, so that deep expressions do not overflow the stack.
+    ///
+    /// A sum is split into the scalings `s*P` it adds up, computed with a
+    /// single multiscalar multiplication, and its other summands. Scalings of
+    /// a [`SymPoint::WellKnownConst`] are among the latter, as their
+    /// precomputed tables are faster than a multiscalar multiplication.
     pub fn evaluate(&self) -> Result<RistrettoPoint, SigmaProofError> {
+        enum Step<'a> {
+            Visit(&'a SymPoint),
+            Sub,
+            Neg,
+            Scale(Scalar),
+            /// Adds up the points scaled by `scalars`, then `rest` other
+            /// summands, evaluated in this order.
+            Sum {
+                scalars: Vec<Scalar>,
+                rest: usize,
+            },
+        }
+
+        let mut steps = vec![Step::Visit(self)];
+        let mut values = Vec::new();
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Visit(node) => match node {
+                    SymPoint::Identity => values.push(RistrettoPoint::identity()),
+                    SymPoint::WellKnownConst(_, p) | SymPoint::Const(p) => values.push(*p),
+                    SymPoint::Var(p) => values.push(p.ok_or(SigmaProofError::UninstantiatedPoint)?),
+                    SymPoint::Add(..) => {
+                        let mut scalars = Vec::new();
+                        let mut points = Vec::new();
+                        let mut rest = Vec::new();
+                        let mut summands = vec![node];
+                        while let Some(summand) = summands.pop() {
+                            match summand {
+                                SymPoint::Add(p1, p2) => summands.extend([&**p2, &**p1]),
+                                SymPoint::Scale(s, p)
+                                    if !matches!(p.as_ref(), SymPoint::WellKnownConst(..)) =>
+                                {
+                                    scalars.push(s.evaluate()?);
+                                    points.push(&**p);
+                                }
+                                p => rest.push(p),
+                            }
+                        }
+                        steps.push(Step::Sum {
+                            scalars,
+                            rest: rest.len(),
+                        });
+                        steps.extend(rest.into_iter().rev().map(Step::Visit));
+                        steps.extend(points.into_iter().rev().map(Step::Visit));
+                    }
+                    SymPoint::Sub(p1, p2) => {
+                        steps.extend([Step::Sub, Step::Visit(p2), Step::Visit(p1)]);
+                    }
+                    SymPoint::Neg(p) => steps.extend([Step::Neg, Step::Visit(p)]),
+                    SymPoint::Scale(s, p) => match p.as_ref() {
+                        SymPoint::WellKnownConst(name, p) => {
+                            values.push(scale_well_known(&s.evaluate()?, name, p));
+                        }
+                        p => steps.extend([Step::Scale(s.evaluate()?), Step::Visit(p)]),
+                    },
+                },
+                Step::Sub => {
+                    let rhs = values.pop().expect(OPERAND);
+                    let lhs = values.pop().expect(OPERAND);
+                    values.push(lhs - rhs);
+                }
+                Step::Neg => {
+                    let p = values.pop().expect(OPERAND);
+                    values.push(-p);
+                }
+                Step::Scale(s) => {
+                    let p = values.pop().expect(OPERAND);
+                    values.push(s * p);
+                }
+                Step::Sum { scalars, rest } => {
+                    let operands = values.split_off(values.len() - scalars.len() - rest);
+                    let (points, rest) = operands.split_at(scalars.len());
+                    let rest: RistrettoPoint = rest.iter().sum();
+                    values.push(rest + RistrettoPoint::multiscalar_mul(scalars, points));
+                }
+            }
+        }
+        Ok(values.pop().expect(OPERAND))
+    }
+
+    /// The point operands of the node, and the scalar of a scaling.
+    fn children(&self) -> (Option<&SymScalar>, [Option<&SymPoint>; 2]) {
+        match self {
+            SymPoint::Identity
+            | SymPoint::WellKnownConst(..)
+            | SymPoint::Const(_)
+            | SymPoint::Var(_) => (None, [None, None]),
+            SymPoint::Neg(p) => (None, [Some(p), None]),
+            SymPoint::Add(p1, p2) | SymPoint::Sub(p1, p2) => (None, [Some(p1), Some(p2)]),
+            SymPoint::Scale(s, p) => (Some(s), [Some(p), None]),
+        }
+    }
+
+    /// The number of nodes on the longest path from the root to a leaf, 1
+    /// for a leaf. The path may end in the scalar of a scaling.
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut stack = vec![(self, 1)];
+        while let Some((node, d)) = stack.pop() {
+            depth = depth.max(d);
+            let (scalar, points) = node.children();
+            if let Some(s) = scalar {
+                depth = depth.max(d + s.depth());
+            }
+            stack.extend(points.into_iter().flatten().map(|p| (p, d + 1)));
+        }
+        depth
+    }
+
+    /// The number of nodes of the expression, leaves and the nodes of the
+    /// scalars of scalings included.
+    pub fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            count += 1;
+            let (scalar, points) = node.children();
+            count += scalar.map_or(0, SymScalar::node_count);
+            stack.extend(points.into_iter().flatten());
+        }
+        count
+    }
+
+    /// The sum `s_1*P_1 + ... + s_n*P_n` of `terms`, as a balanced tree of
+    /// additions rather than the chain the operators build, or the identity
+    /// if there are no terms.
+    ///
+    /// [`SymPoint::evaluate`] computes sums of scalings, whatever their shape,
+    /// with a single multiscalar multiplication.
+    pub fn linear_combination(terms: &[(SymScalar, SymPoint)]) -> SymPoint {
+        match terms {
+            [] => SymPoint::Identity,
+            [(s, p)] => SymPoint::Scale(Box::new(s.clone()), Box::new(p.clone())),
+            terms => {
+                let (left, right) = terms.split_at(terms.len() / 2);
+                SymPoint::Add(
+                    Box::new(Self::linear_combination(left)),
+                    Box::new(Self::linear_combination(right)),
+                )
+            }
+        }
+    }
+
+    /// Fails with [`SigmaProofError::UninstantiatedPoint`], or
+    /// [`SigmaProofError::UninstantiatedScalar`] for a scaling, if the
+    /// expression contains an uninstantiated variable.
+    pub fn check_instantiated(&self) -> Result<(), SigmaProofError> {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if matches!(node, SymPoint::Var(None)) {
+                return Err(SigmaProofError::UninstantiatedPoint);
+            }
+            let (scalar, points) = node.children();
+            if let Some(s) = scalar {
+                s.check_instantiated()?;
+            }
+            stack.extend(points.into_iter().flatten().rev());
+        }
+        Ok(())
+    }
+
+    /// Like `self + rhs`, but fails right away if an operand contains an
+    /// uninstantiated variable instead of when the sum is evaluated.
+    pub fn try_add(self, rhs: SymPoint) -> Result<SymPoint, SigmaProofError> {
+        self.check_instantiated()?;
+        rhs.check_instantiated()?;
+        Ok(self + rhs)
+    }
+
+    /// Like [`SymPoint::try_add`], for `self - rhs`.
+    pub fn try_sub(self, rhs: SymPoint) -> Result<SymPoint, SigmaProofError> {
+        self.check_instantiated()?;
+        rhs.check_instantiated()?;
+        Ok(self - rhs)
+    }
+
+    pub fn is_identity(&self) -> Result<bool, SigmaProofError> {
+        Ok(self.evaluate()? == RistrettoPoint::identity())
+    }
+
+    /// The canonical encoding of the point the expression evaluates to, e.g.
+    /// to deduplicate bases. Expressions of the same point have equal
+    /// encodings, whatever their shape.
+    pub fn canonical(&self) -> Result<CanonicalPoint, SigmaProofError> {
+        Ok(CanonicalPoint(self.evaluate()?.compress()))
+    }
+
+    /// Like [`SymPoint::evaluate`], but looks up and records the value of
+    /// every inner node in `cache`, so that nodes reached again, e.g. when
+    /// evaluating several expressions borrowed from the same tree, are only
+    /// computed once. It walks the tree with an explicit stack too.
+    pub fn evaluate_cached<'a>(
+        &'a self,
+        cache: &mut EvalCache<'a>,
+    ) -> Result<RistrettoPoint, SigmaProofError> {
+        enum Step<'a> {
+            Visit(&'a SymPoint),
+            /// Combines the values of the operands of the node, and caches
+            /// the result.
+            Combine(&'a SymPoint),
+        }
+
+        let mut steps = vec![Step::Visit(self)];
+        let mut values = Vec::new();
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Visit(node) => {
+                    if let Some(point) = cache.points.get(&(node as *const SymPoint)) {
+                        values.push(*point);
+                        continue;
+                    }
+                    match node {
+                        SymPoint::Identity
+                        | SymPoint::WellKnownConst(..)
+                        | SymPoint::Const(_)
+                        | SymPoint::Var(_) => values.push(node.evaluate()?),
+                        SymPoint::Add(p1, p2) | SymPoint::Sub(p1, p2) => {
+                            steps.extend([Step::Combine(node), Step::Visit(p2), Step::Visit(p1)]);
+                        }
+                        SymPoint::Neg(p) => steps.extend([Step::Combine(node), Step::Visit(p)]),
+                        SymPoint::Scale(s, p) => match p.as_ref() {
+                            SymPoint::WellKnownConst(name, p) => {
+                                let point = scale_well_known(&s.evaluate()?, name, p);
+                                cache.points.insert(node, point);
+                                values.push(point);
+                            }
+                            p => steps.extend([Step::Combine(node), Step::Visit(p)]),
+                        },
+                    }
+                }
+                Step::Combine(node) => {
+                    let point = match node {
+                        SymPoint::Add(..) | SymPoint::Sub(..) => {
+                            let rhs = values.pop().expect(OPERAND);
+                            let lhs = values.pop().expect(OPERAND);
+                            if matches!(node, SymPoint::Add(..)) {
+                                lhs + rhs
+                            } else {
+                                lhs - rhs
+                            }
+                        }
+                        SymPoint::Neg(_) => -values.pop().expect(OPERAND),
+                        SymPoint::Scale(s, _) => s.evaluate()? * values.pop().expect(OPERAND),
+                        _ => unreachable!("only inner nodes are combined"),
+                    };
+                    cache.points.insert(node, point);
+                    values.push(point);
+                }
+            }
+        }
+        Ok(values.pop().expect(OPERAND))
+    }
+
+    /// Returns an equivalent expression in which scalings only apply to
+    /// leaves: `s*(p + q)` becomes `s*p + s*q`, and `a*(b*p)` becomes
+    /// `(a*b)*p`.
+    pub fn distribute(&self) -> SymPoint {
+        self.distribute_by(None)
+    }
+
+    /// Like [`SymPoint::distribute`], with every leaf also scaled by `factor`.
+    fn distribute_by(&self, factor: Option<&SymScalar>) -> SymPoint {
+        match self {
+            SymPoint::Identity => SymPoint::Identity,
+            SymPoint::WellKnownConst(..) | SymPoint::Const(_) | SymPoint::Var(_) => match factor {
+                Some(s) => SymPoint::Scale(Box::new(s.clone()), Box::new(self.clone())),
+                None => self.clone(),
+            },
+            SymPoint::Add(p1, p2) => SymPoint::Add(
+                Box::new(p1.distribute_by(factor)),
+                Box::new(p2.distribute_by(factor)),
+            ),
+            SymPoint::Sub(p1, p2) => SymPoint::Sub(
+                Box::new(p1.distribute_by(factor)),
+                Box::new(p2.distribute_by(factor)),
+            ),
+            SymPoint::Neg(p) => SymPoint::Neg(Box::new(p.distribute_by(factor))),
+            SymPoint::Scale(s, p) => match factor {
+                Some(outer) => p.distribute_by(Some(&(outer * s.as_ref()))),
+                None => p.distribute_by(Some(s)),
+            },
+        }
+    }
+
+    /// The distinct leaf points of the expression, in order of first
+    /// appearance, with the name of those that are [`SymPoint::WellKnownConst`].
+    ///
+    /// The identity and uninstantiated variables are skipped. A point that
+    /// appears both named and anonymously is reported with its name.
+    pub fn collect_generators(&self) -> Vec<(Option<&'static str>, RistrettoPoint)> {
+        let mut generators = Vec::new();
+        self.collect_generators_into(&mut generators);
+        generators
+    }
+
+    /// Like [`SymPoint::collect_generators`], adding to `generators`.
+    pub(crate) fn collect_generators_into(
+        &self,
+        generators: &mut Vec<(Option<&'static str>, RistrettoPoint)>,
+    ) {
+        let (name, point) = match self {
+            SymPoint::Identity | SymPoint::Var(None) => return,
+            SymPoint::WellKnownConst(name, p) => (Some(*name), *p),
+            SymPoint::Const(p) | SymPoint::Var(Some(p)) => (None, *p),
+            SymPoint::Add(p1, p2) | SymPoint::Sub(p1, p2) => {
+                p1.collect_generators_into(generators);
+                p2.collect_generators_into(generators);
+                return;
+            }
+            SymPoint::Neg(p) | SymPoint::Scale(_, p) => {
+                p.collect_generators_into(generators);
+                return;
+            }
+        };
+        match generators.iter_mut().find(|(_, p)| *p == point) {
+            Some(found) => found.0 = found.0.or(name),
+            None => generators.push((name, point)),
+        }
+    }
+
+    /// Returns an equivalent expression with trivial scalings removed:
+    /// `1*p` becomes `p`, and `0*p` and `s*Identity` become `Identity`.
+    pub fn simplify(&self) -> SymPoint {
         match self {
-            SymPoint::WellKnownConst(_, p) => Ok(*p),
-            SymPoint::Const(p) => Ok(*p),
-            SymPoint::Var(p) => p.ok_or(SigmaProofError::UninstantiatedPoint),
-            SymPoint::Add(p1, p2) => Ok(p1.evaluate()? + p2.evaluate()?),
-            SymPoint::Sub(p1, p2) => Ok(p1.evaluate()? - p2.evaluate()?),
-            SymPoint::Neg(p) => Ok(-p.evaluate()?),
-            SymPoint::Scale(s, p) => Ok(s.evaluate()? * p.evaluate()?),
+            SymPoint::Identity
+            | SymPoint::WellKnownConst(..)
+            | SymPoint::Const(_)
+            | SymPoint::Var(_) => self.clone(),
+            SymPoint::Add(p1, p2) => {
+                SymPoint::Add(Box::new(p1.simplify()), Box::new(p2.simplify()))
+            }
+            SymPoint::Sub(p1, p2) => {
+                SymPoint::Sub(Box::new(p1.simplify()), Box::new(p2.simplify()))
+            }
+            SymPoint::Neg(p) => SymPoint::Neg(Box::new(p.simplify())),
+            SymPoint::Scale(s, p) => match (s.as_ref(), p.simplify()) {
+                (_, SymPoint::Identity) => SymPoint::Identity,
+                (SymScalar::Const(c), _) if *c == Scalar::ZERO => SymPoint::Identity,
+                (SymScalar::Const(c), p) if *c == Scalar::ONE => p,
+                (s, p) => SymPoint::Scale(Box::new(s.clone()), Box::new(p)),
+            },
         }
     }
 }
 
+/// Memoizes [`SymPoint::evaluate_cached`].
+///
+/// Nodes are keyed by their address, not their contents: a node is only
+/// found again if it is the very same node, while an equal node elsewhere,
+/// e.g. in a clone, is evaluated anew. The cache borrows every expression it
+/// was filled from, so that no address can be reused while it is alive.
+#[derive(Default)]
+pub struct EvalCache<'a> {
+    points: BTreeMap<*const SymPoint, RistrettoPoint>,
+    _expressions: PhantomData<&'a SymPoint>,
+}
+
+impl EvalCache<'_> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of nodes whose value is cached.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+/// The compressed encoding of a point, returned by [`SymPoint::canonical`].
+///
+/// Ristretto encodings are canonical, so two encodings are equal if and only
+/// if the points are, which makes them usable as keys of a `HashMap` or a
+/// [`BTreeMap`], unlike [`RistrettoPoint`] itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CanonicalPoint(CompressedRistretto);
+
+impl CanonicalPoint {
+    pub fn compressed(&self) -> CompressedRistretto {
+        self.0
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        self.0.as_bytes()
+    }
+
+    /// The point itself, which always decompresses as the encoding is that of
+    /// an evaluated point.
+    pub fn point(&self) -> RistrettoPoint {
+        self.0
+            .decompress()
+            .expect("a canonical point is a valid encoding")
+    }
+}
+
+impl PartialOrd for CanonicalPoint {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders by encoding, which has no meaning for the group.
+impl Ord for CanonicalPoint {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl From<RistrettoPoint> for CanonicalPoint {
+    fn from(point: RistrettoPoint) -> Self {
+        Self(point.compress())
+    }
+}
+
+//
+// Conversions from and to concrete values
+//
+
+impl From<Scalar> for SymScalar {
+    fn from(s: Scalar) -> Self {
+        SymScalar::Const(s)
+    }
+}
+
+impl TryFrom<SymScalar> for Scalar {
+    type Error = SigmaProofError;
+
+    fn try_from(s: SymScalar) -> Result<Self, Self::Error> {
+        s.evaluate()
+    }
+}
+
+impl From<RistrettoPoint> for SymPoint {
+    fn from(p: RistrettoPoint) -> Self {
+        SymPoint::Const(p)
+    }
+}
+
+impl TryFrom<SymPoint> for RistrettoPoint {
+    type Error = SigmaProofError;
+
+    fn try_from(p: SymPoint) -> Result<Self, Self::Error> {
+        p.evaluate()
+    }
+}
+
 //
 // SymScalar arithmetic operators
 //
@@ -143,6 +775,34 @@ impl Mul<&SymScalar> for &SymScalar {
     }
 }
 
+impl Div for SymScalar {
+    type Output = SymScalar;
+    fn div(self, rhs: SymScalar) -> SymScalar {
+        SymScalar::Mul(Box::new(self), Box::new(rhs.inverse()))
+    }
+}
+
+impl Div<&SymScalar> for SymScalar {
+    type Output = SymScalar;
+    fn div(self, rhs: &SymScalar) -> SymScalar {
+        SymScalar::Mul(Box::new(self), Box::new(rhs.inverse()))
+    }
+}
+
+impl Div<SymScalar> for &SymScalar {
+    type Output = SymScalar;
+    fn div(self, rhs: SymScalar) -> SymScalar {
+        SymScalar::Mul(Box::new(self.clone()), Box::new(rhs.inverse()))
+    }
+}
+
+impl Div<&SymScalar> for &SymScalar {
+    type Output = SymScalar;
+    fn div(self, rhs: &SymScalar) -> SymScalar {
+        SymScalar::Mul(Box::new(self.clone()), Box::new(rhs.inverse()))
+    }
+}
+
 impl Neg for SymScalar {
     type Output = SymScalar;
     fn neg(self) -> SymScalar {
@@ -157,6 +817,63 @@ impl Neg for &SymScalar {
     }
 }
 
+// SymScalar arithmetic with constants, which are lifted to SymScalar::Const
+macro_rules! impl_sym_scalar_const_op {
+    ($trait:ident, $method:ident, $variant:ident, $t:ty) => {
+        impl $trait<$t> for SymScalar {
+            type Output = SymScalar;
+            fn $method(self, rhs: $t) -> SymScalar {
+                SymScalar::$variant(
+                    Box::new(self),
+                    Box::new(SymScalar::Const(Scalar::from(rhs))),
+                )
+            }
+        }
+
+        impl $trait<$t> for &SymScalar {
+            type Output = SymScalar;
+            fn $method(self, rhs: $t) -> SymScalar {
+                SymScalar::$variant(
+                    Box::new(self.clone()),
+                    Box::new(SymScalar::Const(Scalar::from(rhs))),
+                )
+            }
+        }
+
+        impl $trait<SymScalar> for $t {
+            type Output = SymScalar;
+            fn $method(self, rhs: SymScalar) -> SymScalar {
+                SymScalar::$variant(
+                    Box::new(SymScalar::Const(Scalar::from(self))),
+                    Box::new(rhs),
+                )
+            }
+        }
+
+        impl $trait<&SymScalar> for $t {
+            type Output = SymScalar;
+            fn $method(self, rhs: &SymScalar) -> SymScalar {
+                SymScalar::$variant(
+                    Box::new(SymScalar::Const(Scalar::from(self))),
+                    Box::new(rhs.clone()),
+                )
+            }
+        }
+    };
+}
+
+macro_rules! impl_sym_scalar_const_ops {
+    ($($t:ty),*) => {
+        $(
+            impl_sym_scalar_const_op!(Add, add, Add, $t);
+            impl_sym_scalar_const_op!(Sub, sub, Sub, $t);
+            impl_sym_scalar_const_op!(Mul, mul, Mul, $t);
+        )*
+    };
+}
+
+impl_sym_scalar_const_ops!(Scalar, u8, u16, u32, u64, u128);
+
 // SymPoint arithmetic operators
 impl Add for SymPoint {
     type Output = SymPoint;
@@ -291,6 +1008,39 @@ mod tests {
     use super::*;
     use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 
+    /// Drops `s` one node at a time, as dropping a deep expression recurses.
+    fn dismantle_scalar(s: SymScalar) {
+        let mut stack = vec![s];
+        while let Some(node) = stack.pop() {
+            match node {
+                SymScalar::Const(_) | SymScalar::Var(_) => {}
+                SymScalar::Neg(s) | SymScalar::Inv(s) | SymScalar::Pow(s, _) => stack.push(*s),
+                SymScalar::Add(s1, s2) | SymScalar::Sub(s1, s2) | SymScalar::Mul(s1, s2) => {
+                    stack.extend([*s1, *s2]);
+                }
+            }
+        }
+    }
+
+    /// Drops `p` one node at a time, as dropping a deep expression recurses.
+    fn dismantle_point(p: SymPoint) {
+        let mut stack = vec![p];
+        while let Some(node) = stack.pop() {
+            match node {
+                SymPoint::Identity
+                | SymPoint::WellKnownConst(..)
+                | SymPoint::Const(_)
+                | SymPoint::Var(_) => {}
+                SymPoint::Neg(p) => stack.push(*p),
+                SymPoint::Add(p1, p2) | SymPoint::Sub(p1, p2) => stack.extend([*p1, *p2]),
+                SymPoint::Scale(s, p) => {
+                    dismantle_scalar(*s);
+                    stack.push(*p);
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_symscalar_operators() {
         let a = SymScalar::Const(Scalar::from(5u64));
@@ -313,6 +1063,149 @@ mod tests {
         assert_eq!(neg_a.evaluate().unwrap(), -Scalar::from(5u64));
     }
 
+    #[test]
+    fn test_symscalar_constant_operators() {
+        let x = SymScalar::Var(Some(Scalar::from(5u64)));
+
+        assert_eq!((2u64 * &x).evaluate().unwrap(), Scalar::from(10u64));
+        assert_eq!((&x * 2u64).evaluate().unwrap(), Scalar::from(10u64));
+        assert_eq!((x.clone() * 3u8).evaluate().unwrap(), Scalar::from(15u64));
+        assert_eq!(
+            (&x + Scalar::from(3u64)).evaluate().unwrap(),
+            Scalar::from(8u64)
+        );
+        assert_eq!(
+            (Scalar::from(3u64) + x.clone()).evaluate().unwrap(),
+            Scalar::from(8u64)
+        );
+        assert_eq!((&x - 7u32).evaluate().unwrap(), -Scalar::from(2u64));
+        assert_eq!((7u128 - &x).evaluate().unwrap(), Scalar::from(2u64));
+        assert_eq!((x.clone() - 1u16).evaluate().unwrap(), Scalar::from(4u64));
+
+        // constants are lifted to SymScalar::Const
+        assert!(matches!(&x * 2u64, SymScalar::Mul(_, c) if matches!(*c, SymScalar::Const(_))));
+
+        // and combine with points
+        let point = SymPoint::Const(RISTRETTO_BASEPOINT_POINT);
+        let scaled = (2u64 * &x + 1u64) * &point;
+        assert_eq!(
+            scaled.evaluate().unwrap(),
+            Scalar::from(11u64) * RISTRETTO_BASEPOINT_POINT
+        );
+    }
+
+    #[test]
+    fn test_symscalar_inverse() {
+        let a = SymScalar::Var(Some(Scalar::from(7u64)));
+        assert_eq!((&a * a.inverse()).evaluate().unwrap(), Scalar::ONE);
+
+        let b = SymScalar::Const(Scalar::from(21u64));
+        assert_eq!((&b / &a).evaluate().unwrap(), Scalar::from(3u64));
+        assert_eq!(
+            (b.clone() / a.clone()).evaluate().unwrap(),
+            Scalar::from(3u64)
+        );
+
+        // (1/x) * G
+        let point = a.inverse() * SymPoint::Const(RISTRETTO_BASEPOINT_POINT);
+        assert_eq!(
+            Scalar::from(7u64) * point.evaluate().unwrap(),
+            RISTRETTO_BASEPOINT_POINT
+        );
+    }
+
+    #[test]
+    fn test_symscalar_inverse_of_zero() {
+        let zero = SymScalar::Const(Scalar::ZERO);
+        assert!(matches!(
+            zero.inverse().evaluate(),
+            Err(SigmaProofError::DivisionByZero)
+        ));
+
+        let a = SymScalar::Const(Scalar::ONE);
+        assert!(matches!(
+            (&a / (&a - &a)).evaluate(),
+            Err(SigmaProofError::DivisionByZero)
+        ));
+    }
+
+    #[test]
+    fn test_try_operators() {
+        let (a, b) = (Scalar::from(2u64), Scalar::from(3u64));
+        let sum = SymScalar::Const(a)
+            .try_add(SymScalar::Var(Some(b)))
+            .unwrap();
+        assert_eq!(sum.evaluate().unwrap(), a + b);
+        let product = sum.try_mul(SymScalar::Const(b)).unwrap();
+        assert_eq!(product.evaluate().unwrap(), (a + b) * b);
+
+        // the error surfaces when building the expression, even nested
+        let unset = SymScalar::Const(a) - SymScalar::Var(None);
+        assert!(matches!(
+            SymScalar::Const(a).try_add(unset.clone()),
+            Err(SigmaProofError::UninstantiatedScalar)
+        ));
+        assert!(matches!(
+            unset.clone().try_sub(SymScalar::Const(b)),
+            Err(SigmaProofError::UninstantiatedScalar)
+        ));
+        assert!(matches!(
+            unset
+                .clone()
+                .try_scale(SymPoint::Const(RISTRETTO_BASEPOINT_POINT)),
+            Err(SigmaProofError::UninstantiatedScalar)
+        ));
+        // while the operators defer it to evaluation
+        assert!(matches!(
+            (SymScalar::Const(a) * unset).evaluate(),
+            Err(SigmaProofError::UninstantiatedScalar)
+        ));
+
+        let point = SymPoint::Const(RISTRETTO_BASEPOINT_POINT);
+        let scaled = SymScalar::Const(a).try_scale(point.clone()).unwrap();
+        let sum = scaled.try_add(point.clone()).unwrap();
+        assert_eq!(
+            sum.evaluate().unwrap(),
+            (a + Scalar::ONE) * RISTRETTO_BASEPOINT_POINT
+        );
+        assert!(matches!(
+            point.clone().try_sub(-SymPoint::Var(None)),
+            Err(SigmaProofError::UninstantiatedPoint)
+        ));
+        assert!(matches!(
+            point.try_add(SymScalar::Var(None) * SymPoint::Identity),
+            Err(SigmaProofError::UninstantiatedScalar)
+        ));
+    }
+
+    #[test]
+    fn test_symscalar_pow() {
+        let rng = &mut rand::rngs::OsRng;
+        let x = Scalar::random(rng);
+        let a = SymScalar::Var(Some(x));
+
+        assert_eq!(a.clone().pow(0).evaluate().unwrap(), Scalar::ONE);
+        assert_eq!(
+            SymScalar::Const(Scalar::ZERO).pow(0).evaluate().unwrap(),
+            Scalar::ONE
+        );
+        assert_eq!(a.clone().pow(1).evaluate().unwrap(), x);
+        assert_eq!(a.clone().pow(3).evaluate().unwrap(), x * x * x);
+
+        let mut expected = Scalar::ONE;
+        for exp in 0..40 {
+            assert_eq!(a.clone().pow(exp).evaluate().unwrap(), expected);
+            expected *= x;
+        }
+
+        // x^3 * G
+        let point = a.pow(3) * SymPoint::Const(RISTRETTO_BASEPOINT_POINT);
+        assert_eq!(
+            point.evaluate().unwrap(),
+            (x * x * x) * RISTRETTO_BASEPOINT_POINT
+        );
+    }
+
     #[test]
     fn test_sympoint_operators() {
         let scalar_2 = SymScalar::Const(Scalar::from(2u64));
@@ -349,4 +1242,397 @@ mod tests {
         let expected = Scalar::from(5u64) * RISTRETTO_BASEPOINT_POINT;
         assert_eq!(result.evaluate().unwrap(), expected);
     }
+
+    #[test]
+    fn test_depth_and_node_count() {
+        let x = SymScalar::Var(Some(Scalar::from(2u64)));
+        assert_eq!((x.depth(), x.node_count()), (1, 1));
+
+        // (x + 1) * -x
+        let s = (&x + SymScalar::Const(Scalar::ONE)) * -&x;
+        assert_eq!((s.depth(), s.node_count()), (3, 6));
+
+        let g = SymPoint::WellKnownConst("G", RISTRETTO_BASEPOINT_POINT);
+        assert_eq!((g.depth(), g.node_count()), (1, 1));
+
+        // ((x + 1) * -x) * G - G, where the scalar is the deepest branch
+        let p = s * &g - &g;
+        assert_eq!((p.depth(), p.node_count()), (5, 10));
+        // -(x*G) + (G + G)
+        let p = -(&x * &g) + (&g + &g);
+        assert_eq!((p.depth(), p.node_count()), (4, 8));
+    }
+
+    #[test]
+    fn test_deep_expressions() {
+        // deep enough to overflow the stack of a test thread if evaluated or
+        // counted recursively
+        const DEPTH: usize = 100_000;
+
+        let mut s = SymScalar::Const(Scalar::from(3u64));
+        for _ in 0..DEPTH {
+            s = -s;
+        }
+        assert_eq!(s.evaluate().unwrap(), Scalar::from(3u64));
+        assert_eq!((s.depth(), s.node_count()), (DEPTH + 1, DEPTH + 1));
+
+        let g = SymPoint::WellKnownConst("G", RISTRETTO_BASEPOINT_POINT);
+        let mut sum = g.clone();
+        let mut negated = g.clone();
+        for _ in 0..DEPTH {
+            sum = sum + &g;
+            negated = -negated;
+        }
+        let expected = Scalar::from(DEPTH as u64 + 1) * RISTRETTO_BASEPOINT_POINT;
+        assert_eq!(sum.evaluate().unwrap(), expected);
+        assert_eq!((sum.depth(), sum.node_count()), (DEPTH + 1, 2 * DEPTH + 1));
+        assert_eq!(negated.evaluate().unwrap(), RISTRETTO_BASEPOINT_POINT);
+
+        let scaled = s * negated;
+        assert_eq!(scaled.depth(), DEPTH + 2);
+        assert_eq!(
+            scaled.evaluate().unwrap(),
+            Scalar::from(3u64) * RISTRETTO_BASEPOINT_POINT
+        );
+        dismantle_point(scaled);
+        dismantle_point(sum);
+    }
+
+    #[test]
+    fn test_deep_add_chains() {
+        const LEN: u64 = 100_000;
+
+        let one = SymScalar::Const(Scalar::ONE);
+        let mut sum = one.clone();
+        for _ in 1..LEN {
+            sum = sum + &one;
+        }
+        sum.check_instantiated().unwrap();
+        assert_eq!(sum.evaluate().unwrap(), Scalar::from(LEN));
+        let sum = SymScalar::Var(None) + sum;
+        assert!(matches!(
+            sum.check_instantiated(),
+            Err(SigmaProofError::UninstantiatedScalar)
+        ));
+        assert!(matches!(
+            sum.evaluate(),
+            Err(SigmaProofError::UninstantiatedScalar)
+        ));
+        dismantle_scalar(sum);
+
+        let p = SymPoint::Const(RISTRETTO_BASEPOINT_POINT);
+        let mut sum = p.clone();
+        for _ in 1..LEN {
+            sum = sum + &p;
+        }
+        sum.check_instantiated().unwrap();
+        assert_eq!(
+            sum.evaluate().unwrap(),
+            Scalar::from(LEN) * RISTRETTO_BASEPOINT_POINT
+        );
+        assert_eq!(
+            sum.evaluate_cached(&mut EvalCache::new()).unwrap(),
+            Scalar::from(LEN) * RISTRETTO_BASEPOINT_POINT
+        );
+        let sum = sum - SymPoint::Var(None);
+        assert!(matches!(
+            sum.check_instantiated(),
+            Err(SigmaProofError::UninstantiatedPoint)
+        ));
+        dismantle_point(sum);
+    }
+
+    #[test]
+    fn test_linear_combination() {
+        let rng = &mut rand::rngs::OsRng;
+        let (a, b, c) = (
+            Scalar::random(rng),
+            Scalar::random(rng),
+            Scalar::random(rng),
+        );
+        let g = SymPoint::WellKnownConst("G", RISTRETTO_BASEPOINT_POINT);
+        let h = SymPoint::Const(RistrettoPoint::random(rng));
+        let p = SymPoint::Var(Some(RistrettoPoint::random(rng)));
+
+        let chained = a * &g + b * &h + c * &p;
+        let combination = SymPoint::linear_combination(&[
+            (a.into(), g.clone()),
+            (b.into(), h.clone()),
+            (SymScalar::Var(Some(c)), p.clone()),
+        ]);
+        let expected =
+            a * RISTRETTO_BASEPOINT_POINT + b * h.evaluate().unwrap() + c * p.evaluate().unwrap();
+        assert_eq!(combination.evaluate().unwrap(), expected);
+        assert_eq!(chained.evaluate().unwrap(), expected);
+        assert_eq!(
+            chained.evaluate_cached(&mut EvalCache::new()).unwrap(),
+            expected
+        );
+
+        // (a*G) + (b*H + c*P)
+        let SymPoint::Add(left, right) = &combination else {
+            panic!("expected a sum");
+        };
+        assert!(matches!(left.as_ref(), SymPoint::Scale(..)));
+        assert!(matches!(right.as_ref(), SymPoint::Add(..)));
+
+        assert!(SymPoint::linear_combination(&[]).is_identity().unwrap());
+        let uninstantiated =
+            SymPoint::linear_combination(&[(a.into(), g), (SymScalar::Var(None), h)]);
+        assert!(matches!(
+            uninstantiated.evaluate(),
+            Err(SigmaProofError::UninstantiatedScalar)
+        ));
+    }
+
+    #[test]
+    fn test_simplify_scale_by_one() {
+        let point = SymPoint::Const(RISTRETTO_BASEPOINT_POINT);
+        let scaled = Scalar::ONE * &point;
+        let simplified = scaled.simplify();
+        assert!(matches!(simplified, SymPoint::Const(_)));
+        assert_eq!(simplified.evaluate().unwrap(), scaled.evaluate().unwrap());
+    }
+
+    #[test]
+    fn test_simplify_scale_by_zero() {
+        let point = SymPoint::Const(RISTRETTO_BASEPOINT_POINT);
+        let scaled = Scalar::ZERO * &point;
+        let simplified = scaled.simplify();
+        assert!(matches!(simplified, SymPoint::Identity));
+        assert_eq!(simplified.evaluate().unwrap(), scaled.evaluate().unwrap());
+    }
+
+    /// A random expression of the given depth over random leaves.
+    fn random_point(rng: &mut rand::rngs::OsRng, depth: usize) -> SymPoint {
+        use rand::Rng;
+
+        let scalar = |rng: &mut rand::rngs::OsRng| match rng.gen_range(0..3) {
+            0 => SymScalar::Const(Scalar::random(rng)),
+            1 => SymScalar::Var(Some(Scalar::random(rng))),
+            _ => SymScalar::Const(Scalar::random(rng)) * SymScalar::Var(Some(Scalar::random(rng))),
+        };
+        if depth == 0 {
+            return match rng.gen_range(0..4) {
+                0 => SymPoint::Identity,
+                1 => SymPoint::WellKnownConst("G", RISTRETTO_BASEPOINT_POINT),
+                2 => SymPoint::Const(RistrettoPoint::random(rng)),
+                _ => SymPoint::Var(Some(RistrettoPoint::random(rng))),
+            };
+        }
+        match rng.gen_range(0..5) {
+            0 => random_point(rng, depth - 1) + random_point(rng, depth - 1),
+            1 => random_point(rng, depth - 1) - random_point(rng, depth - 1),
+            2 => -random_point(rng, depth - 1),
+            _ => scalar(rng) * random_point(rng, depth - 1),
+        }
+    }
+
+    /// Whether every scaling in `point` applies to a leaf.
+    fn is_distributed(point: &SymPoint) -> bool {
+        match point {
+            SymPoint::Add(p1, p2) | SymPoint::Sub(p1, p2) => {
+                is_distributed(p1) && is_distributed(p2)
+            }
+            SymPoint::Neg(p) => is_distributed(p),
+            SymPoint::Scale(_, p) => matches!(
+                p.as_ref(),
+                SymPoint::WellKnownConst(..) | SymPoint::Const(_) | SymPoint::Var(_)
+            ),
+            _ => true,
+        }
+    }
+
+    #[test]
+    fn test_distribute() {
+        let p = SymPoint::Const(RistrettoPoint::random(&mut rand::rngs::OsRng));
+        let q = SymPoint::Var(Some(RISTRETTO_BASEPOINT_POINT));
+        let (a, b) = (Scalar::from(2u64), Scalar::from(3u64));
+
+        // a*(b*(p + q)) = (a*b)*p + (a*b)*q
+        let point = a * (b * (&p + &q));
+        let SymPoint::Add(p1, p2) = point.distribute() else {
+            panic!("expected a sum");
+        };
+        for (leaf, expected) in [(p1, &p), (p2, &q)] {
+            let SymPoint::Scale(s, leaf) = *leaf else {
+                panic!("expected a scaling");
+            };
+            assert_eq!(s.evaluate().unwrap(), a * b);
+            assert_eq!(leaf.evaluate().unwrap(), expected.evaluate().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_distribute_random_trees() {
+        let rng = &mut rand::rngs::OsRng;
+        for depth in 0..6 {
+            for _ in 0..20 {
+                let point = random_point(rng, depth);
+                let distributed = point.distribute();
+                assert!(is_distributed(&distributed));
+                assert_eq!(distributed.evaluate().unwrap(), point.evaluate().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonical_point() {
+        use std::collections::HashMap;
+
+        let rng = &mut rand::rngs::OsRng;
+        let (a, b) = (Scalar::random(rng), Scalar::random(rng));
+        let g = SymPoint::WellKnownConst("G", RISTRETTO_BASEPOINT_POINT);
+        let p = SymPoint::Const(RistrettoPoint::random(rng));
+
+        // the same point, built differently
+        let sum = a * &g + b * &p;
+        let same = SymPoint::Var(Some(sum.evaluate().unwrap()));
+        let reordered = SymScalar::Const(b) * &p + (SymScalar::Const(a) * &g);
+        let key = sum.canonical().unwrap();
+        assert_eq!(same.canonical().unwrap(), key);
+        assert_eq!(reordered.canonical().unwrap(), key);
+        assert_eq!(key.point(), sum.evaluate().unwrap());
+
+        let mut bases = HashMap::new();
+        for (i, point) in [&g, &p, &sum, &same, &reordered, &(&g - &g)]
+            .into_iter()
+            .enumerate()
+        {
+            bases.entry(point.canonical().unwrap()).or_insert(i);
+        }
+        assert_eq!(bases.len(), 4);
+        assert_eq!(bases[&key], 2);
+        assert_eq!(bases[&CanonicalPoint::from(RistrettoPoint::identity())], 5);
+
+        // distinct points have distinct keys
+        assert_ne!(g.canonical().unwrap(), p.canonical().unwrap());
+        assert_ne!((&g + &g).canonical().unwrap(), g.canonical().unwrap());
+        assert!(matches!(
+            SymPoint::Var(None).canonical(),
+            Err(SigmaProofError::UninstantiatedPoint)
+        ));
+    }
+
+    #[test]
+    fn test_is_identity() {
+        let point = SymPoint::Const(RISTRETTO_BASEPOINT_POINT);
+        assert!(!point.is_identity().unwrap());
+        assert!((&point - &point).is_identity().unwrap());
+        assert!(SymPoint::Identity.is_identity().unwrap());
+        assert!(matches!(
+            SymPoint::Var(None).is_identity(),
+            Err(SigmaProofError::UninstantiatedPoint)
+        ));
+    }
+
+    #[test]
+    fn test_simplify_scale_identity() {
+        // the identity is only exposed after simplifying the inner expression
+        let inner = Scalar::ZERO * &SymPoint::Const(RISTRETTO_BASEPOINT_POINT);
+        let scaled = SymScalar::Var(Some(Scalar::from(7u64))) * &inner;
+        let simplified = scaled.simplify();
+        assert!(matches!(simplified, SymPoint::Identity));
+        assert_eq!(simplified.evaluate().unwrap(), scaled.evaluate().unwrap());
+
+        // other scalings are kept
+        let point = Scalar::from(2u64) * &SymPoint::Const(RISTRETTO_BASEPOINT_POINT);
+        assert!(matches!(point.simplify(), SymPoint::Scale(..)));
+    }
+
+    #[test]
+    fn test_scalar_conversions() {
+        let s = Scalar::from(42u64);
+        assert!(matches!(SymScalar::from(s), SymScalar::Const(c) if c == s));
+        assert_eq!(
+            Scalar::try_from(SymScalar::from(s) + 1u64).unwrap(),
+            s + Scalar::ONE
+        );
+
+        assert!(matches!(
+            Scalar::try_from(SymScalar::Var(None)),
+            Err(SigmaProofError::UninstantiatedScalar)
+        ));
+    }
+
+    #[test]
+    fn test_scalars_from_bytes() {
+        let hashed = SymScalar::hash_to_scalar(b"correct horse battery staple")
+            .evaluate()
+            .unwrap();
+        let again = SymScalar::hash_to_scalar(b"correct horse battery staple")
+            .evaluate()
+            .unwrap();
+        assert_eq!(hashed, again);
+        assert_ne!(hashed, SymScalar::hash_to_scalar(b"").evaluate().unwrap());
+        assert!(bool::from(
+            Scalar::from_canonical_bytes(hashed.to_bytes()).is_some()
+        ));
+
+        // 2^256 - 1 is reduced
+        let reduced = SymScalar::from_bytes_mod_order([0xff; 32])
+            .evaluate()
+            .unwrap();
+        assert!(bool::from(
+            Scalar::from_canonical_bytes(reduced.to_bytes()).is_some()
+        ));
+        assert_eq!(
+            SymScalar::from_bytes_mod_order(reduced.to_bytes())
+                .evaluate()
+                .unwrap(),
+            reduced
+        );
+    }
+
+    #[test]
+    fn test_point_conversions() {
+        let p = RISTRETTO_BASEPOINT_POINT;
+        assert!(matches!(SymPoint::from(p), SymPoint::Const(c) if c == p));
+        assert_eq!(
+            RistrettoPoint::try_from(SymPoint::from(p) + SymPoint::from(p)).unwrap(),
+            p + p
+        );
+
+        assert!(matches!(
+            RistrettoPoint::try_from(SymPoint::Var(None)),
+            Err(SigmaProofError::UninstantiatedPoint)
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_cached_shared_subtree() {
+        // a deep tree, whose left spine is the chain of partial sums
+        let base = SymPoint::Const(RISTRETTO_BASEPOINT_POINT);
+        let mut tree = base.clone();
+        for i in 1..=100u64 {
+            tree = tree + SymScalar::Const(Scalar::from(i)) * &base;
+        }
+
+        // borrow every partial sum from the tree: together they form a DAG,
+        // in which each sum shares all the nodes of the previous one
+        let mut partial_sums = vec![];
+        let mut node = &tree;
+        while let SymPoint::Add(left, _) = node {
+            partial_sums.push(node);
+            node = left;
+        }
+        assert_eq!(partial_sums.len(), 100);
+
+        // evaluating them one by one walks the tree 100 times, with the cache
+        // every node is computed once
+        let mut cache = EvalCache::new();
+        for sum in &partial_sums {
+            assert_eq!(
+                sum.evaluate_cached(&mut cache).unwrap(),
+                sum.evaluate().unwrap()
+            );
+        }
+        // one entry per addition and per scaling
+        assert_eq!(cache.len(), 200);
+
+        // nodes are found by address, so a clone does not share any node
+        let clone = tree.clone();
+        clone.evaluate_cached(&mut cache).unwrap();
+        assert_eq!(cache.len(), 400);
+    }
 }
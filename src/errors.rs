@@ -1,6 +1,15 @@
+use alloc::string::String;
 use thiserror::Error;
 
+use crate::compiler::DegeneracyKind;
+
+/// The errors of this crate.
+///
+/// It is `Send + Sync + 'static`, so it can be boxed as a `dyn Error` or
+/// wrapped in the error types of other crates. New variants may be added, so
+/// matches outside this crate need a wildcard arm.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum SigmaProofError {
     #[error("SymScalar is not instantiated (contains Var(None))")]
     UninstantiatedScalar,
@@ -8,6 +17,9 @@ pub enum SigmaProofError {
     #[error("SymPoint is not instantiated (contains Var(None))")]
     UninstantiatedPoint,
 
+    #[error("Witness field '{field}' is not instantiated")]
+    UninstantiatedField { field: String },
+
     #[error("Failed to deserialize SymWitness: insufficient scalars provided")]
     InsufficientScalars,
 
@@ -17,6 +29,9 @@ pub enum SigmaProofError {
     #[error("Failed to deserialize SymWitness: too many scalars provided (expected {expected}, got {actual})")]
     TooManyScalars { expected: usize, actual: usize },
 
+    #[error("Failed to deserialize SymInstance: too many points provided (expected {expected}, got {actual})")]
+    TooManyPoints { expected: usize, actual: usize },
+
     #[error("Field '{field}' failed to deserialize")]
     FieldDeserializationFailed { field: String },
 
@@ -26,14 +41,133 @@ pub enum SigmaProofError {
     #[error("Issue with proof parameters: psi output length != f output length")]
     PsiOutputLengthMismatch,
 
-    #[error("There are leftover bytes in the proof")]
-    TranscriptFinalizationFailed,
+    #[error("Proof has {commitments} commitments, but f has {f} outputs and psi has {psi}")]
+    EquationCountMismatch {
+        commitments: usize,
+        f: usize,
+        psi: usize,
+    },
+
+    #[error("Statement is vacuous: f and psi have no outputs")]
+    EmptyStatement,
+
+    #[error("Witness does not satisfy the statement: psi(witness) != f(instance)")]
+    InstanceConsistency,
+
+    #[error("Proof length {len} is not a multiple of 32 or has trailing bytes")]
+    ProofLengthInvalid { len: usize },
+
+    #[error("Proof is too short to contain all commitments")]
+    MissingCommitments,
+
+    #[error("Proof is too short to contain all responses")]
+    MissingResponses,
 
-    #[error("Transcript error")]
+    #[error("Proof has {got:?} commitments and responses, expected {expected:?}")]
+    ProofShapeMismatch {
+        expected: (usize, usize),
+        got: (usize, usize),
+    },
+
+    #[error("Proof contains an invalid point or scalar encoding")]
     TranscriptError,
 
+    #[error("Proof contains a commitment that is the identity")]
+    IdentityCommitment,
+
+    #[error("Commitments of an earlier proof were reused with another challenge")]
+    NonceReuse,
+
     #[error("Invalid scalar values")]
     InvalidScalarValues,
+
+    #[error("Division by zero in a symbolic scalar expression")]
+    DivisionByZero,
+
+    #[error("Expression has depth {depth}, more than the maximum of {max}")]
+    ExpressionTooComplex { depth: usize, max: usize },
+
+    #[error("Instance bytes are not a valid encoding")]
+    InvalidInstanceEncoding,
+
+    #[error("Transcript state is not a valid export")]
+    InvalidTranscriptState,
+
+    #[error("Proof is not correctly framed")]
+    MalformedProof,
+
+    #[error("Proof was made with transcript version {got}, expected {expected}")]
+    TranscriptVersionMismatch { expected: u8, got: u8 },
+
+    #[error("No verifier is registered for the proof's label")]
+    UnknownProtocol,
+
+    #[error("Branch {index} is out of range for {len} branches")]
+    BranchIndexOutOfRange { index: usize, len: usize },
+
+    #[error("Generators are equal or the identity")]
+    GeneratorCollision,
+
+    #[error("Instance is degenerate: {0:?}")]
+    DegenerateInstance(DegeneracyKind),
+
+    #[error("Witness has a runtime length, but the protocol does not override num_responses")]
+    UnknownResponseCount,
 }
 
 pub type SigmaProofResult<T> = Result<T, SigmaProofError>;
+
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<SigmaProofError>();
+};
+
+impl From<DegeneracyKind> for SigmaProofError {
+    fn from(kind: DegeneracyKind) -> Self {
+        SigmaProofError::DegenerateInstance(kind)
+    }
+}
+
+/// Reports the error as [`std::io::ErrorKind::InvalidData`], e.g. when a proof
+/// read from a stream does not verify.
+#[cfg(feature = "std")]
+impl From<SigmaProofError> for std::io::Error {
+    fn from(err: SigmaProofError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{boxed::Box, error::Error, string::ToString};
+
+    fn verify() -> Result<(), Box<dyn Error + Send + Sync>> {
+        Err(SigmaProofError::EquationCheckFailed)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_boxed_error() {
+        let err = verify().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Proof verification failed: equation check failed"
+        );
+        let err = err.downcast::<SigmaProofError>().unwrap();
+        let kind = match *err {
+            SigmaProofError::EquationCheckFailed => "equation",
+            _ => "other",
+        };
+        assert_eq!(kind, "equation");
+
+        let err: SigmaProofError = DegeneracyKind::IdentityPoint.into();
+        assert!(matches!(
+            err,
+            SigmaProofError::DegenerateInstance(DegeneracyKind::IdentityPoint)
+        ));
+        let io: std::io::Error = err.into();
+        assert_eq!(io.kind(), std::io::ErrorKind::InvalidData);
+        assert!(io.get_ref().unwrap().is::<SigmaProofError>());
+    }
+}
@@ -1,6 +1,21 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+compile_error!("sigma-proof-compiler requires the `std` or `alloc` feature");
+
 pub mod absorb;
+pub mod combinators;
 pub mod compiler;
 pub mod equations;
 pub mod errors;
+pub mod multi_round;
+#[cfg(feature = "nonce-guard")]
+mod nonce_guard;
+pub mod proof;
 pub mod sigmas;
+pub mod spec;
+pub mod statement;
 pub mod transcript;
+pub mod wire;
@@ -0,0 +1,280 @@
+//! Protocols with several (commit, challenge) phases before the final round.
+//!
+//! A [`MultiRoundSigma`] protocol runs [`MultiRoundSigma::NUM_PHASES`] phases,
+//! in each of which the prover sends points and receives a challenge, followed
+//! by a final round that is a regular sigma protocol: the prover proves
+//! `psi(w') = f(X)` for a witness `w'` derived from its witness, its
+//! randomness and the challenges, where `f` and `psi` may depend on the
+//! messages and challenges of the earlier phases.
+//!
+//! A 3-move [`SigmaProof`] is the special case with no phases, see
+//! [`AsMultiRound`].
+
+use core::marker::PhantomData;
+
+use alloc::{vec, vec::Vec};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+
+use crate::{
+    absorb::{FixedLenWitness, SymInstance, SymPoint, SymScalar, SymWitness},
+    compiler::{check_proof_shape, check_psi_output, start_transcript, SigmaProof},
+    errors::{SigmaProofError, SigmaProofResult},
+    transcript::ProofTranscript,
+};
+
+pub trait MultiRoundSigma {
+    const LABEL: &'static [u8];
+
+    /// The number of (commit, challenge) phases before the final round.
+    const NUM_PHASES: usize;
+
+    /// The transcript labels of the final round, see
+    /// [`SigmaProof::COMMIT_LABEL`].
+    const COMMIT_LABEL: &'static [u8] = b"r";
+    const CHALLENGE_LABEL: &'static [u8] = b"e";
+    const RESPONSE_LABEL: &'static [u8] = b"z";
+
+    type WITNESS: SymWitness;
+    type INSTANCE: SymInstance;
+    /// The prover's randomness for all phases, sampled before the first one.
+    type RANDOMNESS: FixedLenWitness;
+    /// The witness of the final round, whose length fixes the number of
+    /// responses in a proof.
+    type RESPONSE: FixedLenWitness;
+
+    /// The number of points the prover sends in `phase`.
+    fn num_messages(phase: usize) -> usize;
+
+    /// The points the prover sends in `phase`, given the challenges of the
+    /// previous phases.
+    fn commit(
+        phase: usize,
+        witness: &Self::WITNESS,
+        randomness: &Self::RANDOMNESS,
+        instance: &Self::INSTANCE,
+        challenges: &[Scalar],
+    ) -> Vec<SymPoint>;
+
+    /// The witness of the final round, given the challenges of all phases.
+    fn response_witness(
+        witness: &Self::WITNESS,
+        randomness: &Self::RANDOMNESS,
+        challenges: &[Scalar],
+    ) -> SigmaProofResult<Self::RESPONSE>;
+
+    /// `f` of the final round, where `messages` holds the points sent in all
+    /// phases.
+    fn f(
+        instance: &Self::INSTANCE,
+        messages: &[RistrettoPoint],
+        challenges: &[Scalar],
+    ) -> Vec<SymPoint>;
+
+    /// `psi` of the final round, see [`MultiRoundSigma::f`].
+    fn psi(
+        response: &Self::RESPONSE,
+        instance: &Self::INSTANCE,
+        messages: &[RistrettoPoint],
+        challenges: &[Scalar],
+    ) -> Vec<SymPoint>;
+
+    fn prove(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> SigmaProofResult<Vec<u8>> {
+        // init transcript
+        let mut transcript: ProofTranscript = start_transcript(Self::LABEL, None, b"", instance)?;
+
+        // phases
+        let rng = &mut rand::rngs::OsRng;
+        let randomness = Self::RANDOMNESS::rand(rng);
+        let mut messages = vec![];
+        let mut challenges = vec![];
+        for phase in 0..Self::NUM_PHASES {
+            for point in Self::commit(phase, witness, &randomness, instance, &challenges) {
+                let point = point.evaluate()?;
+                transcript.prover_absorb_point(b"m", &point);
+                messages.push(point);
+            }
+            challenges.push(transcript.challenge(b"c"));
+        }
+
+        // final round 1
+        let response = Self::response_witness(witness, &randomness, &challenges)?;
+        let alphas = response.rand_like(rng);
+        for point in Self::psi(&alphas, instance, &messages, &challenges) {
+            transcript.prover_absorb_point(Self::COMMIT_LABEL, &point.evaluate()?);
+        }
+
+        // final round 2
+        let e = transcript.challenge(Self::CHALLENGE_LABEL);
+
+        // final round 3
+        for z_i in response
+            .values()?
+            .into_iter()
+            .zip(alphas.values()?)
+            .map(|(s, a)| s * e + a)
+        {
+            transcript.prover_absorb_scalar(Self::RESPONSE_LABEL, &z_i);
+        }
+
+        // the randomness and the nonces reveal the witness together with the
+        // proof
+        #[cfg(feature = "zeroize")]
+        {
+            let (mut randomness, mut response, mut alphas) = (randomness, response, alphas);
+            SymWitness::zeroize(&mut randomness);
+            SymWitness::zeroize(&mut response);
+            SymWitness::zeroize(&mut alphas);
+        }
+
+        Ok(transcript.finalize())
+    }
+
+    fn verify(instance: &Self::INSTANCE, proof: &[u8]) -> SigmaProofResult<()> {
+        let num_messages: usize = (0..Self::NUM_PHASES).map(Self::num_messages).sum();
+        if proof.len() < 32 * num_messages {
+            return Err(SigmaProofError::MissingCommitments);
+        }
+
+        // init transcript
+        let mut transcript: ProofTranscript =
+            start_transcript(Self::LABEL, Some(proof), b"", instance)?;
+
+        // phases
+        let mut messages = vec![];
+        let mut challenges = vec![];
+        for phase in 0..Self::NUM_PHASES {
+            messages.extend(
+                transcript
+                    .verifier_receive_points(b"m", Self::num_messages(phase))
+                    .ok_or(SigmaProofError::TranscriptError)?,
+            );
+            challenges.push(transcript.challenge(b"c"));
+        }
+
+        // evaluate f, then check the shape of the rest of the proof
+        let big_x_points = Self::f(instance, &messages, &challenges)
+            .into_iter()
+            .map(|p| p.evaluate())
+            .collect::<Result<Vec<_>, _>>()?;
+        let num_responses = Self::RESPONSE::NUM_SCALARS;
+        check_proof_shape(
+            &proof[32 * num_messages..],
+            big_x_points.len(),
+            num_responses,
+        )?;
+
+        // -> A
+        let big_a = transcript
+            .verifier_receive_points(Self::COMMIT_LABEL, big_x_points.len())
+            .ok_or(SigmaProofError::TranscriptError)?;
+
+        // <- challenge
+        let e = transcript.challenge(Self::CHALLENGE_LABEL);
+
+        // -> sigma
+        let sigmas = transcript
+            .verifier_receive_scalars(Self::RESPONSE_LABEL, num_responses)
+            .ok_or(SigmaProofError::TranscriptError)?;
+
+        // checks
+        let psi_output = Self::psi(
+            &Self::RESPONSE::from_values(&sigmas)?,
+            instance,
+            &messages,
+            &challenges,
+        );
+        check_psi_output(&big_x_points, &big_a, e, &psi_output)
+    }
+}
+
+/// Runs a 3-move [`SigmaProof`] as a [`MultiRoundSigma`] with no phases.
+///
+/// Its proofs are the same as those of [`SigmaProof::prove`]. Only protocols
+/// whose witness is a [`FixedLenWitness`] are supported.
+pub struct AsMultiRound<P>(PhantomData<P>);
+
+impl<P: SigmaProof> MultiRoundSigma for AsMultiRound<P>
+where
+    P::WITNESS: FixedLenWitness,
+{
+    const LABEL: &'static [u8] = P::LABEL;
+    const NUM_PHASES: usize = 0;
+    const COMMIT_LABEL: &'static [u8] = P::COMMIT_LABEL;
+    const CHALLENGE_LABEL: &'static [u8] = P::CHALLENGE_LABEL;
+    const RESPONSE_LABEL: &'static [u8] = P::RESPONSE_LABEL;
+
+    type WITNESS = P::WITNESS;
+    type INSTANCE = P::INSTANCE;
+    type RANDOMNESS = [SymScalar; 0];
+    type RESPONSE = P::WITNESS;
+
+    fn num_messages(_phase: usize) -> usize {
+        0
+    }
+
+    fn commit(
+        _phase: usize,
+        _witness: &Self::WITNESS,
+        _randomness: &Self::RANDOMNESS,
+        _instance: &Self::INSTANCE,
+        _challenges: &[Scalar],
+    ) -> Vec<SymPoint> {
+        vec![]
+    }
+
+    fn response_witness(
+        witness: &Self::WITNESS,
+        _randomness: &Self::RANDOMNESS,
+        _challenges: &[Scalar],
+    ) -> SigmaProofResult<Self::RESPONSE> {
+        P::WITNESS::from_values(&witness.values()?)
+    }
+
+    fn f(
+        instance: &Self::INSTANCE,
+        _messages: &[RistrettoPoint],
+        _challenges: &[Scalar],
+    ) -> Vec<SymPoint> {
+        P::f(instance)
+    }
+
+    fn psi(
+        response: &Self::RESPONSE,
+        instance: &Self::INSTANCE,
+        _messages: &[RistrettoPoint],
+        _challenges: &[Scalar],
+    ) -> Vec<SymPoint> {
+        P::psi(response, instance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::Scalar;
+
+    use super::*;
+    use crate::sigmas::{
+        chaum::{Chaum, ChaumInstance, ChaumWitness},
+        G, H,
+    };
+
+    #[test]
+    fn test_sigma_proof_as_multi_round() {
+        let rng = &mut rand::rngs::OsRng;
+        let x = Scalar::random(rng);
+        let witness = ChaumWitness::from_values(&[x]).unwrap();
+        let instance = ChaumInstance::new(x * G, x * H.clone());
+
+        // proofs are interchangeable
+        let proof = AsMultiRound::<Chaum>::prove(&witness, &instance).unwrap();
+        AsMultiRound::<Chaum>::verify(&instance, &proof).unwrap();
+        Chaum::verify(&instance, &proof).unwrap();
+
+        let proof = Chaum::prove(&witness, &instance).unwrap();
+        AsMultiRound::<Chaum>::verify(&instance, &proof).unwrap();
+
+        let wrong = ChaumWitness::from_values(&[x + Scalar::ONE]).unwrap();
+        let proof = AsMultiRound::<Chaum>::prove(&wrong, &instance).unwrap();
+        assert!(AsMultiRound::<Chaum>::verify(&instance, &proof).is_err());
+    }
+}
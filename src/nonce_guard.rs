@@ -0,0 +1,113 @@
+//! Detection of nonces reused across proofs, enabled by the `nonce-guard`
+//! feature.
+//!
+//! Two proofs of the same statement with the same commitments `A = psi(alpha)`
+//! but different challenges reveal the witness, as `z - z' = (e - e')*w`. The
+//! prover records a digest of the statement and the commitments of the last
+//! [`CAPACITY`] proofs made in the process along with their challenge, and
+//! fails with [`SigmaProofError::NonceReuse`] instead of answering the same
+//! commitments with another challenge.
+//!
+//! Identity commitments carry no nonce, e.g. for a witness without scalars, so
+//! they are left out of the digest, and proofs without any other commitment
+//! are not recorded.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Mutex,
+};
+
+use curve25519_dalek::{traits::IsIdentity, RistrettoPoint, Scalar};
+
+use crate::{
+    absorb::{SymInstance, SymPoint, SymScalar},
+    compiler::SigmaProof,
+    errors::{SigmaProofError, SigmaProofResult},
+};
+
+/// The number of proofs remembered, the oldest being forgotten first.
+const CAPACITY: usize = 4096;
+
+struct Seen {
+    challenges: BTreeMap<[u8; 32], Scalar>,
+    order: VecDeque<[u8; 32]>,
+}
+
+static SEEN: Mutex<Seen> = Mutex::new(Seen {
+    challenges: BTreeMap::new(),
+    order: VecDeque::new(),
+});
+
+/// Records that the commitments `big_a` of a proof of `P` for `instance` are
+/// answered with the challenge `e`.
+pub(crate) fn record<P: SigmaProof + ?Sized>(
+    instance: &P::INSTANCE,
+    big_a: &[RistrettoPoint],
+    e: &Scalar,
+) -> SigmaProofResult<()> {
+    if big_a.iter().all(IsIdentity::is_identity) {
+        return Ok(());
+    }
+
+    let mut digest = merlin::Transcript::new(b"nonce-guard");
+    digest.append_message(b"label", P::LABEL);
+    for scalar in instance.scalars() {
+        digest.append_message(b"s", SymScalar::evaluate(&scalar)?.as_bytes());
+    }
+    for point in instance.points() {
+        digest.append_message(b"P", SymPoint::evaluate(&point)?.compress().as_bytes());
+    }
+    for point in big_a.iter().filter(|point| !point.is_identity()) {
+        digest.append_message(b"A", point.compress().as_bytes());
+    }
+    let mut key = [0u8; 32];
+    digest.challenge_bytes(b"digest", &mut key);
+
+    let mut seen = SEEN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match seen.challenges.get(&key) {
+        Some(previous) if previous != e => Err(SigmaProofError::NonceReuse),
+        Some(_) => Ok(()),
+        None => {
+            if seen.order.len() == CAPACITY {
+                let oldest = seen.order.pop_front().expect("the capacity is nonzero");
+                seen.challenges.remove(&oldest);
+            }
+            seen.challenges.insert(key, *e);
+            seen.order.push_back(key);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    use super::*;
+    use crate::sigmas::schnorr::{SchnorrIdentityProtocol, SchnorrInstance};
+
+    #[test]
+    fn test_nonce_guard() {
+        let rng = &mut rand::rngs::OsRng;
+        let instance = SchnorrInstance::new(RistrettoPoint::random(rng));
+        let big_a = [RistrettoPoint::random(rng)];
+        let (e, other_e) = (Scalar::random(rng), Scalar::random(rng));
+
+        record::<SchnorrIdentityProtocol>(&instance, &big_a, &e).unwrap();
+        record::<SchnorrIdentityProtocol>(&instance, &big_a, &e).unwrap();
+        assert!(matches!(
+            record::<SchnorrIdentityProtocol>(&instance, &big_a, &other_e),
+            Err(SigmaProofError::NonceReuse)
+        ));
+
+        // the same commitments for another statement do not reuse a nonce
+        let other = SchnorrInstance::new(RISTRETTO_BASEPOINT_POINT);
+        record::<SchnorrIdentityProtocol>(&other, &big_a, &other_e).unwrap();
+
+        // nor do identity commitments, which carry no nonce
+        for identity in [&[][..], &[RistrettoPoint::default()]] {
+            record::<SchnorrIdentityProtocol>(&instance, identity, &e).unwrap();
+            record::<SchnorrIdentityProtocol>(&instance, identity, &other_e).unwrap();
+        }
+    }
+}
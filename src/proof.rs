@@ -0,0 +1,352 @@
+//! Self-describing proofs and label-based dispatch to verifiers.
+//!
+//! A self-describing proof is framed as:
+//!
+//! ```text
+//! label_len (u32 LE) || label || proof bytes
+//! ```
+//!
+//! so a verifier can look up the protocol from the label alone via a
+//! [`Dispatcher`], without out-of-band protocol information.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::marker::PhantomData;
+
+use curve25519_dalek::RistrettoPoint;
+
+use crate::{
+    absorb::SymInstance,
+    compiler::SigmaProof,
+    errors::{SigmaProofError, SigmaProofResult},
+    wire::{point_from_wire, point_to_wire, scalar_from_wire, scalar_to_wire, Endianness},
+};
+
+/// A proof tagged with the label of the protocol that produced it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof {
+    label: Vec<u8>,
+    bytes: Vec<u8>,
+}
+
+impl Proof {
+    /// Tags the bytes produced by `P::prove` with `P::LABEL`.
+    pub fn new<P: SigmaProof>(bytes: Vec<u8>) -> Self {
+        Self {
+            label: P::LABEL.to_vec(),
+            bytes,
+        }
+    }
+
+    pub fn label(&self) -> &[u8] {
+        &self.label
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn to_self_describing(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(4 + self.label.len() + self.bytes.len());
+        out.extend_from_slice(&(self.label.len() as u32).to_le_bytes());
+        out.extend_from_slice(&self.label);
+        out.extend_from_slice(&self.bytes);
+        out
+    }
+
+    pub fn from_self_describing(bytes: &[u8]) -> Option<Self> {
+        let (label, body) = split_label(bytes)?;
+        Some(Self {
+            label: label.to_vec(),
+            bytes: body.to_vec(),
+        })
+    }
+}
+
+/// Reads the protocol label of a self-describing proof without parsing the rest.
+pub fn peek_label(bytes: &[u8]) -> Option<&[u8]> {
+    split_label(bytes).map(|(label, _)| label)
+}
+
+fn split_label(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let len_bytes: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+    let len = u32::from_le_bytes(len_bytes) as usize;
+    let label = bytes.get(4..4usize.checked_add(len)?)?;
+    Some((label, &bytes[4 + len..]))
+}
+
+/// Serializes an instance as its scalars followed by its compressed points,
+/// in the encodings of [`crate::wire`].
+pub fn encode_instance<I: SymInstance>(instance: &I) -> SigmaProofResult<Vec<u8>> {
+    let (scalars, points) = (instance.scalars(), instance.points());
+    let mut out = Vec::with_capacity(32 * (scalars.len() + points.len()));
+    for scalar in scalars {
+        out.extend_from_slice(&scalar_to_wire(&scalar.evaluate()?, Endianness::Little));
+    }
+    for point in points {
+        out.extend_from_slice(&point_to_wire(&point.evaluate()?));
+    }
+    Ok(out)
+}
+
+/// Parses an instance serialized by [`encode_instance`].
+///
+/// Instances may have a runtime number of scalars or of points, which is
+/// then inferred from the length of `bytes`, but not of both.
+pub fn decode_instance<I: SymInstance>(bytes: &[u8]) -> SigmaProofResult<I> {
+    if bytes.len() % 32 != 0 {
+        return Err(SigmaProofError::InvalidInstanceEncoding);
+    }
+    let len = bytes.len() / 32;
+    let num_scalars = match (I::num_scalars(), I::num_points()) {
+        (Some(num_scalars), Some(num_points)) if num_scalars + num_points == len => num_scalars,
+        (Some(num_scalars), None) if num_scalars <= len => num_scalars,
+        (None, Some(num_points)) if num_points <= len => len - num_points,
+        _ => return Err(SigmaProofError::InvalidInstanceEncoding),
+    };
+
+    let mut chunks = bytes.chunks_exact(32).map(|chunk| {
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(chunk);
+        buf
+    });
+    let scalars = chunks
+        .by_ref()
+        .take(num_scalars)
+        .map(|buf| scalar_from_wire(buf, Endianness::Little))
+        .collect::<Option<Vec<_>>>()
+        .ok_or(SigmaProofError::InvalidInstanceEncoding)?;
+    let points = chunks
+        .map(point_from_wire)
+        .collect::<Option<Vec<RistrettoPoint>>>()
+        .ok_or(SigmaProofError::InvalidInstanceEncoding)?;
+
+    I::from_values(&scalars, &points)
+}
+
+/// A verifier with its protocol type erased, so verifiers for different
+/// protocols can be stored together.
+pub trait ErasedVerifier: Send + Sync {
+    fn label(&self) -> &'static [u8];
+
+    /// Verifies `proof` against an instance serialized with [`encode_instance`].
+    fn verify(&self, instance: &[u8], proof: &[u8]) -> SigmaProofResult<()>;
+}
+
+/// The [`ErasedVerifier`] of the protocol `P`.
+pub struct Verifier<P>(PhantomData<fn() -> P>);
+
+impl<P> Verifier<P> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<P> Default for Verifier<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: SigmaProof> ErasedVerifier for Verifier<P> {
+    fn label(&self) -> &'static [u8] {
+        P::LABEL
+    }
+
+    fn verify(&self, instance: &[u8], proof: &[u8]) -> SigmaProofResult<()> {
+        let instance = decode_instance::<P::INSTANCE>(instance)?;
+        P::verify(&instance, proof)
+    }
+}
+
+/// Routes self-describing proofs to the verifier registered for their label.
+#[derive(Default)]
+pub struct Dispatcher {
+    verifiers: Vec<Box<dyn ErasedVerifier>>,
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<P: SigmaProof + 'static>(&mut self) -> &mut Self {
+        self.verifiers.push(Box::new(Verifier::<P>::new()));
+        self
+    }
+
+    pub fn verifier(&self, label: &[u8]) -> Option<&dyn ErasedVerifier> {
+        self.verifiers
+            .iter()
+            .find(|verifier| verifier.label() == label)
+            .map(|verifier| verifier.as_ref())
+    }
+
+    /// Verifies a self-describing proof against a serialized instance.
+    pub fn verify(&self, instance: &[u8], proof: &[u8]) -> SigmaProofResult<()> {
+        let (label, body) = split_label(proof).ok_or(SigmaProofError::MalformedProof)?;
+        let verifier = self
+            .verifier(label)
+            .ok_or(SigmaProofError::UnknownProtocol)?;
+        verifier.verify(instance, body)
+    }
+}
+
+/// An independent verification to run with [`verify_all_parallel`].
+pub struct VerifyJob<'a> {
+    pub verifier: &'a dyn ErasedVerifier,
+    pub instance: Vec<u8>,
+    pub proof: Vec<u8>,
+}
+
+/// Verifies independent proofs, possibly of different protocols, on the rayon
+/// thread pool. The results are in the same order as the jobs.
+///
+/// This only spreads the work across threads, every proof is still verified
+/// on its own.
+#[cfg(feature = "rayon")]
+pub fn verify_all_parallel(jobs: Vec<VerifyJob>) -> Vec<SigmaProofResult<()>> {
+    use rayon::prelude::*;
+
+    jobs.into_par_iter()
+        .map(|job| job.verifier.verify(&job.instance, &job.proof))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::Scalar;
+
+    use super::*;
+    use crate::{
+        absorb::SymWitness,
+        sigmas::{
+            chaum::{Chaum, ChaumInstance, ChaumWitness},
+            schnorr::{SchnorrIdentityProtocol, SchnorrInstance, SchnorrWitness},
+            G, H,
+        },
+    };
+
+    fn dispatcher() -> Dispatcher {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher
+            .register::<SchnorrIdentityProtocol>()
+            .register::<Chaum>();
+        dispatcher
+    }
+
+    #[test]
+    fn test_self_describing_roundtrip() {
+        let proof = Proof::new::<Chaum>(vec![1, 2, 3]);
+        let bytes = proof.to_self_describing();
+        assert_eq!(peek_label(&bytes), Some(Chaum::LABEL));
+        assert_eq!(Proof::from_self_describing(&bytes), Some(proof));
+        assert_eq!(peek_label(&[5, 0, 0, 0, b'a']), None);
+    }
+
+    #[test]
+    fn test_dispatcher_routes_proofs() {
+        let rng = &mut rand::rngs::OsRng;
+        let dispatcher = dispatcher();
+
+        let sk = Scalar::random(rng);
+        let schnorr_witness = SchnorrWitness::from_values(&[sk]).unwrap();
+        let schnorr_instance =
+            SchnorrInstance::from_values(&[], &[(sk * G).evaluate().unwrap()]).unwrap();
+        let schnorr_proof = Proof::new::<SchnorrIdentityProtocol>(
+            SchnorrIdentityProtocol::prove(&schnorr_witness, &schnorr_instance).unwrap(),
+        );
+
+        let x = Scalar::random(rng);
+        let chaum_witness = ChaumWitness::from_values(&[x]).unwrap();
+        let chaum_instance = ChaumInstance::from_values(
+            &[],
+            &[
+                (x * G).evaluate().unwrap(),
+                (x * H.clone()).evaluate().unwrap(),
+            ],
+        )
+        .unwrap();
+        let chaum_proof =
+            Proof::new::<Chaum>(Chaum::prove(&chaum_witness, &chaum_instance).unwrap());
+
+        let schnorr_instance = encode_instance(&schnorr_instance).unwrap();
+        let chaum_instance = encode_instance(&chaum_instance).unwrap();
+
+        dispatcher
+            .verify(&schnorr_instance, &schnorr_proof.to_self_describing())
+            .unwrap();
+        dispatcher
+            .verify(&chaum_instance, &chaum_proof.to_self_describing())
+            .unwrap();
+
+        // a proof routed with the wrong instance fails
+        assert!(dispatcher
+            .verify(&schnorr_instance, &chaum_proof.to_self_describing())
+            .is_err());
+    }
+
+    #[test]
+    fn test_dispatcher_unknown_label() {
+        let dispatcher = dispatcher();
+        let proof = Proof {
+            label: b"unknown".to_vec(),
+            bytes: vec![],
+        };
+        assert!(matches!(
+            dispatcher.verify(&[], &proof.to_self_describing()),
+            Err(SigmaProofError::UnknownProtocol)
+        ));
+        assert!(matches!(
+            dispatcher.verify(&[], &[1, 2]),
+            Err(SigmaProofError::MalformedProof)
+        ));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_verify_all_parallel() {
+        let rng = &mut rand::rngs::OsRng;
+        let dispatcher = dispatcher();
+        let schnorr = dispatcher.verifier(SchnorrIdentityProtocol::LABEL).unwrap();
+        let chaum = dispatcher.verifier(Chaum::LABEL).unwrap();
+
+        let jobs: Vec<_> = (0..100)
+            .map(|i| {
+                let x = Scalar::random(rng);
+                // every third job is given a proof for another secret
+                let proven = if i % 3 == 0 { Scalar::random(rng) } else { x };
+                if i % 2 == 0 {
+                    let witness = SchnorrWitness::from_values(&[proven]).unwrap();
+                    let instance =
+                        SchnorrInstance::from_values(&[], &[(x * G).evaluate().unwrap()]).unwrap();
+                    VerifyJob {
+                        verifier: schnorr,
+                        instance: encode_instance(&instance).unwrap(),
+                        proof: SchnorrIdentityProtocol::prove(&witness, &instance).unwrap(),
+                    }
+                } else {
+                    let witness = ChaumWitness::from_values(&[proven]).unwrap();
+                    let instance = ChaumInstance::from_values(
+                        &[],
+                        &[
+                            (x * G).evaluate().unwrap(),
+                            (x * H.clone()).evaluate().unwrap(),
+                        ],
+                    )
+                    .unwrap();
+                    VerifyJob {
+                        verifier: chaum,
+                        instance: encode_instance(&instance).unwrap(),
+                        proof: Chaum::prove(&witness, &instance).unwrap(),
+                    }
+                }
+            })
+            .collect();
+
+        let results = verify_all_parallel(jobs);
+        assert_eq!(results.len(), 100);
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(result.is_ok(), i % 3 != 0, "job {i}");
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    absorb::{SymInstance, SymPoint, SymScalar},
+    combinators::{OrInstance, OrWitness, SigmaOr},
+    compiler::SigmaProof,
+    errors::SigmaProofResult,
+    sigmas::{
+        is_zero::{IsZero, IsZeroInstance, IsZeroWitness},
+        G,
+    },
+};
+use alloc::vec::Vec;
+use curve25519_dalek::Scalar;
+
+/// Proves that a Pedersen commitment `C = b*G + r*H` opens to a bit
+/// `b ∈ {0, 1}`, as the disjunction of `C = r*H` and `C - G = r*H`.
+pub struct BitProof;
+
+type BitOr = SigmaOr<IsZero, IsZero>;
+
+/// The witness `{ b, r }`, laid out as a witness of [`SigmaOr`] whose choice
+/// is `b`.
+pub type BitWitness = OrWitness<IsZeroWitness, IsZeroWitness>;
+
+impl BitWitness {
+    /// The opening `(b, r)` of a commitment to the bit `b`.
+    pub fn new(b: bool, r: Scalar) -> SigmaProofResult<Self> {
+        let blinding = IsZeroWitness::new(SymScalar::Const(r));
+        if b {
+            Self::right(blinding)
+        } else {
+            Self::left(blinding)
+        }
+    }
+}
+
+#[derive(SymInstance, Clone)]
+pub struct BitInstance {
+    commitment: SymPoint,
+}
+
+impl BitInstance {
+    fn branches(&self) -> OrInstance<IsZeroInstance, IsZeroInstance> {
+        let Self { commitment } = self.clone();
+        OrInstance::new(
+            IsZeroInstance::new(commitment.clone()),
+            IsZeroInstance::new(commitment - G),
+        )
+    }
+}
+
+impl SigmaProof for BitProof {
+    const LABEL: &'static [u8] = b"bit-protocol";
+
+    type WITNESS = BitWitness;
+    type INSTANCE = BitInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        BitOr::f(&instance.branches())
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        BitOr::psi(witness, &instance.branches())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sigmas::H;
+
+    fn commit(b: u64, r: Scalar) -> BitInstance {
+        BitInstance {
+            commitment: Scalar::from(b) * G + r * H.clone(),
+        }
+    }
+
+    #[test]
+    fn test_bit_proof_zero() {
+        let rng = &mut rand::rngs::OsRng;
+        let r = Scalar::random(rng);
+        let instance = commit(0, r);
+
+        let proof = BitProof::prove(&BitWitness::new(false, r).unwrap(), &instance).unwrap();
+        BitProof::verify(&instance, &proof).unwrap();
+
+        let proof = BitProof::prove(&BitWitness::new(true, r).unwrap(), &instance).unwrap();
+        assert!(BitProof::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_bit_proof_one() {
+        let rng = &mut rand::rngs::OsRng;
+        let r = Scalar::random(rng);
+        let instance = commit(1, r);
+
+        let proof = BitProof::prove(&BitWitness::new(true, r).unwrap(), &instance).unwrap();
+        BitProof::verify(&instance, &proof).unwrap();
+
+        let proof = BitProof::prove(&BitWitness::new(false, r).unwrap(), &instance).unwrap();
+        assert!(BitProof::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_bit_proof_rejects_two() {
+        let rng = &mut rand::rngs::OsRng;
+        let r = Scalar::random(rng);
+        let instance = commit(2, r);
+
+        for b in [false, true] {
+            let proof = BitProof::prove(&BitWitness::new(b, r).unwrap(), &instance).unwrap();
+            assert!(BitProof::verify(&instance, &proof).is_err());
+        }
+    }
+
+    #[test]
+    fn test_bit_proof_spec_generation() {
+        let spec = BitProof::spec();
+        println!("{spec}");
+    }
+
+    conformance_tests!(BitProof);
+}
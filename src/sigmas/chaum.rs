@@ -3,6 +3,7 @@ use crate::{
     compiler::SigmaProof,
     sigmas::{G, H},
 };
+use alloc::{vec, vec::Vec};
 
 pub struct Chaum;
 
@@ -19,6 +20,7 @@ pub struct ChaumInstance {
 
 impl SigmaProof for Chaum {
     const LABEL: &'static [u8] = b"chaum-protocol";
+    const NUM_EQUATIONS: Option<usize> = Some(2);
 
     type WITNESS = ChaumWitness;
     type INSTANCE = ChaumInstance;
@@ -36,9 +38,15 @@ impl SigmaProof for Chaum {
 
 #[cfg(test)]
 mod tests {
+    use core::sync::atomic::{AtomicUsize, Ordering};
     use curve25519_dalek::Scalar;
 
     use super::*;
+    use crate::{
+        compiler::start_transcript,
+        errors::SigmaProofError,
+        transcript::{Operation, ProofTranscript, RecordingTranscript, TRANSCRIPT_VERSION},
+    };
 
     #[test]
     fn test_chaum_identity_protocol() {
@@ -60,9 +68,594 @@ mod tests {
         Chaum::verify(&instance, &proof).unwrap();
     }
 
+    #[test]
+    fn test_chaum_context() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let instance = ChaumInstance {
+            point1: sk * G,
+            point2: sk * H.clone(),
+        };
+
+        let proof = Chaum::prove_with_context(&witness, &instance, b"session-1").unwrap();
+        Chaum::verify_with_context(&instance, &proof, b"session-1").unwrap();
+        assert!(Chaum::verify_with_context(&instance, &proof, b"session-2").is_err());
+        assert!(Chaum::verify(&instance, &proof).is_err());
+
+        // the methods without context use the empty context
+        let proof = Chaum::prove(&witness, &instance).unwrap();
+        Chaum::verify_with_context(&instance, &proof, b"").unwrap();
+        assert!(Chaum::verify_with_context(&instance, &proof, b"session-1").is_err());
+    }
+
+    #[test]
+    fn test_chaum_prover_verifier_transcripts_agree() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let instance = ChaumInstance::new(sk * G, sk * H.clone());
+        let other = ChaumInstance::new(sk * G, Scalar::random(rng) * H.clone());
+
+        for context in [&b""[..], b"session-1"] {
+            let start = |proof, instance| -> ProofTranscript {
+                start_transcript(Chaum::LABEL, proof, context, instance).unwrap()
+            };
+            let mut prover = start(None, &instance);
+            let mut verifier = start(Some(&[]), &instance);
+            let mut mismatched = start(Some(&[]), &other);
+
+            let e = prover.challenge(Chaum::CHALLENGE_LABEL);
+            assert_eq!(verifier.challenge(Chaum::CHALLENGE_LABEL), e);
+            assert_ne!(mismatched.challenge(Chaum::CHALLENGE_LABEL), e);
+        }
+    }
+
+    #[test]
+    fn test_chaum_recorded_absorbs_match() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let instance = ChaumInstance::new(sk * G, sk * H.clone());
+
+        RecordingTranscript::take();
+        let proof =
+            Chaum::prove_with_backend::<RecordingTranscript>(&witness, &instance, b"").unwrap();
+        let prover = RecordingTranscript::take();
+        Chaum::verify_with_backend::<RecordingTranscript>(&instance, &proof, b"").unwrap();
+        let verifier = RecordingTranscript::take();
+
+        // both sides absorb the same messages up to the challenge
+        let challenge = Operation::Squeeze(Chaum::CHALLENGE_LABEL, 64);
+        let until_challenge = |operations: &[Operation]| {
+            let end = operations.iter().position(|op| *op == challenge).unwrap();
+            operations[..=end].to_vec()
+        };
+        assert_eq!(until_challenge(&prover), until_challenge(&verifier));
+
+        // and the commitments are the first 64 bytes of the proof
+        let commitments: Vec<_> = prover
+            .iter()
+            .filter_map(|op| match op {
+                Operation::Absorb(label, bytes) if *label == Chaum::COMMIT_LABEL => {
+                    Some(bytes.as_slice())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(commitments, [&proof[..32], &proof[32..64]]);
+        assert_eq!(prover, verifier);
+    }
+
+    #[test]
+    fn test_chaum_proof_parts() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let instance = ChaumInstance::new(sk * G, sk * H.clone());
+
+        let (commitments, e, responses) = Chaum::prove_parts(&witness, &instance).unwrap();
+        assert_eq!((commitments.len(), responses.len()), (2, 1));
+        Chaum::verify_parts(&instance, &commitments, e, &responses).unwrap();
+
+        // the parts encode the proof verify accepts, with the same challenge
+        let proof: Vec<u8> = commitments
+            .iter()
+            .map(|a| a.compress().to_bytes())
+            .chain(responses.iter().map(|z| z.to_bytes()))
+            .flatten()
+            .collect();
+        assert_eq!(Chaum::verify_with_challenge(&instance, &proof).unwrap(), e);
+
+        // and both reject the same tampered response
+        let tampered = [responses[0] + Scalar::ONE];
+        assert!(matches!(
+            Chaum::verify_parts(&instance, &commitments, e, &tampered),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+        let mut proof = proof;
+        proof[64..].copy_from_slice(tampered[0].as_bytes());
+        assert!(matches!(
+            Chaum::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_chaum_compact_proof() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let instance = ChaumInstance {
+            point1: sk * G,
+            point2: sk * H.clone(),
+        };
+
+        let proof = Chaum::prove(&witness, &instance).unwrap();
+        let compact = Chaum::prove_compact(&witness, &instance).unwrap();
+        assert_eq!(proof.len(), 32 * 3);
+        assert_eq!(compact.len(), 32 * 2);
+
+        Chaum::verify_compact(&instance, &compact).unwrap();
+
+        // tampering with the challenge or the response is rejected
+        for i in [0, 32] {
+            let mut tampered = compact.clone();
+            tampered[i] ^= 1;
+            assert!(Chaum::verify_compact(&instance, &tampered).is_err());
+        }
+        assert!(Chaum::verify_compact(&instance, &compact[..32]).is_err());
+
+        // a compact proof does not verify against another instance
+        let other = ChaumInstance {
+            point1: sk * G,
+            point2: Scalar::random(rng) * H.clone(),
+        };
+        assert!(Chaum::verify_compact(&other, &compact).is_err());
+    }
+
+    #[test]
+    fn test_chaum_prepared_instance() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let instance = ChaumInstance {
+            point1: sk * G,
+            point2: sk * H.clone(),
+        };
+        let prepared = instance.prepare::<Chaum>().unwrap();
+
+        let proofs: Vec<_> = (0..4)
+            .map(|_| Chaum::prove(&witness, &instance).unwrap())
+            .collect();
+        for proof in &proofs {
+            Chaum::verify(&instance, proof).unwrap();
+            Chaum::verify_prepared(&prepared, proof).unwrap();
+        }
+
+        // both reject the same tampered and truncated proofs
+        let mut tampered = proofs[0].clone();
+        tampered[32 * 2] ^= 1;
+        for proof in [&tampered[..], &proofs[0][..32 * 2]] {
+            assert!(Chaum::verify(&instance, proof).is_err());
+            assert!(Chaum::verify_prepared(&prepared, proof).is_err());
+        }
+
+        // and proofs of another statement
+        let other = ChaumInstance {
+            point1: sk * G,
+            point2: Scalar::random(rng) * H.clone(),
+        };
+        let other_prepared = other.prepare::<Chaum>().unwrap();
+        assert!(Chaum::verify(&other, &proofs[0]).is_err());
+        assert!(matches!(
+            Chaum::verify_prepared(&other_prepared, &proofs[0]),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_chaum_aggregated_proof() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let instance = ChaumInstance::new(sk * G, sk * H.clone());
+        let witness = || ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+
+        for k in [2, 3] {
+            let witnesses: Vec<_> = (0..k).map(|_| witness()).collect();
+            let proof = Chaum::prove_aggregated(&witnesses, &instance).unwrap();
+            assert_eq!(proof.len(), 32 * k * 3);
+            Chaum::verify_aggregated(&instance, k, &proof).unwrap();
+
+            // the number of witnesses is bound to the proof
+            assert!(Chaum::verify_aggregated(&instance, k - 1, &proof).is_err());
+            assert!(Chaum::verify_aggregated(&instance, k + 1, &proof).is_err());
+
+            // a single tampered witness invalidates the proof
+            let mut witnesses = witnesses;
+            witnesses[k - 1] = ChaumWitness {
+                x: SymScalar::Const(Scalar::random(rng)),
+            };
+            let proof = Chaum::prove_aggregated(&witnesses, &instance).unwrap();
+            assert!(matches!(
+                Chaum::verify_aggregated(&instance, k, &proof),
+                Err(SigmaProofError::EquationCheckFailed)
+            ));
+        }
+
+        // a single proof is not an aggregate of one witness
+        let proof = Chaum::prove(&witness(), &instance).unwrap();
+        assert!(Chaum::verify_aggregated(&instance, 1, &proof).is_err());
+        assert!(matches!(
+            Chaum::prove_aggregated(&[], &instance),
+            Err(SigmaProofError::EmptyStatement)
+        ));
+
+        // a number of witnesses whose proof length overflows
+        for k in [usize::MAX / 2, usize::MAX] {
+            assert!(matches!(
+                Chaum::verify_aggregated(&instance, k, &proof),
+                Err(SigmaProofError::MissingCommitments)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_chaum_framed_proof() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let instance = ChaumInstance::new(sk * G, sk * H.clone());
+
+        let proof = Chaum::prove_framed(&witness, &instance).unwrap();
+        assert_eq!(proof[..9], [TRANSCRIPT_VERSION, 2, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(proof.len(), 9 + 32 * 3);
+        Chaum::verify_framed(&instance, &proof).unwrap();
+
+        // a header announcing another shape is rejected before the body is read
+        let mut misframed = proof.clone();
+        misframed[1] = 3;
+        assert!(matches!(
+            Chaum::verify_framed(&instance, &misframed),
+            Err(SigmaProofError::ProofShapeMismatch {
+                expected: (2, 1),
+                got: (3, 1),
+            })
+        ));
+
+        assert!(matches!(
+            Chaum::verify_framed(&instance, &proof[..8]),
+            Err(SigmaProofError::MalformedProof)
+        ));
+
+        // bumping the version invalidates proofs made before, which are
+        // reported as such rather than as failing their equations
+        let mut old = proof.clone();
+        old[0] = TRANSCRIPT_VERSION - 1;
+        assert!(matches!(
+            Chaum::verify_framed(&instance, &old),
+            Err(SigmaProofError::TranscriptVersionMismatch {
+                expected: TRANSCRIPT_VERSION,
+                got,
+            }) if got == TRANSCRIPT_VERSION - 1
+        ));
+    }
+
+    #[test]
+    fn test_chaum_verify_errors() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let instance = ChaumInstance {
+            point1: sk * G,
+            point2: sk * H.clone(),
+        };
+        let proof = Chaum::prove(&witness, &instance).unwrap();
+
+        assert!(matches!(
+            Chaum::verify(&instance, &proof[..33]),
+            Err(SigmaProofError::ProofLengthInvalid { len: 33 })
+        ));
+        assert!(matches!(
+            Chaum::verify(&instance, &proof[..32]),
+            Err(SigmaProofError::MissingCommitments)
+        ));
+        assert!(matches!(
+            Chaum::verify(&instance, &proof[..64]),
+            Err(SigmaProofError::MissingResponses)
+        ));
+
+        let mut long = proof.clone();
+        long.extend_from_slice(&[0; 32]);
+        assert!(matches!(
+            Chaum::verify(&instance, &long),
+            Err(SigmaProofError::ProofLengthInvalid { len: 128 })
+        ));
+
+        // a well-formed proof for another statement
+        let other = ChaumInstance {
+            point1: sk * G,
+            point2: Scalar::random(rng) * H.clone(),
+        };
+        assert!(matches!(
+            Chaum::verify(&other, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    /// Chaum with `f` missing its second output, so that its prover sends one
+    /// more commitment than its verifier expects.
+    struct MismatchedChaum;
+
+    impl SigmaProof for MismatchedChaum {
+        const LABEL: &'static [u8] = b"mismatched-chaum-protocol";
+
+        type WITNESS = ChaumWitness;
+        type INSTANCE = ChaumInstance;
+
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![instance.point1.clone()]
+        }
+
+        fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            Chaum::psi(witness, instance)
+        }
+    }
+
+    #[test]
+    fn test_chaum_equation_count_mismatch() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let instance = ChaumInstance::new(sk * G, sk * H.clone());
+
+        let proof = MismatchedChaum::prove(&witness, &instance).unwrap();
+        assert!(matches!(
+            MismatchedChaum::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCountMismatch {
+                commitments: 2,
+                f: 1,
+                psi: 2,
+            })
+        ));
+        assert!(matches!(
+            MismatchedChaum::verify_streaming(&instance, &proof),
+            Err(SigmaProofError::EquationCountMismatch { .. })
+        ));
+
+        // the commitments are counted per witness in an aggregated proof
+        let witnesses = [witness.clone(), witness.clone(), witness.clone()];
+        let proof = MismatchedChaum::prove_aggregated(&witnesses, &instance).unwrap();
+        assert!(matches!(
+            MismatchedChaum::verify_aggregated(&instance, 3, &proof),
+            Err(SigmaProofError::EquationCountMismatch {
+                commitments: 2,
+                f: 1,
+                psi: 2,
+            })
+        ));
+
+        // a compact proof holds no commitment
+        let proof = MismatchedChaum::prove_compact(&witness, &instance).unwrap();
+        assert!(matches!(
+            MismatchedChaum::verify_compact(&instance, &proof),
+            Err(SigmaProofError::EquationCountMismatch {
+                commitments: 0,
+                f: 1,
+                psi: 2,
+            })
+        ));
+    }
+
+    /// [`MismatchedChaum`] declaring the two equations of its `psi`.
+    struct DeclaredMismatchedChaum;
+
+    impl SigmaProof for DeclaredMismatchedChaum {
+        const LABEL: &'static [u8] = MismatchedChaum::LABEL;
+        const NUM_EQUATIONS: Option<usize> = Some(2);
+
+        type WITNESS = ChaumWitness;
+        type INSTANCE = ChaumInstance;
+
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            MismatchedChaum::f(instance)
+        }
+
+        fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            MismatchedChaum::psi(witness, instance)
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "DeclaredMismatchedChaum::f returned 1 points, but")]
+    fn test_chaum_num_equations_mismatch() {
+        let sk = Scalar::random(&mut rand::rngs::OsRng);
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let instance = ChaumInstance::new(sk * G, sk * H.clone());
+        let _ = DeclaredMismatchedChaum::prove(&witness, &instance);
+    }
+
+    static PSI_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Chaum counting the calls to `psi`.
+    struct CountingChaum;
+
+    impl SigmaProof for CountingChaum {
+        const LABEL: &'static [u8] = Chaum::LABEL;
+        const NUM_EQUATIONS: Option<usize> = Chaum::NUM_EQUATIONS;
+
+        type WITNESS = ChaumWitness;
+        type INSTANCE = ChaumInstance;
+
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            Chaum::f(instance)
+        }
+
+        fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            PSI_CALLS.fetch_add(1, Ordering::Relaxed);
+            Chaum::psi(witness, instance)
+        }
+    }
+
+    #[test]
+    fn test_chaum_verify_counts_declared_equations() {
+        let sk = Scalar::random(&mut rand::rngs::OsRng);
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let instance = ChaumInstance::new(sk * G, sk * H.clone());
+        let proof = Chaum::prove(&witness, &instance).unwrap();
+
+        // psi is only evaluated on the responses, not to count the equations
+        CountingChaum::verify(&instance, &proof).unwrap();
+        assert_eq!(PSI_CALLS.load(Ordering::Relaxed), 1);
+    }
+
+    /// Chaum with the default `f`.
+    struct DefaultFChaum;
+
+    impl SigmaProof for DefaultFChaum {
+        const LABEL: &'static [u8] = Chaum::LABEL;
+        const F_IS_IDENTITY: bool = true;
+
+        type WITNESS = ChaumWitness;
+        type INSTANCE = ChaumInstance;
+
+        fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            Chaum::psi(witness, instance)
+        }
+    }
+
+    #[test]
+    fn test_chaum_default_f() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let instance = ChaumInstance::new(sk * G, sk * H.clone());
+
+        let evaluate = |points: Vec<SymPoint>| -> Vec<_> {
+            points.iter().map(|p| p.evaluate().unwrap()).collect()
+        };
+        assert_eq!(
+            evaluate(DefaultFChaum::f(&instance)),
+            evaluate(Chaum::f(&instance))
+        );
+
+        let proof = DefaultFChaum::prove(&witness, &instance).unwrap();
+        Chaum::verify(&instance, &proof).unwrap();
+
+        // the protocols are equivalent, so their statements hash equally
+        assert_eq!(
+            DefaultFChaum::statement_hash().unwrap(),
+            Chaum::statement_hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_chaum_instance_constructor() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let (g, h) = (G.evaluate().unwrap(), H.evaluate().unwrap());
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+
+        // concrete points, as well as symbolic ones, can be passed
+        let instance = ChaumInstance::new(sk * g, sk * H.clone());
+        let points: Vec<_> = instance
+            .points()
+            .iter()
+            .map(|p| p.evaluate().unwrap())
+            .collect();
+        assert_eq!(points, vec![sk * g, sk * h]);
+
+        let proof = Chaum::prove(&witness, &instance).unwrap();
+        Chaum::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_chaum_instance_bytes() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let instance = ChaumInstance::new(sk * G, sk * H.clone());
+
+        let bytes = instance.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 32 * 2);
+        assert_eq!(
+            bytes[..32],
+            *(sk * G).evaluate().unwrap().compress().as_bytes()
+        );
+
+        let parsed = ChaumInstance::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.to_bytes().unwrap(), bytes);
+        let parsed = ChaumInstance::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(parsed.to_bytes().unwrap(), bytes);
+
+        // a proof for the parsed instance verifies against the original
+        let witness = ChaumWitness {
+            x: SymScalar::Const(sk),
+        };
+        let proof = Chaum::prove(&witness, &parsed).unwrap();
+        Chaum::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_chaum_instance_bytes_malformed() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let bytes = ChaumInstance::new(sk * G, sk * H.clone())
+            .to_bytes()
+            .unwrap();
+
+        for malformed in [
+            &bytes[..32],
+            &bytes[..63],
+            &[bytes.as_slice(), &[0; 32]].concat(),
+        ] {
+            assert!(matches!(
+                ChaumInstance::from_bytes(malformed),
+                Err(SigmaProofError::InvalidInstanceEncoding)
+            ));
+        }
+
+        // not a canonical encoding of a point
+        let mut invalid = bytes.clone();
+        invalid[32..].copy_from_slice(&[0xff; 32]);
+        assert!(matches!(
+            ChaumInstance::try_from(invalid.as_slice()),
+            Err(SigmaProofError::InvalidInstanceEncoding)
+        ));
+    }
+
     #[test]
     fn test_chaum_spec_generation() {
         let spec = Chaum::spec();
         println!("{spec}");
+        assert!(spec.contains("(\\texttt{x} \\cdot G, \\texttt{x} \\cdot H)"));
+        assert!(spec.contains("The generators used by $\\psi$ are $G, H$."));
     }
+
+    conformance_tests!(Chaum);
 }
@@ -0,0 +1,113 @@
+use crate::{
+    absorb::{SymInstance, SymPoint, SymScalar, SymWitness},
+    compiler::SigmaProof,
+    sigmas::H,
+};
+use alloc::{vec, vec::Vec};
+use curve25519_dalek::Scalar;
+
+/// Proves that two Pedersen commitments `Ca = m*G + ra*H` and
+/// `Cb = m*G + rb*H` hide the same value.
+///
+/// The difference `Ca - Cb` is then `r*H` with `r = ra - rb`, so the prover
+/// only shows knowledge of `r`. Unlike most protocols, `f` does not return the
+/// instance points but a difference of them.
+pub struct EqualityOfCommitments;
+
+#[derive(SymWitness, Clone)]
+pub struct EqualityOfCommitmentsWitness {
+    blinding: SymScalar,
+}
+
+impl EqualityOfCommitmentsWitness {
+    /// Builds the witness from the blindings of both commitments.
+    pub fn from_blindings(ra: Scalar, rb: Scalar) -> Self {
+        Self {
+            blinding: SymScalar::Const(ra - rb),
+        }
+    }
+}
+
+#[derive(SymInstance, Clone)]
+pub struct EqualityOfCommitmentsInstance {
+    commitment_a: SymPoint,
+    commitment_b: SymPoint,
+}
+
+impl SigmaProof for EqualityOfCommitments {
+    const LABEL: &'static [u8] = b"equality-of-commitments-protocol";
+
+    type WITNESS = EqualityOfCommitmentsWitness;
+    type INSTANCE = EqualityOfCommitmentsInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::INSTANCE {
+            commitment_a,
+            commitment_b,
+        } = instance.clone();
+        vec![commitment_a - commitment_b]
+    }
+
+    fn psi(witness: &Self::WITNESS, _instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::WITNESS { blinding } = witness.clone();
+        vec![blinding * H.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{errors::SigmaProofError, sigmas::G};
+
+    fn commit(m: Scalar, r: Scalar) -> SymPoint {
+        (m * G) + (r * H.clone())
+    }
+
+    #[test]
+    fn test_equality_of_commitments() {
+        let rng = &mut rand::rngs::OsRng;
+        let m = Scalar::random(rng);
+        let (ra, rb) = (Scalar::random(rng), Scalar::random(rng));
+
+        let witness = EqualityOfCommitmentsWitness::from_blindings(ra, rb);
+        let instance = EqualityOfCommitmentsInstance {
+            commitment_a: commit(m, ra),
+            commitment_b: commit(m, rb),
+        };
+
+        let proof = EqualityOfCommitments::prove_checked(&witness, &instance).unwrap();
+        EqualityOfCommitments::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_equality_of_commitments_unequal_values() {
+        let rng = &mut rand::rngs::OsRng;
+        let m = Scalar::random(rng);
+        let (ra, rb) = (Scalar::random(rng), Scalar::random(rng));
+
+        let witness = EqualityOfCommitmentsWitness::from_blindings(ra, rb);
+        let instance = EqualityOfCommitmentsInstance {
+            commitment_a: commit(m, ra),
+            commitment_b: commit(m + Scalar::ONE, rb),
+        };
+
+        assert!(matches!(
+            EqualityOfCommitments::prove_checked(&witness, &instance),
+            Err(SigmaProofError::InstanceConsistency)
+        ));
+        let proof = EqualityOfCommitments::prove(&witness, &instance).unwrap();
+        assert!(matches!(
+            EqualityOfCommitments::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_equality_of_commitments_spec_generation() {
+        let spec = EqualityOfCommitments::spec();
+        println!("{spec}");
+        assert!(spec.contains(r"(\texttt{commitment\_a} - \texttt{commitment\_b})"));
+    }
+
+    conformance_tests!(EqualityOfCommitments);
+}
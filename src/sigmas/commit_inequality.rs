@@ -0,0 +1,118 @@
+use crate::{
+    absorb::{SymInstance, SymPoint, SymScalar, SymWitness},
+    compiler::SigmaProof,
+    errors::{SigmaProofError, SigmaProofResult},
+    sigmas::{G, H},
+};
+use alloc::{vec, vec::Vec};
+use curve25519_dalek::Scalar;
+
+/// Proves that two Pedersen commitments `C1 = m1*G + r1*H` and
+/// `C2 = m2*G + r2*H` hide different values.
+///
+/// The difference `D = C1 - C2` opens to `m = m1 - m2` with blinding
+/// `r = r1 - r2`. `m` is nonzero iff the prover can write `G` in terms of `D`
+/// and `H`, i.e. `G = a*D + b*H` with `a = 1/m` and `b = -r/m`.
+pub struct CommitInequality;
+
+#[derive(SymWitness, Clone)]
+pub struct CommitInequalityWitness {
+    inverse: SymScalar,
+    blinding: SymScalar,
+}
+
+impl CommitInequalityWitness {
+    /// Builds the witness from the openings of both commitments, failing if
+    /// the committed values are equal.
+    pub fn from_openings(m1: Scalar, r1: Scalar, m2: Scalar, r2: Scalar) -> SigmaProofResult<Self> {
+        let m = m1 - m2;
+        if m == Scalar::ZERO {
+            return Err(SigmaProofError::InvalidScalarValues);
+        }
+        let inverse = m.invert();
+        Ok(Self {
+            inverse: SymScalar::Const(inverse),
+            blinding: SymScalar::Const(-(r1 - r2) * inverse),
+        })
+    }
+}
+
+#[derive(SymInstance, Clone)]
+pub struct CommitInequalityInstance {
+    commitment1: SymPoint,
+    commitment2: SymPoint,
+}
+
+impl SigmaProof for CommitInequality {
+    const LABEL: &'static [u8] = b"commit-inequality-protocol";
+
+    type WITNESS = CommitInequalityWitness;
+    type INSTANCE = CommitInequalityInstance;
+
+    fn f(_instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        vec![G]
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::WITNESS { inverse, blinding } = witness.clone();
+        let Self::INSTANCE {
+            commitment1,
+            commitment2,
+        } = instance.clone();
+        vec![inverse * (commitment1 - commitment2) + blinding * H.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(m: Scalar, r: Scalar) -> SymPoint {
+        (m * G) + (r * H.clone())
+    }
+
+    #[test]
+    fn test_commit_inequality_protocol() {
+        let rng = &mut rand::rngs::OsRng;
+        let (m1, r1) = (Scalar::random(rng), Scalar::random(rng));
+        let (m2, r2) = (Scalar::random(rng), Scalar::random(rng));
+
+        let witness = CommitInequalityWitness::from_openings(m1, r1, m2, r2).unwrap();
+        let instance = CommitInequalityInstance {
+            commitment1: commit(m1, r1),
+            commitment2: commit(m2, r2),
+        };
+
+        let proof = CommitInequality::prove(&witness, &instance).unwrap();
+        CommitInequality::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_commit_inequality_equal_values() {
+        let rng = &mut rand::rngs::OsRng;
+        let m = Scalar::random(rng);
+        let (r1, r2) = (Scalar::random(rng), Scalar::random(rng));
+
+        assert!(matches!(
+            CommitInequalityWitness::from_openings(m, r1, m, r2),
+            Err(SigmaProofError::InvalidScalarValues)
+        ));
+
+        // a witness for different values does not verify against equal ones
+        let witness = CommitInequalityWitness::from_openings(m, r1, m + Scalar::ONE, r2).unwrap();
+        let instance = CommitInequalityInstance {
+            commitment1: commit(m, r1),
+            commitment2: commit(m, r2),
+        };
+        let proof = CommitInequality::prove(&witness, &instance).unwrap();
+        assert!(CommitInequality::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_commit_inequality_spec_generation() {
+        let spec = CommitInequality::spec();
+        println!("{spec}");
+    }
+
+    conformance_tests!(CommitInequality);
+}
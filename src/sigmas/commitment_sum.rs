@@ -0,0 +1,132 @@
+use crate::{
+    absorb::{SymInstance, SymPoint, SymScalar, SymWitness},
+    compiler::SigmaProof,
+    sigmas::{G, H},
+};
+use alloc::{vec, vec::Vec};
+
+/// Proves that the Pedersen commitment `C3` opens to the sum of the openings
+/// of `C1` and `C2`, without revealing any of them.
+///
+/// With `Ci = vi*G + ri*H`, the prover shows knowledge of `v1`, `r1`, `v2`
+/// and `r2` such that
+///
+/// ```text
+/// v1*G + r1*H = C1
+/// v2*G + r2*H = C2
+/// (v1 + v2)*G + (r1 + r2)*H = C3
+/// ```
+///
+/// The opening of `C3` is not part of the witness: it is `(v1 + v2, r1 + r2)`
+/// by the last equation.
+pub struct CommitmentSum;
+
+#[derive(SymWitness, Clone)]
+pub struct CommitmentSumWitness {
+    value1: SymScalar,
+    blinding1: SymScalar,
+    value2: SymScalar,
+    blinding2: SymScalar,
+}
+
+#[derive(SymInstance, Clone)]
+pub struct CommitmentSumInstance {
+    commitment1: SymPoint,
+    commitment2: SymPoint,
+    sum: SymPoint,
+}
+
+impl SigmaProof for CommitmentSum {
+    const LABEL: &'static [u8] = b"commitment-sum-protocol";
+    const NUM_EQUATIONS: Option<usize> = Some(3);
+
+    type WITNESS = CommitmentSumWitness;
+    type INSTANCE = CommitmentSumInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::INSTANCE {
+            commitment1,
+            commitment2,
+            sum,
+        } = instance.clone();
+        vec![commitment1, commitment2, sum]
+    }
+
+    fn psi(witness: &Self::WITNESS, _instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::WITNESS {
+            value1,
+            blinding1,
+            value2,
+            blinding2,
+        } = witness.clone();
+        vec![
+            &value1 * G + &blinding1 * H.clone(),
+            &value2 * G + &blinding2 * H.clone(),
+            (value1 + value2) * G + (blinding1 + blinding2) * H.clone(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::Scalar;
+
+    use super::*;
+    use crate::errors::SigmaProofError;
+
+    fn commit(v: Scalar, r: Scalar) -> SymPoint {
+        v * G + r * H.clone()
+    }
+
+    /// A witness for `C1` and `C2`, and an instance whose `C3` commits to
+    /// `v1 + v2 + offset` with blinding `r1 + r2`.
+    fn statement(offset: Scalar) -> (CommitmentSumWitness, CommitmentSumInstance) {
+        let rng = &mut rand::rngs::OsRng;
+        let (v1, r1, v2, r2) = (
+            Scalar::random(rng),
+            Scalar::random(rng),
+            Scalar::random(rng),
+            Scalar::random(rng),
+        );
+        let witness = CommitmentSumWitness::from_values(&[v1, r1, v2, r2]).unwrap();
+        let instance = CommitmentSumInstance {
+            commitment1: commit(v1, r1),
+            commitment2: commit(v2, r2),
+            sum: commit(v1 + v2 + offset, r1 + r2),
+        };
+        (witness, instance)
+    }
+
+    #[test]
+    fn test_commitment_sum() {
+        let (witness, instance) = statement(Scalar::ZERO);
+        let proof = CommitmentSum::prove_checked(&witness, &instance).unwrap();
+        CommitmentSum::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_commitment_sum_violated() {
+        let (witness, instance) = statement(Scalar::ONE);
+        assert!(matches!(
+            CommitmentSum::prove_checked(&witness, &instance),
+            Err(SigmaProofError::InstanceConsistency)
+        ));
+        let proof = CommitmentSum::prove(&witness, &instance).unwrap();
+        assert!(matches!(
+            CommitmentSum::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_commitment_sum_spec_generation() {
+        let spec = CommitmentSum::spec();
+        println!("{spec}");
+        assert!(spec.contains(
+            "((\\texttt{value1} + \\texttt{value2}) \\cdot G \
+             + (\\texttt{blinding1} + \\texttt{blinding2}) \\cdot H) = \\texttt{sum}"
+        ));
+    }
+
+    conformance_tests!(CommitmentSum);
+}
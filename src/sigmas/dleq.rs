@@ -0,0 +1,121 @@
+use crate::{
+    absorb::{SymInstance, SymPoint, SymScalar, SymWitness},
+    compiler::SigmaProof,
+    errors::SigmaProofResult,
+    sigmas::check_independent_generators,
+};
+use alloc::{vec, vec::Vec};
+use curve25519_dalek::RistrettoPoint;
+
+/// Proves that `log_{base_g}(a) == log_{base_h}(b)` for arbitrary bases.
+pub struct DleqProtocol;
+
+#[derive(SymWitness, Clone)]
+pub struct DleqWitness {
+    x: SymScalar,
+}
+
+#[derive(SymInstance, Clone)]
+pub struct DleqInstance {
+    base_g: SymPoint,
+    base_h: SymPoint,
+    a: SymPoint,
+    b: SymPoint,
+}
+
+impl DleqInstance {
+    /// Builds the instance, rejecting equal bases or identity bases with
+    /// [`check_independent_generators`].
+    pub fn try_new(
+        base_g: RistrettoPoint,
+        base_h: RistrettoPoint,
+        a: RistrettoPoint,
+        b: RistrettoPoint,
+    ) -> SigmaProofResult<Self> {
+        check_independent_generators(&[base_g, base_h])?;
+        Ok(Self {
+            base_g: SymPoint::Const(base_g),
+            base_h: SymPoint::Const(base_h),
+            a: SymPoint::Const(a),
+            b: SymPoint::Const(b),
+        })
+    }
+}
+
+impl SigmaProof for DleqProtocol {
+    const LABEL: &'static [u8] = b"dleq-protocol";
+
+    type WITNESS = DleqWitness;
+    type INSTANCE = DleqInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::INSTANCE { a, b, .. } = instance.clone();
+        vec![a, b]
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::WITNESS { x } = witness.clone();
+        let Self::INSTANCE { base_g, base_h, .. } = instance.clone();
+        vec![&x * base_g, &x * base_h]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::Scalar;
+
+    use super::*;
+    use crate::errors::SigmaProofError;
+
+    fn random_instance(x: Scalar, y: Scalar) -> DleqInstance {
+        let rng = &mut rand::rngs::OsRng;
+        let base_g = RistrettoPoint::random(rng);
+        let base_h = RistrettoPoint::random(rng);
+        DleqInstance::try_new(base_g, base_h, x * base_g, y * base_h).unwrap()
+    }
+
+    #[test]
+    fn test_dleq_protocol() {
+        let rng = &mut rand::rngs::OsRng;
+        let x = Scalar::random(rng);
+        let witness = DleqWitness {
+            x: SymScalar::Const(x),
+        };
+        let instance = random_instance(x, x);
+
+        let proof = DleqProtocol::prove(&witness, &instance).unwrap();
+        DleqProtocol::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_dleq_mismatched_logs() {
+        let rng = &mut rand::rngs::OsRng;
+        let x = Scalar::random(rng);
+        let witness = DleqWitness {
+            x: SymScalar::Const(x),
+        };
+        let instance = random_instance(x, Scalar::random(rng));
+
+        let proof = DleqProtocol::prove(&witness, &instance).unwrap();
+        assert!(DleqProtocol::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_dleq_equal_bases() {
+        let rng = &mut rand::rngs::OsRng;
+        let base = RistrettoPoint::random(rng);
+        let x = Scalar::random(rng);
+        assert!(matches!(
+            DleqInstance::try_new(base, base, x * base, x * base),
+            Err(SigmaProofError::GeneratorCollision)
+        ));
+    }
+
+    #[test]
+    fn test_dleq_spec_generation() {
+        let spec = DleqProtocol::spec();
+        println!("{spec}");
+    }
+
+    conformance_tests!(DleqProtocol);
+}
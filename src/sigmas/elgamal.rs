@@ -0,0 +1,165 @@
+use crate::{
+    absorb::{SymInstance, SymPoint, SymScalar, SymWitness},
+    compiler::SigmaProof,
+    sigmas::G,
+};
+use alloc::{vec, vec::Vec};
+
+/// Proves that `(c1, c2) = (r*G, m*G + r*pubkey)` is a well-formed ElGamal
+/// encryption under `pubkey`, i.e. knowledge of the message `m` and of the
+/// randomness `r`.
+pub struct ElGamalEncryption;
+
+#[derive(SymWitness, Clone)]
+pub struct ElGamalEncryptionWitness {
+    message: SymScalar,
+    randomness: SymScalar,
+}
+
+#[derive(SymInstance, Clone)]
+pub struct ElGamalEncryptionInstance {
+    pubkey: SymPoint,
+    c1: SymPoint,
+    c2: SymPoint,
+}
+
+impl SigmaProof for ElGamalEncryption {
+    const LABEL: &'static [u8] = b"elgamal-encryption-protocol";
+
+    type WITNESS = ElGamalEncryptionWitness;
+    type INSTANCE = ElGamalEncryptionInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::INSTANCE { c1, c2, .. } = instance.clone();
+        vec![c1, c2]
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::WITNESS {
+            message,
+            randomness,
+        } = witness.clone();
+        vec![
+            &randomness * G,
+            message * G + randomness * instance.pubkey.clone(),
+        ]
+    }
+}
+
+/// Like [`ElGamalEncryption`] for a public message `m`, i.e. knowledge of the
+/// randomness `r` such that `(c1, c2 - m*G) = (r*G, r*pubkey)`.
+pub struct ElGamalKnownMessage;
+
+#[derive(SymWitness, Clone)]
+pub struct ElGamalKnownMessageWitness {
+    randomness: SymScalar,
+}
+
+#[derive(SymInstance, Clone)]
+pub struct ElGamalKnownMessageInstance {
+    message: SymScalar,
+    pubkey: SymPoint,
+    c1: SymPoint,
+    c2: SymPoint,
+}
+
+impl SigmaProof for ElGamalKnownMessage {
+    const LABEL: &'static [u8] = b"elgamal-known-message-protocol";
+
+    type WITNESS = ElGamalKnownMessageWitness;
+    type INSTANCE = ElGamalKnownMessageInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::INSTANCE {
+            message, c1, c2, ..
+        } = instance.clone();
+        vec![c1, c2 - message * G]
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::WITNESS { randomness } = witness.clone();
+        vec![&randomness * G, randomness * instance.pubkey.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::{RistrettoPoint, Scalar};
+
+    use super::*;
+    use crate::errors::SigmaProofError;
+
+    /// Encrypts `m` under a fresh key with the randomness `r`.
+    fn encrypt(m: Scalar, r: Scalar) -> (RistrettoPoint, SymPoint, SymPoint) {
+        let pubkey = RistrettoPoint::random(&mut rand::rngs::OsRng);
+        (pubkey, r * G, m * G + r * SymPoint::Const(pubkey))
+    }
+
+    #[test]
+    fn test_elgamal_encryption() {
+        let rng = &mut rand::rngs::OsRng;
+        let (m, r) = (Scalar::random(rng), Scalar::random(rng));
+        let (pubkey, c1, c2) = encrypt(m, r);
+        let witness = ElGamalEncryptionWitness::from_values(&[m, r]).unwrap();
+        let instance = ElGamalEncryptionInstance::new(pubkey, c1, c2);
+
+        let proof = ElGamalEncryption::prove_checked(&witness, &instance).unwrap();
+        ElGamalEncryption::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_elgamal_encryption_malformed() {
+        let rng = &mut rand::rngs::OsRng;
+        let (m, r) = (Scalar::random(rng), Scalar::random(rng));
+        let (pubkey, c1, _) = encrypt(m, r);
+        // c2 uses another randomness than c1
+        let c2 = m * G + Scalar::random(rng) * SymPoint::Const(pubkey);
+        let witness = ElGamalEncryptionWitness::from_values(&[m, r]).unwrap();
+        let instance = ElGamalEncryptionInstance::new(pubkey, c1, c2);
+
+        assert!(matches!(
+            ElGamalEncryption::prove_checked(&witness, &instance),
+            Err(SigmaProofError::InstanceConsistency)
+        ));
+        let proof = ElGamalEncryption::prove(&witness, &instance).unwrap();
+        assert!(matches!(
+            ElGamalEncryption::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_elgamal_known_message() {
+        let rng = &mut rand::rngs::OsRng;
+        let (m, r) = (Scalar::from(42u64), Scalar::random(rng));
+        let (pubkey, c1, c2) = encrypt(m, r);
+        let witness = ElGamalKnownMessageWitness::from_values(&[r]).unwrap();
+        let instance = ElGamalKnownMessageInstance::new(m, pubkey, c1.clone(), c2.clone());
+
+        let proof = ElGamalKnownMessage::prove_checked(&witness, &instance).unwrap();
+        ElGamalKnownMessage::verify(&instance, &proof).unwrap();
+
+        // the ciphertext does not encrypt another message
+        let other = ElGamalKnownMessageInstance::new(m + Scalar::ONE, pubkey, c1, c2);
+        assert!(ElGamalKnownMessage::verify(&other, &proof).is_err());
+        assert!(matches!(
+            ElGamalKnownMessage::prove_checked(&witness, &other),
+            Err(SigmaProofError::InstanceConsistency)
+        ));
+    }
+
+    #[test]
+    fn test_elgamal_spec_generation() {
+        let spec = ElGamalEncryption::spec();
+        println!("{spec}");
+        assert!(spec.contains(
+            r"(\texttt{randomness} \cdot G, (\texttt{message} \cdot G + \texttt{randomness} \cdot \texttt{pubkey}))"
+        ));
+
+        let spec = ElGamalKnownMessage::spec();
+        assert!(spec.contains(r"(\texttt{c2} - \texttt{message} \cdot G)"));
+    }
+
+    conformance_tests!(ElGamalEncryption);
+    conformance_tests!(known_message: ElGamalKnownMessage);
+}
@@ -0,0 +1,201 @@
+use crate::{
+    absorb::{sealed_instance, FixedLenInstance, SymInstance, SymPoint, SymScalar},
+    compiler::{DegeneracyKind, SigmaProof},
+    errors::SigmaProofResult,
+    sigmas::linear_relation::{LinearRelation, LinearRelationInstance},
+};
+use alloc::{string::String, vec::Vec};
+use curve25519_dalek::{traits::Identity, RistrettoPoint, Scalar};
+use rand_core::CryptoRngCore;
+
+/// A protocol for knowledge of a representation `point = sum_i x_i * bases[i]`
+/// of a point in `N` public bases.
+///
+/// This is the [`LinearRelation`] with a single equation, under its own
+/// label. [`Okamoto`](super::okamoto::Okamoto) is the case `N = 2` with the
+/// bases fixed to `G` and `H`, and Schnorr the case `N = 1`.
+pub struct GeneralizedOkamoto<const N: usize>;
+
+pub type GeneralizedOkamotoWitness<const N: usize> = [SymScalar; N];
+
+#[derive(Clone)]
+pub struct GeneralizedOkamotoInstance<const N: usize>(LinearRelationInstance<N, 1>);
+
+impl<const N: usize> GeneralizedOkamotoInstance<N> {
+    /// Builds the statement `point = sum_i x_i * bases[i]`.
+    pub fn new(bases: [SymPoint; N], point: SymPoint) -> Self {
+        Self(LinearRelationInstance::new([bases], [point]))
+    }
+}
+
+impl<const N: usize> sealed_instance::Sealed for GeneralizedOkamotoInstance<N> {}
+
+impl<const N: usize> SymInstance for GeneralizedOkamotoInstance<N> {
+    fn num_scalars() -> Option<usize> {
+        LinearRelationInstance::<N, 1>::num_scalars()
+    }
+
+    fn num_points() -> Option<usize> {
+        LinearRelationInstance::<N, 1>::num_points()
+    }
+
+    /// Expects the bases, followed by the point.
+    fn from_values(scalars: &[Scalar], points: &[RistrettoPoint]) -> SigmaProofResult<Self> {
+        LinearRelationInstance::from_values(scalars, points).map(Self)
+    }
+
+    fn get_field_names() -> Vec<String> {
+        LinearRelationInstance::<N, 1>::get_field_names()
+    }
+
+    fn points(&self) -> Vec<SymPoint> {
+        self.0.points()
+    }
+
+    fn scalars(&self) -> Vec<SymScalar> {
+        self.0.scalars()
+    }
+}
+
+impl<const N: usize> FixedLenInstance for GeneralizedOkamotoInstance<N> {
+    const NUM_SCALARS: usize = LinearRelationInstance::<N, 1>::NUM_SCALARS;
+    const NUM_POINTS: usize = LinearRelationInstance::<N, 1>::NUM_POINTS;
+}
+
+impl<const N: usize> SigmaProof for GeneralizedOkamoto<N> {
+    const LABEL: &'static [u8] = b"generalized-okamoto-protocol";
+
+    type WITNESS = GeneralizedOkamotoWitness<N>;
+    type INSTANCE = GeneralizedOkamotoInstance<N>;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        LinearRelation::f(&instance.0)
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        LinearRelation::psi(witness, &instance.0)
+    }
+
+    /// Degenerates the bases rather than arbitrary points, and sets the point
+    /// to a representation in them, so that the instance is otherwise one an
+    /// honest prover could prove.
+    fn random_degenerate_instance<R: CryptoRngCore + ?Sized>(
+        rng: &mut R,
+        kind: DegeneracyKind,
+    ) -> Option<Self::INSTANCE> {
+        let mut bases: [RistrettoPoint; N] = core::array::from_fn(|_| RistrettoPoint::random(rng));
+        match kind {
+            DegeneracyKind::IdentityPoint => {
+                *bases.first_mut()? = RistrettoPoint::identity();
+            }
+            DegeneracyKind::DuplicatePoints => {
+                let first = *bases.first()?;
+                *bases.get_mut(1)? = first;
+            }
+        }
+
+        let point: RistrettoPoint = bases.iter().map(|b| Scalar::random(rng) * b).sum();
+        Some(GeneralizedOkamotoInstance::new(
+            bases.map(SymPoint::Const),
+            point.into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sigmas::{
+        okamoto::{Okamoto, OkamotoInstance, OkamotoWitness},
+        G, H,
+    };
+    use crate::{absorb::SymWitness, errors::SigmaProofError};
+
+    fn random_statement(
+        rng: &mut rand::rngs::OsRng,
+    ) -> ([Scalar; 3], GeneralizedOkamotoInstance<3>) {
+        let xs: [Scalar; 3] = core::array::from_fn(|_| Scalar::random(rng));
+        let bases: [RistrettoPoint; 3] = core::array::from_fn(|_| RistrettoPoint::random(rng));
+        let point: RistrettoPoint = xs.iter().zip(&bases).map(|(x, b)| x * b).sum();
+        let instance = GeneralizedOkamotoInstance::new(bases.map(SymPoint::Const), point.into());
+        (xs, instance)
+    }
+
+    #[test]
+    fn test_generalized_okamoto_three_bases() {
+        let rng = &mut rand::rngs::OsRng;
+        let (xs, instance) = random_statement(rng);
+        let witness = xs.map(SymScalar::Const);
+
+        let proof = GeneralizedOkamoto::prove(&witness, &instance).unwrap();
+        GeneralizedOkamoto::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_generalized_okamoto_invalid_witness() {
+        let rng = &mut rand::rngs::OsRng;
+        let (xs, instance) = random_statement(rng);
+
+        // swapping two scalars is a representation of another point
+        let swapped = [xs[1], xs[0], xs[2]].map(SymScalar::Const);
+        let proof = GeneralizedOkamoto::prove(&swapped, &instance).unwrap();
+        assert!(matches!(
+            GeneralizedOkamoto::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+        assert!(matches!(
+            GeneralizedOkamoto::prove_checked(&swapped, &instance),
+            Err(SigmaProofError::InstanceConsistency)
+        ));
+    }
+
+    #[test]
+    fn test_generalized_okamoto_matches_okamoto() {
+        let rng = &mut rand::rngs::OsRng;
+        let (x, y) = (Scalar::random(rng), Scalar::random(rng));
+        let point = (x * G) + (y * H.clone());
+
+        let instance = GeneralizedOkamotoInstance::new([G, H.clone()], point.clone());
+        let okamoto_instance =
+            OkamotoInstance::from_values(&[], &[point.evaluate().unwrap()]).unwrap();
+        let okamoto_witness = OkamotoWitness::from_values(&[x, y]).unwrap();
+
+        let proof = GeneralizedOkamoto::prove(&[x.into(), y.into()], &instance).unwrap();
+        let okamoto_proof = Okamoto::prove(&okamoto_witness, &okamoto_instance).unwrap();
+        assert_eq!(proof.len(), okamoto_proof.len());
+        GeneralizedOkamoto::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_generalized_okamoto_from_values() {
+        let rng = &mut rand::rngs::OsRng;
+        let (_, instance) = random_statement(rng);
+        let points: Vec<RistrettoPoint> = instance
+            .points()
+            .iter()
+            .map(|p| p.evaluate().unwrap())
+            .collect();
+
+        let decoded = GeneralizedOkamotoInstance::<3>::from_values(&[], &points).unwrap();
+        let decoded: Vec<RistrettoPoint> = decoded
+            .points()
+            .iter()
+            .map(|p| p.evaluate().unwrap())
+            .collect();
+        assert_eq!(decoded, points);
+        assert!(matches!(
+            GeneralizedOkamotoInstance::<3>::from_values(&[], &points[..3]),
+            Err(SigmaProofError::InsufficientPoints)
+        ));
+        let oversized = [&points[..], &[RistrettoPoint::random(rng)]].concat();
+        assert!(matches!(
+            GeneralizedOkamotoInstance::<3>::from_values(&[], &oversized),
+            Err(SigmaProofError::TooManyPoints {
+                expected: 4,
+                actual: 5
+            })
+        ));
+    }
+
+    conformance_tests!(GeneralizedOkamoto<3>);
+}
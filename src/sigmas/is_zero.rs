@@ -0,0 +1,90 @@
+use crate::{
+    absorb::{SymInstance, SymPoint, SymScalar, SymWitness},
+    compiler::SigmaProof,
+    sigmas::H,
+};
+use alloc::{vec, vec::Vec};
+
+/// Proves that a Pedersen commitment `C = m*G + r*H` opens to `m = 0`, i.e.
+/// knowledge of `r` such that `C = r*H`.
+pub struct IsZero;
+
+#[derive(SymWitness, Clone)]
+pub struct IsZeroWitness {
+    blinding: SymScalar,
+}
+
+impl IsZeroWitness {
+    pub fn new(blinding: SymScalar) -> Self {
+        Self { blinding }
+    }
+}
+
+#[derive(SymInstance, Clone)]
+pub struct IsZeroInstance {
+    commitment: SymPoint,
+}
+
+impl SigmaProof for IsZero {
+    const LABEL: &'static [u8] = b"is-zero-protocol";
+
+    type WITNESS = IsZeroWitness;
+    type INSTANCE = IsZeroInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::INSTANCE { commitment } = instance.clone();
+        vec![commitment]
+    }
+
+    fn psi(witness: &Self::WITNESS, _instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::WITNESS { blinding } = witness.clone();
+        vec![blinding * H.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::Scalar;
+
+    use super::*;
+    use crate::sigmas::G;
+
+    #[test]
+    fn test_is_zero_protocol() {
+        let rng = &mut rand::rngs::OsRng;
+        let r = Scalar::random(rng);
+        let witness = IsZeroWitness {
+            blinding: SymScalar::Const(r),
+        };
+        let instance = IsZeroInstance {
+            commitment: r * H.clone(),
+        };
+
+        let proof = IsZero::prove(&witness, &instance).unwrap();
+        IsZero::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_is_zero_nonzero_commitment() {
+        let rng = &mut rand::rngs::OsRng;
+        let r = Scalar::random(rng);
+        let witness = IsZeroWitness {
+            blinding: SymScalar::Const(r),
+        };
+        // commits to 1 instead of 0
+        let instance = IsZeroInstance {
+            commitment: G + (r * H.clone()),
+        };
+
+        let proof = IsZero::prove(&witness, &instance).unwrap();
+        assert!(IsZero::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_is_zero_spec_generation() {
+        let spec = IsZero::spec();
+        println!("{spec}");
+    }
+
+    conformance_tests!(IsZero);
+}
@@ -0,0 +1,94 @@
+use crate::{
+    absorb::{SymInstance, SymPoint, SymScalar, SymWitness},
+    compiler::SigmaProof,
+    sigmas::{G, H},
+};
+use alloc::{vec, vec::Vec};
+
+/// Proves that a Pedersen commitment `C = v*G + r*H` opens to the public
+/// value `v`, i.e. knowledge of `r` such that `r*H = C - v*G`.
+pub struct CommitmentToKnownValue;
+
+#[derive(SymWitness, Clone)]
+pub struct CommitmentToKnownValueWitness {
+    blinding: SymScalar,
+}
+
+#[derive(SymInstance, Clone)]
+pub struct CommitmentToKnownValueInstance {
+    value: SymScalar,
+    commitment: SymPoint,
+}
+
+impl SigmaProof for CommitmentToKnownValue {
+    const LABEL: &'static [u8] = b"commitment-to-known-value-protocol";
+
+    type WITNESS = CommitmentToKnownValueWitness;
+    type INSTANCE = CommitmentToKnownValueInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::INSTANCE { value, commitment } = instance.clone();
+        vec![commitment - value * G]
+    }
+
+    fn psi(witness: &Self::WITNESS, _instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::WITNESS { blinding } = witness.clone();
+        vec![blinding * H.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::Scalar;
+
+    use super::*;
+    use crate::errors::SigmaProofError;
+
+    fn commit(v: u64, r: Scalar) -> SymPoint {
+        Scalar::from(v) * G + r * H.clone()
+    }
+
+    #[test]
+    fn test_commitment_to_known_value() {
+        let rng = &mut rand::rngs::OsRng;
+        let r = Scalar::random(rng);
+        let witness = CommitmentToKnownValueWitness {
+            blinding: SymScalar::Const(r),
+        };
+        let instance = CommitmentToKnownValueInstance::new(Scalar::from(42u64), commit(42, r));
+
+        let proof = CommitmentToKnownValue::prove(&witness, &instance).unwrap();
+        CommitmentToKnownValue::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_commitment_to_known_value_wrong_value() {
+        let rng = &mut rand::rngs::OsRng;
+        let r = Scalar::random(rng);
+        let witness = CommitmentToKnownValueWitness {
+            blinding: SymScalar::Const(r),
+        };
+        let instance = CommitmentToKnownValueInstance::new(Scalar::from(41u64), commit(42, r));
+
+        let proof = CommitmentToKnownValue::prove(&witness, &instance).unwrap();
+        assert!(matches!(
+            CommitmentToKnownValue::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+
+        // the value is bound to the proof, so a proof for the right value does
+        // not verify against another one
+        let honest = CommitmentToKnownValueInstance::new(Scalar::from(42u64), commit(42, r));
+        let proof = CommitmentToKnownValue::prove(&witness, &honest).unwrap();
+        assert!(CommitmentToKnownValue::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_commitment_to_known_value_spec_generation() {
+        let spec = CommitmentToKnownValue::spec();
+        println!("{spec}");
+        assert!(spec.contains("(\\texttt{commitment} - \\texttt{value} \\cdot G)"));
+    }
+
+    conformance_tests!(CommitmentToKnownValue);
+}
@@ -0,0 +1,143 @@
+use crate::{
+    absorb::{SymInstance, SymPoint, SymScalar, SymWitness},
+    compiler::SigmaProof,
+    sigmas::{G, H},
+};
+use alloc::{vec, vec::Vec};
+
+/// Proves that the Pedersen commitment `D` opens to `a*v + b`, where `v` is
+/// the value committed in `C` and the coefficients `a` and `b` are public.
+///
+/// With `C = v*G + r*H` and `D = (a*v + b)*G + s*H`, the prover shows
+/// knowledge of `v`, `r` and `s` such that
+///
+/// ```text
+/// v*G + r*H = C
+/// (a*v)*G + s*H = D - b*G
+/// ```
+///
+/// The coefficients are instance scalars rather than constants of the
+/// protocol, so they are bound to the proof and named in its spec.
+pub struct LinearCombinationProof;
+
+#[derive(SymWitness, Clone)]
+pub struct LinearCombinationWitness {
+    value: SymScalar,
+    blinding: SymScalar,
+    result_blinding: SymScalar,
+}
+
+#[derive(SymInstance, Clone)]
+pub struct LinearCombinationInstance {
+    a: SymScalar,
+    b: SymScalar,
+    commitment: SymPoint,
+    result: SymPoint,
+}
+
+impl SigmaProof for LinearCombinationProof {
+    const LABEL: &'static [u8] = b"linear-combination-protocol";
+    const NUM_EQUATIONS: Option<usize> = Some(2);
+
+    type WITNESS = LinearCombinationWitness;
+    type INSTANCE = LinearCombinationInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::INSTANCE {
+            a: _,
+            b,
+            commitment,
+            result,
+        } = instance.clone();
+        vec![commitment, result - b * G]
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::WITNESS {
+            value,
+            blinding,
+            result_blinding,
+        } = witness.clone();
+        vec![
+            &value * G + blinding * H.clone(),
+            (&instance.a * value) * G + result_blinding * H.clone(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::Scalar;
+
+    use super::*;
+    use crate::errors::SigmaProofError;
+
+    /// A witness and an instance for `D = a*v + b`, with `D` committing to
+    /// `result` instead if given.
+    fn statement(
+        a: Scalar,
+        b: Scalar,
+        result: Option<Scalar>,
+    ) -> (LinearCombinationWitness, LinearCombinationInstance) {
+        let rng = &mut rand::rngs::OsRng;
+        let (v, r, s) = (
+            Scalar::random(rng),
+            Scalar::random(rng),
+            Scalar::random(rng),
+        );
+        let result = result.unwrap_or(a * v + b);
+        let witness = LinearCombinationWitness {
+            value: SymScalar::Const(v),
+            blinding: SymScalar::Const(r),
+            result_blinding: SymScalar::Const(s),
+        };
+        let instance =
+            LinearCombinationInstance::new(a, b, v * G + r * H.clone(), result * G + s * H.clone());
+        (witness, instance)
+    }
+
+    #[test]
+    fn test_linear_combination_proof() {
+        let (witness, instance) = statement(Scalar::from(2u64), Scalar::from(7u64), None);
+        let proof = LinearCombinationProof::prove_checked(&witness, &instance).unwrap();
+        LinearCombinationProof::verify(&instance, &proof).unwrap();
+
+        // the coefficients are bound to the proof
+        let LinearCombinationInstance {
+            a,
+            b: _,
+            commitment,
+            result,
+        } = instance;
+        let other = LinearCombinationInstance::new(a, Scalar::from(8u64), commitment, result);
+        assert!(matches!(
+            LinearCombinationProof::verify(&other, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_linear_combination_wrong_result() {
+        let (witness, instance) =
+            statement(Scalar::from(2u64), Scalar::from(7u64), Some(Scalar::ONE));
+        assert!(matches!(
+            LinearCombinationProof::prove_checked(&witness, &instance),
+            Err(SigmaProofError::InstanceConsistency)
+        ));
+        let proof = LinearCombinationProof::prove(&witness, &instance).unwrap();
+        assert!(LinearCombinationProof::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_linear_combination_spec_names_coefficients() {
+        let spec = LinearCombinationProof::spec();
+        println!("{spec}");
+        assert!(spec.contains("(\\texttt{result} - \\texttt{b} \\cdot G)"));
+        assert!(spec.contains("(\\texttt{a} \\cdot \\texttt{value}) \\cdot G"));
+
+        let dsl = LinearCombinationProof::to_dsl().unwrap();
+        assert!(dsl.contains("(a * value)"));
+    }
+
+    conformance_tests!(LinearCombinationProof);
+}
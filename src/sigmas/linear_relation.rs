@@ -0,0 +1,249 @@
+use crate::{
+    absorb::{sealed_instance, FixedLenInstance, SymInstance, SymPoint, SymScalar},
+    compiler::SigmaProof,
+    errors::{SigmaProofError, SigmaProofResult},
+};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+
+/// A protocol for any linear relation `targets = bases · w`, where `bases` is
+/// an `M × N` matrix of points and `w` a witness of `N` scalars.
+///
+/// Schnorr, Chaum, Okamoto and other Maurer-style statements are all linear
+/// relations, so they can be expressed declaratively by building a
+/// [`LinearRelationInstance`] instead of writing `f` and `psi` by hand. Use the
+/// identity point for bases that a witness scalar does not appear in.
+pub struct LinearRelation<const N: usize, const M: usize>;
+
+pub type LinearRelationWitness<const N: usize> = [SymScalar; N];
+
+#[derive(Clone)]
+pub struct LinearRelationInstance<const N: usize, const M: usize> {
+    bases: [[SymPoint; N]; M],
+    targets: [SymPoint; M],
+}
+
+impl<const N: usize, const M: usize> LinearRelationInstance<N, M> {
+    /// Builds the relation `targets[i] = sum_j w[j] * bases[i][j]`.
+    pub fn new(bases: [[SymPoint; N]; M], targets: [SymPoint; M]) -> Self {
+        Self { bases, targets }
+    }
+}
+
+impl<const N: usize, const M: usize> sealed_instance::Sealed for LinearRelationInstance<N, M> {}
+
+impl<const N: usize, const M: usize> SymInstance for LinearRelationInstance<N, M> {
+    fn num_scalars() -> Option<usize> {
+        Some(Self::NUM_SCALARS)
+    }
+
+    fn num_points() -> Option<usize> {
+        Some(Self::NUM_POINTS)
+    }
+
+    /// Expects the bases in row-major order, followed by the targets.
+    fn from_values(scalars: &[Scalar], points: &[RistrettoPoint]) -> SigmaProofResult<Self> {
+        if !scalars.is_empty() {
+            return Err(SigmaProofError::TooManyScalars {
+                expected: 0,
+                actual: scalars.len(),
+            });
+        }
+        if points.len() < M * N + M {
+            return Err(SigmaProofError::InsufficientPoints);
+        }
+        if points.len() > M * N + M {
+            return Err(SigmaProofError::TooManyPoints {
+                expected: M * N + M,
+                actual: points.len(),
+            });
+        }
+        let (bases, targets) = points.split_at(M * N);
+        Ok(Self {
+            bases: core::array::from_fn(|i| {
+                core::array::from_fn(|j| SymPoint::Const(bases[i * N + j]))
+            }),
+            targets: core::array::from_fn(|i| SymPoint::Const(targets[i])),
+        })
+    }
+
+    fn get_field_names() -> Vec<String> {
+        let mut names = vec!["base".to_string(); M * N];
+        names.extend(vec!["target".to_string(); M]);
+        names
+    }
+
+    fn points(&self) -> Vec<SymPoint> {
+        self.bases
+            .iter()
+            .flatten()
+            .chain(&self.targets)
+            .cloned()
+            .collect()
+    }
+
+    fn scalars(&self) -> Vec<SymScalar> {
+        vec![]
+    }
+}
+
+impl<const N: usize, const M: usize> FixedLenInstance for LinearRelationInstance<N, M> {
+    const NUM_SCALARS: usize = 0;
+    const NUM_POINTS: usize = M * N + M;
+}
+
+impl<const N: usize, const M: usize> SigmaProof for LinearRelation<N, M> {
+    const LABEL: &'static [u8] = b"linear-relation-protocol";
+
+    type WITNESS = LinearRelationWitness<N>;
+    type INSTANCE = LinearRelationInstance<N, M>;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        instance.targets.to_vec()
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        instance
+            .bases
+            .iter()
+            .map(|row| {
+                witness
+                    .iter()
+                    .zip(row)
+                    .map(|(w, base)| w * base.clone())
+                    .reduce(|acc, term| acc + term)
+                    .unwrap_or(SymPoint::Identity)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        absorb::SymWitness,
+        sigmas::{
+            chaum::{Chaum, ChaumInstance, ChaumWitness},
+            G, H,
+        },
+    };
+
+    fn chaum_relation(point1: SymPoint, point2: SymPoint) -> LinearRelationInstance<1, 2> {
+        LinearRelationInstance::new([[G], [H.clone()]], [point1, point2])
+    }
+
+    #[test]
+    fn test_linear_relation_chaum() {
+        let rng = &mut rand::rngs::OsRng;
+        let x = Scalar::random(rng);
+
+        let (point1, point2) = (x * G, x * H.clone());
+        let chaum_instance = ChaumInstance::from_values(
+            &[],
+            &[point1.evaluate().unwrap(), point2.evaluate().unwrap()],
+        )
+        .unwrap();
+        let chaum_witness = ChaumWitness::from_values(&[x]).unwrap();
+        let instance = chaum_relation(point1, point2);
+        let witness = [SymScalar::Const(x)];
+
+        let chaum_proof = Chaum::prove(&chaum_witness, &chaum_instance).unwrap();
+        let proof = LinearRelation::prove(&witness, &instance).unwrap();
+        assert_eq!(proof.len(), chaum_proof.len());
+
+        Chaum::verify(&chaum_instance, &chaum_proof).unwrap();
+        LinearRelation::verify(&instance, &proof).unwrap();
+
+        // both reject a witness with mismatched logs
+        let bad_instance = chaum_relation(x * G, Scalar::random(rng) * H.clone());
+        let bad_proof = LinearRelation::prove(&witness, &bad_instance).unwrap();
+        assert!(LinearRelation::verify(&bad_instance, &bad_proof).is_err());
+    }
+
+    #[test]
+    fn test_linear_relation_okamoto() {
+        let rng = &mut rand::rngs::OsRng;
+        let (x, y) = (Scalar::random(rng), Scalar::random(rng));
+
+        let instance = LinearRelationInstance::new([[G, H.clone()]], [(x * G) + (y * H.clone())]);
+        let witness = [SymScalar::Const(x), SymScalar::Const(y)];
+
+        let proof = LinearRelation::prove(&witness, &instance).unwrap();
+        LinearRelation::verify(&instance, &proof).unwrap();
+
+        let wrong = [SymScalar::Const(y), SymScalar::Const(x)];
+        let proof = LinearRelation::prove(&wrong, &instance).unwrap();
+        assert!(LinearRelation::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_linear_relation_one_broken_equation() {
+        const M: usize = 4;
+        let rng = &mut rand::rngs::OsRng;
+        let x = Scalar::random(rng);
+        let witness = [SymScalar::Const(x)];
+        let bases: [RistrettoPoint; M] = core::array::from_fn(|_| RistrettoPoint::random(rng));
+
+        // whichever equation is broken, all of them are checked and the same
+        // error is returned
+        for broken in 0..M {
+            let mut instance = LinearRelationInstance::<1, M>::new(
+                bases.map(|b| [SymPoint::Const(b)]),
+                bases.map(|b| SymPoint::Const(x * b)),
+            );
+            instance.targets[broken] = SymPoint::Const(RistrettoPoint::random(rng));
+
+            let proof = LinearRelation::prove(&witness, &instance).unwrap();
+            assert!(matches!(
+                LinearRelation::verify(&instance, &proof),
+                Err(SigmaProofError::EquationCheckFailed)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_linear_relation_verify_streaming() {
+        const M: usize = 200;
+        let rng = &mut rand::rngs::OsRng;
+        let x = Scalar::random(rng);
+
+        let bases: [RistrettoPoint; M] = core::array::from_fn(|_| RistrettoPoint::random(rng));
+        let mut instance = LinearRelationInstance::<1, M>::new(
+            bases.map(|b| [SymPoint::Const(b)]),
+            bases.map(|b| SymPoint::Const(x * b)),
+        );
+        let witness = [SymScalar::Const(x)];
+
+        let proof = LinearRelation::prove(&witness, &instance).unwrap();
+        LinearRelation::verify_streaming(&instance, &proof).unwrap();
+
+        // a single wrong equation is caught
+        instance.targets[M - 1] = SymPoint::Const(bases[M - 1]);
+        let proof = LinearRelation::prove(&witness, &instance).unwrap();
+        assert!(matches!(
+            LinearRelation::verify_streaming(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+        assert!(matches!(
+            LinearRelation::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_linear_relation_spec_generation() {
+        let spec = LinearRelation::<2, 2>::spec();
+        println!("{spec}");
+        assert!(spec.contains("\\texttt{w\\_0}, \\texttt{w\\_1}"));
+
+        let dsl = LinearRelation::<2, 2>::to_dsl().unwrap();
+        assert!(dsl.contains("witness w_0, w_1;"));
+    }
+
+    conformance_tests!(LinearRelation<2, 2>);
+}
@@ -1,16 +1,78 @@
-use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, RistrettoPoint};
+use curve25519_dalek::{
+    constants::{RISTRETTO_BASEPOINT_POINT, RISTRETTO_BASEPOINT_TABLE},
+    ristretto::RistrettoBasepointTable,
+    traits::Identity,
+    RistrettoPoint, Scalar,
+};
+#[cfg(not(feature = "std"))]
+use spin::Lazy;
+#[cfg(feature = "std")]
+use std::sync::LazyLock as Lazy;
 
-use crate::absorb::SymPoint;
+use crate::{
+    absorb::SymPoint,
+    errors::{SigmaProofError, SigmaProofResult},
+};
+use alloc::vec::Vec;
+use sha2::Sha512;
 
+/// Generates the tests every protocol is expected to pass, to be invoked in
+/// the protocol's test module. Modules testing several protocols must name
+/// each invocation, e.g. `conformance_tests!(generic: GenericSchnorr)`.
+#[cfg(test)]
+macro_rules! conformance_tests {
+    ($name:ident : $protocol:ty) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn test_degenerate_instances() {
+                use crate::compiler::DegeneracyKind;
+                use crate::errors::SigmaProofError;
+
+                let rng = &mut rand::rngs::OsRng;
+                for kind in DegeneracyKind::ALL {
+                    let Some(instance) = <$protocol>::random_degenerate_instance(rng, kind)
+                    else {
+                        continue;
+                    };
+                    assert!(matches!(
+                        <$protocol>::validate_instance(&instance),
+                        Err(SigmaProofError::DegenerateInstance(k)) if k == kind
+                    ));
+                }
+            }
+        }
+    };
+    ($protocol:ty) => {
+        conformance_tests!(conformance: $protocol);
+    };
+}
+
+pub mod bit;
 pub mod chaum;
+pub mod commit_equality;
+pub mod commit_inequality;
+pub mod commitment_sum;
+pub mod dleq;
+pub mod elgamal;
+pub mod generalized_okamoto;
+pub mod is_zero;
+pub mod known_value;
+pub mod linear_combination;
+pub mod linear_relation;
 pub mod okamoto;
+pub mod one_of_many;
+pub mod randomized_schnorr;
+pub mod range;
 pub mod schnorr;
+pub mod vector_commitment;
 pub mod zero;
 
 pub const G: SymPoint = SymPoint::WellKnownConst("G", RISTRETTO_BASEPOINT_POINT);
 
 // TODO: replace with a more acceptable point?
-pub static H: std::sync::LazyLock<SymPoint> = std::sync::LazyLock::new(|| {
+pub static H: Lazy<SymPoint> = Lazy::new(|| {
     SymPoint::WellKnownConst(
         "H",
         RistrettoPoint::from_uniform_bytes(&[
@@ -20,3 +82,143 @@ pub static H: std::sync::LazyLock<SymPoint> = std::sync::LazyLock::new(|| {
         ]),
     )
 });
+
+/// The precomputed multiples of [`H`], built on first use.
+static H_TABLE: Lazy<RistrettoBasepointTable> =
+    Lazy::new(|| RistrettoBasepointTable::create(&H.evaluate().expect("H is a constant")));
+
+/// Computes `scalar * point` with a precomputed table if `point` is the
+/// generator [`G`] or [`H`] named `name`, and returns `None` otherwise.
+///
+/// The tables are multiplied in constant time, as the prover scales the
+/// generators by its secret nonces.
+pub(crate) fn mul_generator(
+    name: &str,
+    point: &RistrettoPoint,
+    scalar: &Scalar,
+) -> Option<RistrettoPoint> {
+    match name {
+        "G" if *point == RISTRETTO_BASEPOINT_POINT => Some(RISTRETTO_BASEPOINT_TABLE * scalar),
+        "H" if *point == H.evaluate().ok()? => Some(&*H_TABLE * scalar),
+        _ => None,
+    }
+}
+
+/// Derives `n` generators from `label` by hashing `label || i` to the curve,
+/// with `i` as a little-endian `u64`.
+///
+/// Nobody knows the discrete log of the generators with respect to each other
+/// or to [`G`] and [`H`], so they can serve as independent bases, e.g. for
+/// vector commitments. Distinct labels give unrelated generators.
+pub fn derive_generators(label: &[u8], n: usize) -> Vec<RistrettoPoint> {
+    (0..n as u64)
+        .map(|i| RistrettoPoint::hash_from_bytes::<Sha512>(&[label, &i.to_le_bytes()].concat()))
+        .collect()
+}
+
+/// Rejects generators that are trivially dependent: the identity, or two equal
+/// generators.
+///
+/// Protocols such as [`okamoto`] or [`dleq`] are only sound if their bases are
+/// independent. This cannot be checked in general (a base could be a known
+/// multiple of another), so this only catches the obvious mistakes.
+pub fn check_independent_generators(points: &[RistrettoPoint]) -> SigmaProofResult<()> {
+    for (i, point) in points.iter().enumerate() {
+        if *point == RistrettoPoint::identity() || points[i + 1..].contains(point) {
+            return Err(SigmaProofError::GeneratorCollision);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_independent_generators() {
+        let rng = &mut rand::rngs::OsRng;
+        let g = G.evaluate().unwrap();
+        let h = H.evaluate().unwrap();
+
+        check_independent_generators(&[g, h, RistrettoPoint::random(rng)]).unwrap();
+        check_independent_generators(&[]).unwrap();
+
+        assert!(matches!(
+            check_independent_generators(&[g, h, g]),
+            Err(SigmaProofError::GeneratorCollision)
+        ));
+        assert!(matches!(
+            check_independent_generators(&[g, RistrettoPoint::identity()]),
+            Err(SigmaProofError::GeneratorCollision)
+        ));
+    }
+
+    #[test]
+    fn test_derive_generators() {
+        let generators = derive_generators(b"test-generators", 4);
+        assert_eq!(generators.len(), 4);
+        assert_eq!(generators, derive_generators(b"test-generators", 4));
+        assert_eq!(generators[..2], derive_generators(b"test-generators", 2));
+        assert_ne!(generators[0], derive_generators(b"other-generators", 1)[0]);
+
+        let mut all = generators.clone();
+        all.extend([G.evaluate().unwrap(), H.evaluate().unwrap()]);
+        check_independent_generators(&all).unwrap();
+    }
+
+    #[test]
+    fn test_mul_generator() {
+        let rng = &mut rand::rngs::OsRng;
+        let scalars: Vec<Scalar> = (0..64).map(|_| Scalar::random(rng)).collect();
+        let g = G.evaluate().unwrap();
+        let h = H.evaluate().unwrap();
+
+        for (name, generator) in [("G", &G), ("H", &*H)] {
+            let point = generator.evaluate().unwrap();
+            for s in &scalars {
+                let naive = s * point;
+                assert_eq!(mul_generator(name, &point, s), Some(naive));
+                assert_eq!((s * generator).evaluate().unwrap(), naive);
+            }
+        }
+
+        // only the generators themselves are recognized
+        let s = &scalars[0];
+        assert_eq!(mul_generator("G", &h, s), None);
+        assert_eq!(mul_generator("H", &g, s), None);
+        let other = RistrettoPoint::random(rng);
+        assert_eq!(mul_generator("X", &other, s), None);
+        let impostor = SymPoint::WellKnownConst("G", other);
+        assert_eq!((s * impostor).evaluate().unwrap(), s * other);
+    }
+
+    /// Compares the precomputed tables with the naive scalar multiplication,
+    /// run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_mul_generator() {
+        use std::time::Instant;
+
+        let rng = &mut rand::rngs::OsRng;
+        let scalars: Vec<Scalar> = (0..1000).map(|_| Scalar::random(rng)).collect();
+        for (name, generator) in [("G", &G), ("H", &*H)] {
+            let point = generator.evaluate().unwrap();
+            let _ = mul_generator(name, &point, &scalars[0]);
+
+            let start = Instant::now();
+            let naive: Vec<_> = scalars.iter().map(|s| s * point).collect();
+            let naive_time = start.elapsed();
+
+            let start = Instant::now();
+            let table: Vec<_> = scalars
+                .iter()
+                .map(|s| mul_generator(name, &point, s).unwrap())
+                .collect();
+            let table_time = start.elapsed();
+
+            assert_eq!(naive, table);
+            println!("{name}: naive {naive_time:?}, precomputed {table_time:?}");
+        }
+    }
+}
@@ -3,6 +3,7 @@ use crate::{
     compiler::SigmaProof,
     sigmas::{G, H},
 };
+use alloc::{vec, vec::Vec};
 
 pub struct Okamoto;
 
@@ -36,7 +37,8 @@ impl SigmaProof for Okamoto {
 
 #[cfg(test)]
 mod tests {
-    use curve25519_dalek::Scalar;
+    use curve25519_dalek::{RistrettoPoint, Scalar};
+    use sha2::Sha512;
 
     use super::*;
 
@@ -60,9 +62,97 @@ mod tests {
         Okamoto::verify(&instance, &proof).unwrap();
     }
 
+    #[test]
+    fn test_okamoto_randomness_bytes() {
+        let instance = OkamotoInstance {
+            point: RistrettoPoint::random(&mut rand::rngs::OsRng).into(),
+        };
+        assert_eq!(Okamoto::randomness_bytes(&instance).unwrap(), 128);
+    }
+
+    #[test]
+    fn test_okamoto_generators() {
+        let witness = OkamotoWitness {
+            x: SymScalar::Var(None),
+            y: SymScalar::Var(None),
+        };
+        let instance = OkamotoInstance {
+            point: SymPoint::Var(None),
+        };
+        let psi = Okamoto::psi(&witness, &instance);
+        let generators = psi[0].collect_generators();
+
+        let expected = [
+            (Some("G"), G.evaluate().unwrap()),
+            (Some("H"), H.evaluate().unwrap()),
+        ];
+        assert_eq!(generators, expected);
+        assert!(Okamoto::spec().contains("The generators used by $\\psi$ are $G, H$."));
+    }
+
+    #[test]
+    fn test_okamoto_public_parameters() {
+        let parameters: Vec<_> = Okamoto::public_parameters()
+            .unwrap()
+            .into_iter()
+            .map(|(name, point)| (name, point.compress().to_bytes()))
+            .collect();
+
+        // the compressed Ristretto basepoint, and H
+        let g = [
+            0xe2, 0xf2, 0xae, 0x0a, 0x6a, 0xbc, 0x4e, 0x71, 0xa8, 0x84, 0xa9, 0x61, 0xc5, 0x00,
+            0x51, 0x5f, 0x58, 0xe3, 0x0b, 0x6a, 0xa5, 0x82, 0xdd, 0x8d, 0xb6, 0xa6, 0x59, 0x45,
+            0xe0, 0x8d, 0x2d, 0x76,
+        ];
+        let h = [
+            0x2e, 0x7c, 0x49, 0x64, 0xf9, 0x1f, 0x5f, 0x2b, 0x07, 0x4a, 0x9b, 0xc1, 0x47, 0xef,
+            0x97, 0x3c, 0x08, 0xdb, 0xe2, 0x96, 0x83, 0x74, 0x6f, 0x97, 0x9f, 0x11, 0x35, 0x80,
+            0x65, 0xa2, 0xd1, 0x55,
+        ];
+        assert_eq!(parameters, [("G", g), ("H", h)]);
+    }
+
+    #[test]
+    fn test_okamoto_spec_names_generators() {
+        let spec = Okamoto::spec();
+        assert!(spec.contains("(\\texttt{x} \\cdot G + \\texttt{y} \\cdot H)"));
+        // well-known constants are not rendered as anonymous points
+        assert!(!spec.contains('P'));
+    }
+
+    /// Okamoto with its generators as anonymous constants.
+    struct AnonymousOkamoto;
+
+    impl SigmaProof for AnonymousOkamoto {
+        const LABEL: &'static [u8] = b"anonymous-okamoto-protocol";
+
+        type WITNESS = OkamotoWitness;
+        type INSTANCE = OkamotoInstance;
+
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            Okamoto::f(instance)
+        }
+
+        fn psi(witness: &Self::WITNESS, _instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            let Self::WITNESS { x, y } = witness.clone();
+            let base =
+                |label: &[u8]| SymPoint::Const(RistrettoPoint::hash_from_bytes::<Sha512>(label));
+            vec![x * base(b"first") + y * base(b"second")]
+        }
+    }
+
+    #[test]
+    fn test_okamoto_spec_numbers_anonymous_points() {
+        let spec = AnonymousOkamoto::spec();
+        assert!(spec.contains("(\\texttt{x} \\cdot P_{1} + \\texttt{y} \\cdot P_{2})"));
+        assert!(spec.contains("The generators used by $\\psi$ are $P_{1}, P_{2}$."));
+    }
+
     #[test]
     fn test_okamoto_spec_generation() {
         let spec = Okamoto::spec();
         println!("{spec}");
     }
+
+    conformance_tests!(Okamoto);
 }
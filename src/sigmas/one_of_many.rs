@@ -0,0 +1,322 @@
+use crate::{
+    absorb::{
+        sealed_instance, sealed_witness, FixedLenWitness, SymInstance, SymPoint, SymScalar,
+        SymWitness,
+    },
+    compiler::SigmaProof,
+    errors::{SigmaProofError, SigmaProofResult},
+    sigmas::G,
+};
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use rand_core::CryptoRngCore;
+
+/// Proves knowledge of the discrete log of one of the keys `P_1, ..., P_n` of
+/// a ring, without revealing which, as in a ring signature.
+///
+/// This generalizes [`crate::combinators::SigmaOr`] to `n` branches, written
+/// as a single linear relation. The witness is `(c_1, ..., c_n, x_1, ...,
+/// x_n)` and the equations are
+///
+/// ```text
+/// x_i*G - c_i*P_i = 0      for every i
+/// (c_1 + ... + c_n)*G = G
+/// ```
+///
+/// A prover knowing the key `sk` of `P_j` uses `c_j = 1` and `x_j = sk`, and
+/// zero for every other branch. In the proof, the response for `c_i` is the
+/// share of the challenge answered by branch `i`, and the other branches are
+/// simulated. Conversely, the `c_i` of any solution sum to 1, so some `c_i` is
+/// nonzero and `x_i / c_i` is the discrete log of `P_i`.
+///
+/// A proof holds `n + 1` commitments and `2n` responses.
+pub struct OneOfMany;
+
+/// The witness of [`OneOfMany`]: the challenge shares, then the keys.
+///
+/// Its length depends on the ring, so it is no [`FixedLenWitness`].
+#[derive(Clone)]
+pub struct OneOfManyWitness {
+    shares: Vec<SymScalar>,
+    keys: Vec<SymScalar>,
+}
+
+impl OneOfManyWitness {
+    /// The witness of a prover knowing the secret key `sk` of the key at
+    /// `index` in a ring of `len` keys.
+    pub fn new(sk: Scalar, index: usize, len: usize) -> SigmaProofResult<Self> {
+        if index >= len {
+            return Err(SigmaProofError::BranchIndexOutOfRange { index, len });
+        }
+        let branch = |value: Scalar| {
+            (0..len)
+                .map(|i| SymScalar::Const(if i == index { value } else { Scalar::ZERO }))
+                .collect()
+        };
+        Ok(Self {
+            shares: branch(Scalar::ONE),
+            keys: branch(sk),
+        })
+    }
+}
+
+impl sealed_witness::Sealed for OneOfManyWitness {}
+
+impl SymWitness for OneOfManyWitness {
+    fn values(&self) -> SigmaProofResult<Vec<Scalar>> {
+        self.shares
+            .iter()
+            .chain(&self.keys)
+            .map(SymScalar::evaluate)
+            .collect()
+    }
+
+    /// Expects the `n` challenge shares, followed by the `n` keys.
+    fn from_values(scalars: &[Scalar]) -> SigmaProofResult<Self> {
+        if scalars.len() % 2 != 0 {
+            return Err(SigmaProofError::InsufficientScalars);
+        }
+        let (shares, keys) = scalars.split_at(scalars.len() / 2);
+        let vars = |values: &[Scalar]| values.iter().map(|s| SymScalar::Var(Some(*s))).collect();
+        Ok(Self {
+            shares: vars(shares),
+            keys: vars(keys),
+        })
+    }
+
+    fn num_scalars() -> Option<usize> {
+        None
+    }
+
+    fn get_var_name(_index: usize) -> Cow<'static, str> {
+        "w".into()
+    }
+
+    #[cfg(feature = "zeroize")]
+    fn zeroize(&mut self) {
+        for scalar in self.shares.iter_mut().chain(&mut self.keys) {
+            SymWitness::zeroize(scalar);
+        }
+    }
+
+    fn scalar_len(&self) -> usize {
+        self.shares.len() + self.keys.len()
+    }
+
+    fn rand_like<R: CryptoRngCore + ?Sized>(&self, rng: &mut R) -> Self {
+        let mut rand = |len: usize| (0..len).map(|_| SymScalar::rand(rng)).collect();
+        Self {
+            shares: rand(self.shares.len()),
+            keys: rand(self.keys.len()),
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for OneOfManyWitness {
+    fn zeroize(&mut self) {
+        SymWitness::zeroize(self);
+    }
+}
+
+/// The ring of public keys of [`OneOfMany`].
+///
+/// Its size is only known at runtime, so it has no
+/// [`SymInstance::num_points`], and [`SymInstance::from_values`] accepts
+/// rings of any nonzero size.
+#[derive(Clone)]
+pub struct RingInstance {
+    keys: Vec<SymPoint>,
+}
+
+impl RingInstance {
+    pub fn new(keys: Vec<SymPoint>) -> Self {
+        Self { keys }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl sealed_instance::Sealed for RingInstance {}
+
+impl SymInstance for RingInstance {
+    fn num_scalars() -> Option<usize> {
+        Some(0)
+    }
+
+    fn num_points() -> Option<usize> {
+        None
+    }
+
+    fn from_values(scalars: &[Scalar], points: &[RistrettoPoint]) -> SigmaProofResult<Self> {
+        if !scalars.is_empty() {
+            return Err(SigmaProofError::TooManyScalars {
+                expected: 0,
+                actual: scalars.len(),
+            });
+        }
+        if points.is_empty() {
+            return Err(SigmaProofError::InsufficientPoints);
+        }
+        Ok(Self {
+            keys: points.iter().map(|p| SymPoint::Const(*p)).collect(),
+        })
+    }
+
+    fn get_field_names() -> Vec<String> {
+        vec!["key".to_string()]
+    }
+
+    fn points(&self) -> Vec<SymPoint> {
+        self.keys.clone()
+    }
+
+    fn scalars(&self) -> Vec<SymScalar> {
+        vec![]
+    }
+}
+
+impl SigmaProof for OneOfMany {
+    const LABEL: &'static [u8] = b"one-of-many-protocol";
+
+    type WITNESS = OneOfManyWitness;
+    type INSTANCE = RingInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let mut f = vec![SymPoint::Identity; instance.keys.len()];
+        f.push(G);
+        f
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let mut psi: Vec<SymPoint> = witness
+            .keys
+            .iter()
+            .zip(&witness.shares)
+            .zip(&instance.keys)
+            .map(|((x_i, c_i), key)| x_i * G - c_i * key.clone())
+            .collect();
+        let shares = witness
+            .shares
+            .iter()
+            .cloned()
+            .reduce(|acc, c_i| acc + c_i)
+            .unwrap_or(SymScalar::Const(Scalar::ZERO));
+        psi.push(shares * G);
+        psi
+    }
+
+    fn num_responses(instance: &Self::INSTANCE) -> SigmaProofResult<usize> {
+        Ok(2 * instance.keys.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A ring of `len` random keys, with the secret keys.
+    fn ring(len: usize) -> (Vec<Scalar>, RingInstance) {
+        let rng = &mut rand::rngs::OsRng;
+        let sks: Vec<Scalar> = (0..len).map(|_| Scalar::random(rng)).collect();
+        let keys = sks.iter().map(|sk| sk * G).collect();
+        (sks, RingInstance::new(keys))
+    }
+
+    #[test]
+    fn test_one_of_many_members() {
+        for len in [3, 5] {
+            let (sks, instance) = ring(len);
+            for (index, sk) in sks.iter().enumerate() {
+                let witness = OneOfManyWitness::new(*sk, index, len).unwrap();
+                let proof = OneOfMany::prove_checked(&witness, &instance).unwrap();
+                assert_eq!(proof.len(), 32 * ((len + 1) + 2 * len));
+                OneOfMany::verify(&instance, &proof).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_one_of_many_non_member() {
+        let rng = &mut rand::rngs::OsRng;
+        for len in [3, 5] {
+            let (sks, instance) = ring(len);
+            let outsider = Scalar::random(rng);
+            for index in [0, len - 1] {
+                let witness = OneOfManyWitness::new(outsider, index, len).unwrap();
+                assert!(matches!(
+                    OneOfMany::prove_checked(&witness, &instance),
+                    Err(SigmaProofError::InstanceConsistency)
+                ));
+                let proof = OneOfMany::prove(&witness, &instance).unwrap();
+                assert!(matches!(
+                    OneOfMany::verify(&instance, &proof),
+                    Err(SigmaProofError::EquationCheckFailed)
+                ));
+            }
+
+            // the key of one member does not open another's branch
+            let witness = OneOfManyWitness::new(sks[0], 1, len).unwrap();
+            let proof = OneOfMany::prove(&witness, &instance).unwrap();
+            assert!(OneOfMany::verify(&instance, &proof).is_err());
+        }
+    }
+
+    #[test]
+    fn test_one_of_many_ring_mismatch() {
+        let (sks, instance) = ring(3);
+        let witness = OneOfManyWitness::new(sks[2], 2, 3).unwrap();
+        let proof = OneOfMany::prove(&witness, &instance).unwrap();
+
+        // the proof is bound to the ring, including its size
+        let (_, other) = ring(3);
+        assert!(OneOfMany::verify(&other, &proof).is_err());
+        let mut larger = instance.keys.clone();
+        larger.push(G);
+        assert!(OneOfMany::verify(&RingInstance::new(larger), &proof).is_err());
+
+        assert!(matches!(
+            OneOfManyWitness::new(sks[0], 3, 3),
+            Err(SigmaProofError::BranchIndexOutOfRange { index: 3, len: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_one_of_many_runtime_length() {
+        assert_eq!(OneOfManyWitness::num_scalars(), None);
+        assert_eq!(RingInstance::num_points(), None);
+
+        // the number of responses and the encoding follow the ring
+        for len in [1, 4] {
+            let (_, instance) = ring(len);
+            assert_eq!(OneOfMany::num_responses(&instance).unwrap(), 2 * len);
+            assert_eq!(
+                OneOfMany::randomness_bytes(&instance).unwrap(),
+                2 * len * 64
+            );
+            let bytes = crate::proof::encode_instance(&instance).unwrap();
+            let decoded: RingInstance = crate::proof::decode_instance(&bytes).unwrap();
+            assert_eq!(decoded.len(), len);
+        }
+
+        // the spec samples a ring of RUNTIME_SAMPLE_LEN keys
+        let statement = OneOfMany::statement().unwrap();
+        assert_eq!(
+            statement.witness.len(),
+            2 * crate::absorb::RUNTIME_SAMPLE_LEN
+        );
+    }
+
+    conformance_tests!(OneOfMany);
+}
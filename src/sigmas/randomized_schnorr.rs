@@ -0,0 +1,140 @@
+use crate::{
+    absorb::{SymInstance, SymPoint, SymScalar, SymWitness},
+    errors::SigmaProofResult,
+    multi_round::MultiRoundSigma,
+    sigmas::G,
+};
+use alloc::{vec, vec::Vec};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+
+/// A 5-move proof of knowledge of `x` such that `pubkey = x*G`.
+///
+/// The prover first sends `T = t*G` for a random `t` and receives a challenge
+/// `c`, then proves knowledge of `y = x + c*t` such that `pubkey + c*T = y*G`
+/// with a Schnorr proof. Extracting `x` takes two final rounds for each of two
+/// challenges `c`, i.e. the protocol is (2, 2)-special sound.
+pub struct RandomizedSchnorr;
+
+#[derive(SymWitness, Clone)]
+pub struct RandomizedSchnorrWitness {
+    x: SymScalar,
+}
+
+#[derive(SymInstance, Clone)]
+pub struct RandomizedSchnorrInstance {
+    pubkey: SymPoint,
+}
+
+impl MultiRoundSigma for RandomizedSchnorr {
+    const LABEL: &'static [u8] = b"randomized-schnorr-protocol";
+    const NUM_PHASES: usize = 1;
+
+    type WITNESS = RandomizedSchnorrWitness;
+    type INSTANCE = RandomizedSchnorrInstance;
+    type RANDOMNESS = [SymScalar; 1];
+    type RESPONSE = [SymScalar; 1];
+
+    fn num_messages(_phase: usize) -> usize {
+        1
+    }
+
+    fn commit(
+        _phase: usize,
+        _witness: &Self::WITNESS,
+        randomness: &Self::RANDOMNESS,
+        _instance: &Self::INSTANCE,
+        _challenges: &[Scalar],
+    ) -> Vec<SymPoint> {
+        let [t] = randomness.clone();
+        vec![t * G]
+    }
+
+    fn response_witness(
+        witness: &Self::WITNESS,
+        randomness: &Self::RANDOMNESS,
+        challenges: &[Scalar],
+    ) -> SigmaProofResult<Self::RESPONSE> {
+        let Self::WITNESS { x } = witness.clone();
+        let [t] = randomness.clone();
+        Ok([x + challenges[0] * t])
+    }
+
+    fn f(
+        instance: &Self::INSTANCE,
+        messages: &[RistrettoPoint],
+        challenges: &[Scalar],
+    ) -> Vec<SymPoint> {
+        let Self::INSTANCE { pubkey } = instance.clone();
+        vec![pubkey + challenges[0] * SymPoint::Const(messages[0])]
+    }
+
+    fn psi(
+        response: &Self::RESPONSE,
+        _instance: &Self::INSTANCE,
+        _messages: &[RistrettoPoint],
+        _challenges: &[Scalar],
+    ) -> Vec<SymPoint> {
+        let [y] = response.clone();
+        vec![y * G]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::SigmaProofError;
+
+    #[test]
+    fn test_randomized_schnorr_protocol() {
+        let rng = &mut rand::rngs::OsRng;
+        let x = Scalar::random(rng);
+        let witness = RandomizedSchnorrWitness::from_values(&[x]).unwrap();
+        let instance = RandomizedSchnorrInstance::new(x * G);
+
+        let proof = RandomizedSchnorr::prove(&witness, &instance).unwrap();
+        // T, A and z
+        assert_eq!(proof.len(), 32 * 3);
+        RandomizedSchnorr::verify(&instance, &proof).unwrap();
+
+        // each proof uses a fresh T
+        let other = RandomizedSchnorr::prove(&witness, &instance).unwrap();
+        assert_ne!(proof[..32], other[..32]);
+    }
+
+    #[test]
+    fn test_randomized_schnorr_wrong_witness() {
+        let rng = &mut rand::rngs::OsRng;
+        let x = Scalar::random(rng);
+        let witness = RandomizedSchnorrWitness::from_values(&[x + Scalar::ONE]).unwrap();
+        let instance = RandomizedSchnorrInstance::new(x * G);
+
+        let proof = RandomizedSchnorr::prove(&witness, &instance).unwrap();
+        assert!(matches!(
+            RandomizedSchnorr::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_randomized_schnorr_tampered_proof() {
+        let rng = &mut rand::rngs::OsRng;
+        let x = Scalar::random(rng);
+        let witness = RandomizedSchnorrWitness::from_values(&[x]).unwrap();
+        let instance = RandomizedSchnorrInstance::new(x * G);
+        let proof = RandomizedSchnorr::prove(&witness, &instance).unwrap();
+
+        // replacing T changes both challenges
+        let mut tampered = proof.clone();
+        tampered[..32].copy_from_slice((x * G).evaluate().unwrap().compress().as_bytes());
+        assert!(RandomizedSchnorr::verify(&instance, &tampered).is_err());
+
+        assert!(matches!(
+            RandomizedSchnorr::verify(&instance, &proof[..16]),
+            Err(SigmaProofError::MissingCommitments)
+        ));
+        assert!(matches!(
+            RandomizedSchnorr::verify(&instance, &proof[..64]),
+            Err(SigmaProofError::MissingResponses)
+        ));
+    }
+}
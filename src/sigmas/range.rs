@@ -0,0 +1,384 @@
+use crate::{
+    absorb::{
+        sealed_instance, sealed_witness, FixedLenInstance, FixedLenWitness, SymInstance, SymPoint,
+        SymScalar, SymWitness,
+    },
+    compiler::SigmaProof,
+    errors::{SigmaProofError, SigmaProofResult},
+    sigmas::{
+        bit::{BitInstance, BitProof, BitWitness},
+        G, H,
+    },
+};
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use rand_core::CryptoRngCore;
+
+/// Proves that a Pedersen commitment `C = v*G + r*H` opens to a value
+/// `v ∈ [0, 2^N)`, by committing to the bits `C_i = b_i*G + r_i*H` of `v`.
+///
+/// The prover shows knowledge of `v`, `r` and of the openings of the `C_i`
+/// such that
+///
+/// ```text
+/// v*G + r*H = C
+/// each C_i opens to a bit b_i               (a BitProof per bit)
+/// (v - sum_i 2^i * b_i)*G = 0
+/// ```
+///
+/// The last equation links the bits to the value: the `b_i` are the choices
+/// of the [`BitProof`] witnesses, which are bound to the openings of the
+/// `C_i`. The bit commitments are part of the instance, so a proof holds
+/// `2N + 2` commitments and `3N + 2` responses, i.e. `32 * (5N + 4)` bytes,
+/// growing linearly with `N`: 1408 bytes for `N = 8`, 10368 for `N = 64`.
+pub struct RangeProof<const N: usize>;
+
+impl<const N: usize> RangeProof<N> {
+    /// Commits to `value` with the blinding `blinding`, and to its `N` lowest
+    /// bits with `bit_blindings`.
+    ///
+    /// A `value` of `2^N` or more is committed as is, but its bits are those of
+    /// `value mod 2^N`, so no proof for the statement verifies.
+    pub fn commit(
+        value: u64,
+        blinding: Scalar,
+        bit_blindings: [Scalar; N],
+    ) -> SigmaProofResult<(RangeWitness<N>, RangeInstance<N>)> {
+        const { assert!(N <= 64, "RangeProof commits to the bits of a u64") };
+        let h = H.evaluate()?;
+        let bits: [bool; N] = core::array::from_fn(|i| (value >> i) & 1 == 1);
+
+        let mut witness_bits = Vec::with_capacity(N);
+        let mut bit_commitments = Vec::with_capacity(N);
+        for (&b, &r) in bits.iter().zip(&bit_blindings) {
+            witness_bits.push(BitWitness::new(b, r)?);
+            bit_commitments.push(SymPoint::Const(
+                Scalar::from(b as u64) * G.evaluate()? + r * h,
+            ));
+        }
+
+        let witness = RangeWitness {
+            value: SymScalar::Const(Scalar::from(value)),
+            blinding: SymScalar::Const(blinding),
+            bits: witness_bits
+                .try_into()
+                .unwrap_or_else(|_| unreachable!("one witness per bit")),
+        };
+        let instance = RangeInstance {
+            commitment: SymPoint::Const(Scalar::from(value) * G.evaluate()? + blinding * h),
+            bits: bit_commitments
+                .try_into()
+                .unwrap_or_else(|_| unreachable!("one commitment per bit")),
+        };
+        Ok((witness, instance))
+    }
+}
+
+/// The opening `(v, r)` of the value commitment, then the openings of the
+/// bit commitments.
+#[derive(Clone)]
+pub struct RangeWitness<const N: usize> {
+    value: SymScalar,
+    blinding: SymScalar,
+    bits: [BitWitness; N],
+}
+
+impl<const N: usize> sealed_witness::Sealed for RangeWitness<N> {}
+
+impl<const N: usize> SymWitness for RangeWitness<N> {
+    fn values(&self) -> SigmaProofResult<Vec<Scalar>> {
+        let mut values = self.value.values()?;
+        values.extend(self.blinding.values()?);
+        for bit in &self.bits {
+            values.extend(bit.values()?);
+        }
+        Ok(values)
+    }
+
+    /// Expects the value and the blinding, followed by the scalars of each
+    /// bit witness.
+    fn from_values(scalars: &[Scalar]) -> SigmaProofResult<Self> {
+        if scalars.len() < Self::NUM_SCALARS {
+            return Err(SigmaProofError::InsufficientScalars);
+        }
+        if scalars.len() > Self::NUM_SCALARS {
+            return Err(SigmaProofError::TooManyScalars {
+                expected: Self::NUM_SCALARS,
+                actual: scalars.len(),
+            });
+        }
+        let mut bits = Vec::with_capacity(N);
+        for chunk in scalars[2..].chunks_exact(BitWitness::NUM_SCALARS) {
+            bits.push(BitWitness::from_values(chunk)?);
+        }
+        Ok(Self {
+            value: SymWitness::from_values(&scalars[..1])?,
+            blinding: SymWitness::from_values(&scalars[1..2])?,
+            bits: bits
+                .try_into()
+                .unwrap_or_else(|_| unreachable!("the length was checked")),
+        })
+    }
+
+    fn num_scalars() -> Option<usize> {
+        Some(Self::NUM_SCALARS)
+    }
+
+    fn get_var_name(index: usize) -> Cow<'static, str> {
+        match index {
+            0 => "value".into(),
+            1 => "blinding".into(),
+            i if i < Self::NUM_SCALARS => {
+                BitWitness::get_var_name((i - 2) % BitWitness::NUM_SCALARS)
+            }
+            _ => "unknown".into(),
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    fn zeroize(&mut self) {
+        SymWitness::zeroize(&mut self.value);
+        SymWitness::zeroize(&mut self.blinding);
+        for bit in &mut self.bits {
+            SymWitness::zeroize(bit);
+        }
+    }
+
+    fn scalar_len(&self) -> usize {
+        Self::NUM_SCALARS
+    }
+
+    fn rand_like<R: CryptoRngCore + ?Sized>(&self, rng: &mut R) -> Self {
+        Self::rand(rng)
+    }
+}
+
+impl<const N: usize> FixedLenWitness for RangeWitness<N> {
+    const NUM_SCALARS: usize = 2 + N * BitWitness::NUM_SCALARS;
+
+    fn rand<R: CryptoRngCore + ?Sized>(rng: &mut R) -> Self {
+        Self {
+            value: FixedLenWitness::rand(rng),
+            blinding: FixedLenWitness::rand(rng),
+            bits: core::array::from_fn(|_| BitWitness::rand(rng)),
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const N: usize> zeroize::Zeroize for RangeWitness<N> {
+    fn zeroize(&mut self) {
+        SymWitness::zeroize(self);
+    }
+}
+
+/// The value commitment `C`, then the bit commitments `C_0, ..., C_{N-1}`
+/// from the least significant bit.
+#[derive(Clone)]
+pub struct RangeInstance<const N: usize> {
+    commitment: SymPoint,
+    bits: [SymPoint; N],
+}
+
+impl<const N: usize> RangeInstance<N> {
+    pub fn new(commitment: SymPoint, bits: [SymPoint; N]) -> Self {
+        Self { commitment, bits }
+    }
+}
+
+impl<const N: usize> sealed_instance::Sealed for RangeInstance<N> {}
+
+impl<const N: usize> SymInstance for RangeInstance<N> {
+    fn num_scalars() -> Option<usize> {
+        Some(0)
+    }
+
+    fn num_points() -> Option<usize> {
+        Some(Self::NUM_POINTS)
+    }
+
+    /// Expects the value commitment, followed by the bit commitments.
+    fn from_values(scalars: &[Scalar], points: &[RistrettoPoint]) -> SigmaProofResult<Self> {
+        if !scalars.is_empty() {
+            return Err(SigmaProofError::TooManyScalars {
+                expected: 0,
+                actual: scalars.len(),
+            });
+        }
+        if points.len() < Self::NUM_POINTS {
+            return Err(SigmaProofError::InsufficientPoints);
+        }
+        if points.len() > Self::NUM_POINTS {
+            return Err(SigmaProofError::TooManyPoints {
+                expected: Self::NUM_POINTS,
+                actual: points.len(),
+            });
+        }
+        Ok(Self {
+            commitment: SymPoint::Const(points[0]),
+            bits: core::array::from_fn(|i| SymPoint::Const(points[i + 1])),
+        })
+    }
+
+    fn get_field_names() -> Vec<String> {
+        let mut names = vec!["commitment".to_string()];
+        names.extend(vec!["bit".to_string(); N]);
+        names
+    }
+
+    fn points(&self) -> Vec<SymPoint> {
+        [&self.commitment]
+            .into_iter()
+            .chain(&self.bits)
+            .cloned()
+            .collect()
+    }
+
+    fn scalars(&self) -> Vec<SymScalar> {
+        vec![]
+    }
+}
+
+impl<const N: usize> FixedLenInstance for RangeInstance<N> {
+    const NUM_SCALARS: usize = 0;
+    const NUM_POINTS: usize = N + 1;
+}
+
+impl<const N: usize> SigmaProof for RangeProof<N> {
+    const LABEL: &'static [u8] = b"range-protocol";
+    const NUM_EQUATIONS: Option<usize> = Some(2 * N + 2);
+
+    type WITNESS = RangeWitness<N>;
+    type INSTANCE = RangeInstance<N>;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let mut f = vec![instance.commitment.clone()];
+        for bit in &instance.bits {
+            f.extend(BitProof::f(&BitInstance::new(bit.clone())));
+        }
+        f.push(SymPoint::Identity);
+        f
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let mut psi = vec![&witness.value * G + &witness.blinding * H.clone()];
+        let mut recombined = Vec::with_capacity(N);
+        let mut power = Scalar::ONE;
+        for (bit, commitment) in witness.bits.iter().zip(&instance.bits) {
+            psi.extend(BitProof::psi(bit, &BitInstance::new(commitment.clone())));
+            recombined.push(SymScalar::Const(power) * bit.choice());
+            power += power;
+        }
+        let recombined = recombined
+            .into_iter()
+            .reduce(|acc, term| acc + term)
+            .unwrap_or(SymScalar::Const(Scalar::ZERO));
+        psi.push((&witness.value - recombined) * G);
+        psi
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statement<const N: usize>(value: u64) -> (RangeWitness<N>, RangeInstance<N>) {
+        let rng = &mut rand::rngs::OsRng;
+        RangeProof::<N>::commit(
+            value,
+            Scalar::random(rng),
+            core::array::from_fn(|_| Scalar::random(rng)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_range_proof_in_range() {
+        for value in [0, 1, 42, 255] {
+            let (witness, instance) = statement::<8>(value);
+            let proof = RangeProof::<8>::prove_checked(&witness, &instance).unwrap();
+            RangeProof::<8>::verify(&instance, &proof).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_range_proof_out_of_range() {
+        for value in [256, 1000, u64::MAX] {
+            let (witness, instance) = statement::<8>(value);
+            assert!(matches!(
+                RangeProof::<8>::prove_checked(&witness, &instance),
+                Err(SigmaProofError::InstanceConsistency)
+            ));
+            let proof = RangeProof::<8>::prove(&witness, &instance).unwrap();
+            assert!(matches!(
+                RangeProof::<8>::verify(&instance, &proof),
+                Err(SigmaProofError::EquationCheckFailed)
+            ));
+        }
+
+        // 2 = 2*1 + 0*2, but the first "bit" is not a bit
+        let rng = &mut rand::rngs::OsRng;
+        let (witness, mut instance) = statement::<8>(2);
+        let (r, s) = (Scalar::random(rng), Scalar::random(rng));
+        let two = Scalar::from(2u64);
+        instance.bits[0] = two * G + r * H.clone();
+        instance.bits[1] = s * H.clone();
+        let mut scalars = witness.values().unwrap();
+        scalars[2..8].copy_from_slice(&[two, r, Scalar::ZERO, Scalar::ZERO, s, Scalar::ZERO]);
+        let witness = RangeWitness::<8>::from_values(&scalars).unwrap();
+        let proof = RangeProof::<8>::prove(&witness, &instance).unwrap();
+        assert!(RangeProof::<8>::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_range_proof_size() {
+        fn proof_len<const N: usize>() -> usize {
+            let (witness, instance) = statement::<N>(1);
+            RangeProof::<N>::prove(&witness, &instance).unwrap().len()
+        }
+
+        for (n, len) in [
+            (1, proof_len::<1>()),
+            (8, proof_len::<8>()),
+            (16, proof_len::<16>()),
+            (32, proof_len::<32>()),
+        ] {
+            println!("n = {n}: {len} bytes");
+            assert_eq!(len, 32 * (5 * n + 4));
+        }
+    }
+
+    #[test]
+    fn test_range_instance_point_count() {
+        let (_, instance) = statement::<8>(42);
+        let points: Vec<RistrettoPoint> = instance
+            .points()
+            .into_iter()
+            .map(|p| p.evaluate().unwrap())
+            .collect();
+        assert_eq!(points.len(), 9);
+        assert!(RangeInstance::<8>::from_values(&[], &points).is_ok());
+
+        assert!(matches!(
+            RangeInstance::<8>::from_values(&[], &points[..8]),
+            Err(SigmaProofError::InsufficientPoints)
+        ));
+
+        let mut extra = points.clone();
+        extra.push(RistrettoPoint::random(&mut rand::rngs::OsRng));
+        assert!(matches!(
+            RangeInstance::<8>::from_values(&[], &extra),
+            Err(SigmaProofError::TooManyPoints {
+                expected: 9,
+                actual: 10
+            })
+        ));
+    }
+
+    conformance_tests!(RangeProof<8>);
+}
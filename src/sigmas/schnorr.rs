@@ -2,6 +2,7 @@ use crate::{
     absorb::{SymInstance, SymPoint, SymScalar, SymWitness},
     compiler::SigmaProof,
 };
+use alloc::{vec, vec::Vec};
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 
 pub struct SchnorrIdentityProtocol;
@@ -33,11 +34,124 @@ impl SigmaProof for SchnorrIdentityProtocol {
     }
 }
 
+/// Proves knowledge of the discrete log of `pubkey` with respect to an
+/// arbitrary `base` taken from the instance.
+pub struct GenericSchnorr;
+
+#[derive(SymInstance, Clone)]
+pub struct GenericSchnorrInstance {
+    base: SymPoint,
+    pubkey: SymPoint,
+}
+
+impl SigmaProof for GenericSchnorr {
+    const LABEL: &'static [u8] = b"generic-schnorr-protocol";
+
+    type WITNESS = SchnorrWitness;
+    type INSTANCE = GenericSchnorrInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::INSTANCE { pubkey, .. } = instance.clone();
+        vec![pubkey]
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::WITNESS { privatekey } = witness.clone();
+        let Self::INSTANCE { base, .. } = instance.clone();
+        vec![privatekey * base]
+    }
+}
+
+/// Schnorr over a base from the instance, see [`GenericSchnorr`].
+pub type SchnorrOverBase = GenericSchnorr;
+pub type SchnorrOverBaseInstance = GenericSchnorrInstance;
+
+/// Proves knowledge of `x` such that `pubkey = (k*x)*G`, where the multiplier
+/// `k` is a public scalar of the instance.
+pub struct ScaledSchnorr;
+
+#[derive(SymInstance, Clone)]
+pub struct ScaledSchnorrInstance {
+    multiplier: SymScalar,
+    pubkey: SymPoint,
+}
+
+impl SigmaProof for ScaledSchnorr {
+    const LABEL: &'static [u8] = b"scaled-schnorr-protocol";
+
+    type WITNESS = SchnorrWitness;
+    type INSTANCE = ScaledSchnorrInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::INSTANCE { pubkey, .. } = instance.clone();
+        vec![pubkey]
+    }
+
+    fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let Self::WITNESS { privatekey } = witness.clone();
+        let Self::INSTANCE { multiplier, .. } = instance.clone();
+        vec![(multiplier * privatekey) * SymPoint::Const(RISTRETTO_BASEPOINT_POINT)]
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use curve25519_dalek::Scalar;
+    use curve25519_dalek::{
+        ristretto::CompressedRistretto, traits::Identity, RistrettoPoint, Scalar,
+    };
 
     use super::*;
+    use crate::{
+        errors::{SigmaProofError, SigmaProofResult},
+        multi_round::{AsMultiRound, MultiRoundSigma},
+        sigmas::{G, H},
+        transcript::{ProofTranscript, TranscriptBackend},
+    };
+
+    /// Declares a test protocol that forwards `f` and `psi` to
+    /// [`SchnorrIdentityProtocol`] under the given label, with the given
+    /// constants and methods of [`SigmaProof`] overridden. With `psi only`,
+    /// `f` is not forwarded, so that it can be overridden or left to its
+    /// default.
+    macro_rules! forwarding_schnorr {
+        ($(#[$meta:meta])* $name:ident, $label:expr; $($item:item)*) => {
+            forwarding_schnorr! {
+                $(#[$meta])*
+                $name, $label, psi only;
+                fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+                    SchnorrIdentityProtocol::f(instance)
+                }
+                $($item)*
+            }
+        };
+        ($(#[$meta:meta])* $name:ident, $label:expr, psi only; $($item:item)*) => {
+            $(#[$meta])*
+            struct $name;
+
+            impl SigmaProof for $name {
+                const LABEL: &'static [u8] = $label;
+
+                type WITNESS = SchnorrWitness;
+                type INSTANCE = SchnorrInstance;
+
+                fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+                    SchnorrIdentityProtocol::psi(witness, instance)
+                }
+
+                $($item)*
+            }
+        };
+    }
+
+    /// A random secret key, with the Schnorr witness and instance for it.
+    fn schnorr_statement() -> (Scalar, SchnorrWitness, SchnorrInstance) {
+        let sk = Scalar::random(&mut rand::rngs::OsRng);
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+        let instance = SchnorrInstance::new(sk * RISTRETTO_BASEPOINT_POINT);
+        (sk, witness, instance)
+    }
 
     #[test]
     fn test_schnorr_identity_protocol() {
@@ -59,9 +173,672 @@ mod tests {
         SchnorrIdentityProtocol::verify(&instance, &proof).unwrap();
     }
 
+    #[test]
+    fn test_schnorr_recover_challenge() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+        let pk = sk * RISTRETTO_BASEPOINT_POINT;
+        let instance = SchnorrInstance {
+            pubkey: SymPoint::Const(pk),
+        };
+
+        let proof = SchnorrIdentityProtocol::prove(&witness, &instance).unwrap();
+        let e = SchnorrIdentityProtocol::recover_challenge(&instance, &proof).unwrap();
+
+        // the proof is A || z with z*G = A + e*pk
+        let big_a = CompressedRistretto::from_slice(&proof[..32])
+            .unwrap()
+            .decompress()
+            .unwrap();
+        let z = Scalar::from_canonical_bytes(proof[32..].try_into().unwrap()).unwrap();
+        assert_eq!(z * RISTRETTO_BASEPOINT_POINT, big_a + e * pk);
+    }
+
+    #[test]
+    fn test_schnorr_verify_with_challenge() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+        let instance = SchnorrInstance::new(sk * RISTRETTO_BASEPOINT_POINT);
+
+        let proof = SchnorrIdentityProtocol::prove(&witness, &instance).unwrap();
+        let e = SchnorrIdentityProtocol::verify_with_challenge(&instance, &proof).unwrap();
+        assert_eq!(
+            e,
+            SchnorrIdentityProtocol::recover_challenge(&instance, &proof).unwrap()
+        );
+
+        // the challenge of an invalid proof is not returned
+        let mut tampered = proof.clone();
+        tampered[32] ^= 1;
+        assert!(matches!(
+            SchnorrIdentityProtocol::verify_with_challenge(&instance, &tampered),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_schnorr_non_canonical_commitment() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+        let instance = SchnorrInstance {
+            pubkey: SymPoint::Const(sk * RISTRETTO_BASEPOINT_POINT),
+        };
+        let mut proof = SchnorrIdentityProtocol::prove(&witness, &instance).unwrap();
+
+        // replace A with a non-reduced encoding of the field element 0
+        proof[..32].copy_from_slice(&[0xff; 32]);
+        proof[0] = 0xed;
+        proof[31] = 0x7f;
+        assert!(matches!(
+            SchnorrIdentityProtocol::verify(&instance, &proof),
+            Err(SigmaProofError::TranscriptError)
+        ));
+    }
+
+    #[test]
+    fn test_schnorr_simulate() {
+        let rng = &mut rand::rngs::OsRng;
+        let instance = SchnorrInstance {
+            pubkey: SymPoint::Const(RistrettoPoint::random(rng)),
+        };
+
+        let e = Scalar::random(rng);
+        let (commitments, responses) =
+            SchnorrIdentityProtocol::simulate(&instance, e, rng).unwrap();
+        assert_eq!((commitments.len(), responses.len()), (1, 1));
+        SchnorrIdentityProtocol::check_transcript(&instance, &commitments, e, &responses).unwrap();
+
+        // the simulated transcript only holds for its own challenge
+        assert!(matches!(
+            SchnorrIdentityProtocol::check_transcript(
+                &instance,
+                &commitments,
+                e + Scalar::ONE,
+                &responses
+            ),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    /// A backend distinct from the default one, which prefixes every label.
+    struct PrefixedMerlin(merlin::Transcript);
+
+    impl TranscriptBackend for PrefixedMerlin {
+        fn new(label: &'static [u8]) -> Self {
+            let mut transcript = merlin::Transcript::new(b"prefixed");
+            transcript.append_message(b"label", label);
+            Self(transcript)
+        }
+
+        fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+            self.0.append_message(b"prefixed", label);
+            self.0.append_message(label, message);
+        }
+
+        fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+            self.0.append_message(b"prefixed", label);
+            self.0.challenge_bytes(label, dest);
+        }
+    }
+
+    fn schnorr_roundtrip<B: TranscriptBackend>() -> (SchnorrInstance, Vec<u8>) {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+        let instance = SchnorrInstance::new(sk * RISTRETTO_BASEPOINT_POINT);
+
+        let proof =
+            SchnorrIdentityProtocol::prove_with_backend::<B>(&witness, &instance, b"").unwrap();
+        SchnorrIdentityProtocol::verify_with_backend::<B>(&instance, &proof, b"").unwrap();
+        (instance, proof)
+    }
+
+    #[test]
+    fn test_schnorr_backends() {
+        let (instance, proof) = schnorr_roundtrip::<merlin::Transcript>();
+        SchnorrIdentityProtocol::verify(&instance, &proof).unwrap();
+        assert!(
+            SchnorrIdentityProtocol::verify_with_backend::<PrefixedMerlin>(&instance, &proof, b"")
+                .is_err()
+        );
+
+        let (instance, proof) = schnorr_roundtrip::<PrefixedMerlin>();
+        assert!(SchnorrIdentityProtocol::verify(&instance, &proof).is_err());
+    }
+
+    /// Schnorr bound to an epoch, which is not part of the instance.
+    struct EpochSchnorr<const EPOCH: u64>;
+
+    impl<const EPOCH: u64> SigmaProof for EpochSchnorr<EPOCH> {
+        const LABEL: &'static [u8] = b"epoch-schnorr-protocol";
+
+        type WITNESS = SchnorrWitness;
+        type INSTANCE = SchnorrInstance;
+
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            SchnorrIdentityProtocol::f(instance)
+        }
+
+        fn psi(witness: &Self::WITNESS, instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            SchnorrIdentityProtocol::psi(witness, instance)
+        }
+
+        fn bind_extra<B: TranscriptBackend>(transcript: &mut ProofTranscript<B>) {
+            transcript.common_absorb_bytes(b"epoch", &EPOCH.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn test_schnorr_bind_extra() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+        let instance = SchnorrInstance::new(sk * RISTRETTO_BASEPOINT_POINT);
+
+        let proof = EpochSchnorr::<1>::prove(&witness, &instance).unwrap();
+        EpochSchnorr::<1>::verify(&instance, &proof).unwrap();
+        assert!(matches!(
+            EpochSchnorr::<2>::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+
+        let compact = EpochSchnorr::<1>::prove_compact(&witness, &instance).unwrap();
+        EpochSchnorr::<1>::verify_compact(&instance, &compact).unwrap();
+        assert!(EpochSchnorr::<2>::verify_compact(&instance, &compact).is_err());
+    }
+
+    #[test]
+    fn test_schnorr_signature() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+        let instance = SchnorrInstance::new(sk * RISTRETTO_BASEPOINT_POINT);
+
+        let messages: [&[u8]; 2] = [b"pay alice 10", b"pay bob 10"];
+        let signatures = messages.map(|message| {
+            SchnorrIdentityProtocol::prove_signature(&witness, &instance, message).unwrap()
+        });
+        for (i, signature) in signatures.iter().enumerate() {
+            SchnorrIdentityProtocol::verify_signature(&instance, signature, messages[i]).unwrap();
+
+            // a signature does not verify under the other message
+            assert!(matches!(
+                SchnorrIdentityProtocol::verify_signature(&instance, signature, messages[1 - i]),
+                Err(SigmaProofError::EquationCheckFailed)
+            ));
+            // nor as a plain proof
+            assert!(SchnorrIdentityProtocol::verify(&instance, signature).is_err());
+        }
+
+        // a plain proof is not a signature, even of the empty message
+        let proof = SchnorrIdentityProtocol::prove(&witness, &instance).unwrap();
+        assert!(SchnorrIdentityProtocol::verify_signature(&instance, &proof, b"").is_err());
+
+        // nor does a signature verify under another key
+        let other = SchnorrInstance::new(Scalar::random(rng) * RISTRETTO_BASEPOINT_POINT);
+        assert!(
+            SchnorrIdentityProtocol::verify_signature(&other, &signatures[0], messages[0]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_schnorr_deterministic_bind_extra() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+        let instance = SchnorrInstance::new(sk * RISTRETTO_BASEPOINT_POINT);
+
+        // the same proof can be made again
+        let proof = EpochSchnorr::<1>::prove_deterministic(&witness, &instance).unwrap();
+        let again = EpochSchnorr::<1>::prove_deterministic(&witness, &instance).unwrap();
+        assert_eq!(proof, again);
+
+        // the deterministic nonces depend on the epoch, so proving for another
+        // one does not answer the same commitment with another challenge
+        let other = EpochSchnorr::<2>::prove_deterministic(&witness, &instance).unwrap();
+        assert_ne!(proof[..32], other[..32]);
+        EpochSchnorr::<1>::verify(&instance, &proof).unwrap();
+        EpochSchnorr::<2>::verify(&instance, &other).unwrap();
+    }
+
+    forwarding_schnorr! {
+        /// Schnorr rejecting identity commitments.
+        StrictSchnorr, SchnorrIdentityProtocol::LABEL;
+        const REJECT_IDENTITY_COMMITMENTS: bool = true;
+    }
+
+    #[test]
+    fn test_schnorr_identity_commitment() {
+        let (sk, witness, instance) = schnorr_statement();
+
+        // a valid proof with the nonce 0, i.e. A = identity and z = e*sk
+        let mut proof = RistrettoPoint::identity().compress().to_bytes().to_vec();
+        proof.extend([0; 32]);
+        let e = SchnorrIdentityProtocol::recover_challenge(&instance, &proof).unwrap();
+        proof[32..].copy_from_slice((e * sk).as_bytes());
+
+        SchnorrIdentityProtocol::verify(&instance, &proof).unwrap();
+        SchnorrIdentityProtocol::verify_streaming(&instance, &proof).unwrap();
+        assert!(matches!(
+            StrictSchnorr::verify(&instance, &proof),
+            Err(SigmaProofError::IdentityCommitment)
+        ));
+        assert!(matches!(
+            StrictSchnorr::verify_streaming(&instance, &proof),
+            Err(SigmaProofError::IdentityCommitment)
+        ));
+
+        // the same proof in the compact encoding, whose commitment is
+        // recomputed
+        let compact = [e.as_bytes(), &proof[32..]].concat();
+        SchnorrIdentityProtocol::verify_compact(&instance, &compact).unwrap();
+        assert!(matches!(
+            StrictSchnorr::verify_compact(&instance, &compact),
+            Err(SigmaProofError::IdentityCommitment)
+        ));
+
+        // honest proofs are accepted by both
+        let proof = StrictSchnorr::prove(&witness, &instance).unwrap();
+        StrictSchnorr::verify(&instance, &proof).unwrap();
+        SchnorrIdentityProtocol::verify(&instance, &proof).unwrap();
+    }
+
+    forwarding_schnorr! {
+        /// Schnorr with the labels of an inner protocol, as when run inside
+        /// another one.
+        RelabeledSchnorr, SchnorrIdentityProtocol::LABEL;
+        const COMMIT_LABEL: &'static [u8] = b"inner-r";
+        const CHALLENGE_LABEL: &'static [u8] = b"inner-e";
+        const RESPONSE_LABEL: &'static [u8] = b"inner-z";
+    }
+
+    #[test]
+    fn test_schnorr_transcript_labels() {
+        let (_, witness, instance) = schnorr_statement();
+
+        let proof = SchnorrIdentityProtocol::prove(&witness, &instance).unwrap();
+        let relabeled = RelabeledSchnorr::prove(&witness, &instance).unwrap();
+        SchnorrIdentityProtocol::verify(&instance, &proof).unwrap();
+        RelabeledSchnorr::verify(&instance, &relabeled).unwrap();
+
+        // the labels separate the protocols although everything else is equal
+        assert!(SchnorrIdentityProtocol::verify(&instance, &relabeled).is_err());
+        assert!(RelabeledSchnorr::verify(&instance, &proof).is_err());
+        assert_ne!(
+            SchnorrIdentityProtocol::recover_challenge(&instance, &proof).unwrap(),
+            RelabeledSchnorr::recover_challenge(&instance, &proof).unwrap()
+        );
+
+        // and are kept when run as a multi-round protocol
+        AsMultiRound::<RelabeledSchnorr>::verify(&instance, &relabeled).unwrap();
+        let proof = AsMultiRound::<RelabeledSchnorr>::prove(&witness, &instance).unwrap();
+        RelabeledSchnorr::verify(&instance, &proof).unwrap();
+    }
+
+    forwarding_schnorr! {
+        /// Schnorr with the default `f`.
+        DefaultFSchnorr, SchnorrIdentityProtocol::LABEL, psi only;
+        const F_IS_IDENTITY: bool = true;
+    }
+
+    #[test]
+    fn test_schnorr_default_f() {
+        let (_, witness, instance) = schnorr_statement();
+
+        let evaluate = |points: Vec<SymPoint>| -> Vec<RistrettoPoint> {
+            points.iter().map(|p| p.evaluate().unwrap()).collect()
+        };
+        assert_eq!(
+            evaluate(DefaultFSchnorr::f(&instance)),
+            evaluate(SchnorrIdentityProtocol::f(&instance))
+        );
+
+        // proofs are interchangeable
+        let proof = DefaultFSchnorr::prove(&witness, &instance).unwrap();
+        SchnorrIdentityProtocol::verify(&instance, &proof).unwrap();
+        let proof = SchnorrIdentityProtocol::prove(&witness, &instance).unwrap();
+        DefaultFSchnorr::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_schnorr_deterministic() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+        let instance = SchnorrInstance::new(sk * RISTRETTO_BASEPOINT_POINT);
+
+        let proof = SchnorrIdentityProtocol::prove_deterministic(&witness, &instance).unwrap();
+        let again = SchnorrIdentityProtocol::prove_deterministic(&witness, &instance).unwrap();
+        assert_eq!(proof, again);
+        SchnorrIdentityProtocol::verify(&instance, &proof).unwrap();
+
+        // the nonce depends on the statement
+        let other_sk = Scalar::random(rng);
+        let other = SchnorrIdentityProtocol::prove_deterministic(
+            &SchnorrWitness {
+                privatekey: SymScalar::Const(other_sk),
+            },
+            &SchnorrInstance::new(other_sk * RISTRETTO_BASEPOINT_POINT),
+        )
+        .unwrap();
+        assert_ne!(proof[..32], other[..32]);
+
+        // randomized proofs use fresh nonces
+        let randomized = SchnorrIdentityProtocol::prove(&witness, &instance).unwrap();
+        assert_ne!(proof, randomized);
+    }
+
+    #[test]
+    fn test_schnorr_randomness_bytes() {
+        let instance = SchnorrInstance::new(RISTRETTO_BASEPOINT_POINT);
+        assert_eq!(
+            SchnorrIdentityProtocol::randomness_bytes(&instance).unwrap(),
+            64
+        );
+    }
+
     #[test]
     fn test_schnorr_spec_generation() {
         let spec = SchnorrIdentityProtocol::spec();
         println!("{spec}");
     }
+
+    #[test]
+    fn test_generic_schnorr_protocol() {
+        let rng = &mut rand::rngs::OsRng;
+        let sk = Scalar::random(rng);
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+
+        let base = RistrettoPoint::random(rng);
+        let instance = GenericSchnorrInstance {
+            base: SymPoint::Const(base),
+            pubkey: SymPoint::Const(sk * base),
+        };
+
+        let proof = GenericSchnorr::prove(&witness, &instance).unwrap();
+        GenericSchnorr::verify(&instance, &proof).unwrap();
+
+        // the same key does not verify against the default base
+        let instance = GenericSchnorrInstance {
+            base: SymPoint::Const(RISTRETTO_BASEPOINT_POINT),
+            pubkey: SymPoint::Const(sk * base),
+        };
+        let proof = GenericSchnorr::prove(&witness, &instance).unwrap();
+        assert!(GenericSchnorr::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_schnorr_over_base() {
+        let rng = &mut rand::rngs::OsRng;
+        let bases = [
+            G.evaluate().unwrap(),
+            H.evaluate().unwrap(),
+            RistrettoPoint::random(rng),
+        ];
+        for base in bases {
+            let sk = Scalar::random(rng);
+            let witness = SchnorrWitness {
+                privatekey: SymScalar::Const(sk),
+            };
+            let instance = SchnorrOverBaseInstance {
+                base: SymPoint::Const(base),
+                pubkey: SymPoint::Const(sk * base),
+            };
+
+            let proof = SchnorrOverBase::prove(&witness, &instance).unwrap();
+            SchnorrOverBase::verify(&instance, &proof).unwrap();
+
+            let wrong = SchnorrWitness {
+                privatekey: SymScalar::Const(sk + Scalar::ONE),
+            };
+            let proof = SchnorrOverBase::prove(&wrong, &instance).unwrap();
+            assert!(SchnorrOverBase::verify(&instance, &proof).is_err());
+        }
+    }
+
+    #[test]
+    fn test_generic_schnorr_spec_generation() {
+        let spec = GenericSchnorr::spec();
+        println!("{spec}");
+    }
+
+    #[test]
+    fn test_scaled_schnorr_protocol() {
+        let rng = &mut rand::rngs::OsRng;
+        let (k, sk) = (Scalar::random(rng), Scalar::random(rng));
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+        let instance = ScaledSchnorrInstance::new(k, (k * sk) * RISTRETTO_BASEPOINT_POINT);
+
+        let proof = ScaledSchnorr::prove(&witness, &instance).unwrap();
+        ScaledSchnorr::verify(&instance, &proof).unwrap();
+
+        // the multiplier is part of the statement
+        let other = ScaledSchnorrInstance::new(k + Scalar::ONE, instance.pubkey.clone());
+        assert!(ScaledSchnorr::verify(&other, &proof).is_err());
+    }
+
+    #[test]
+    fn test_scaled_schnorr_absorbs_multiplier() {
+        let rng = &mut rand::rngs::OsRng;
+        let (k, sk) = (Scalar::random(rng), Scalar::random(rng));
+        let witness = SchnorrWitness {
+            privatekey: SymScalar::Const(sk),
+        };
+        let pubkey = (k * sk) * RISTRETTO_BASEPOINT_POINT;
+        let instance = ScaledSchnorrInstance::new(k, pubkey);
+        let proof = ScaledSchnorr::prove(&witness, &instance).unwrap();
+
+        // instances that only differ in the multiplier derive different challenges
+        let other = ScaledSchnorrInstance::new(k + Scalar::ONE, pubkey);
+        assert_ne!(
+            ScaledSchnorr::recover_challenge(&instance, &proof).unwrap(),
+            ScaledSchnorr::recover_challenge(&other, &proof).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_scaled_schnorr_spec_generation() {
+        let spec = ScaledSchnorr::spec();
+        println!("{spec}");
+        assert!(spec.contains(r"(\texttt{multiplier} \cdot \texttt{privatekey}) \cdot G"));
+        assert!(spec.contains(r"\mathbb{F}^{1} \times \mathbb{G}^{1}"));
+    }
+
+    /// Knowledge of `x` with `pubkey = x^2 * G`, which is not linear in `x`
+    /// and only used to render its spec.
+    struct SquareSchnorr;
+
+    impl SigmaProof for SquareSchnorr {
+        const LABEL: &'static [u8] = b"square-schnorr-protocol";
+
+        type WITNESS = SchnorrWitness;
+        type INSTANCE = SchnorrInstance;
+
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            SchnorrIdentityProtocol::f(instance)
+        }
+
+        fn psi(witness: &Self::WITNESS, _instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![witness.privatekey.clone().pow(2) * G]
+        }
+    }
+
+    #[test]
+    fn test_square_schnorr_spec_generation() {
+        let spec = SquareSchnorr::spec();
+        assert!(spec.contains(r"\texttt{privatekey}^{2} \cdot G"));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_schnorr_prove_many() {
+        let rng = &mut rand::rngs::OsRng;
+        let pairs: Vec<_> = (0..64)
+            .map(|_| {
+                let sk = Scalar::random(rng);
+                (
+                    SchnorrWitness::from_values(&[sk]).unwrap(),
+                    SchnorrInstance::new(sk * RISTRETTO_BASEPOINT_POINT),
+                )
+            })
+            .collect();
+
+        let proofs = SchnorrIdentityProtocol::prove_many(&pairs).unwrap();
+        assert_eq!(proofs.len(), pairs.len());
+        for ((_, instance), proof) in pairs.iter().zip(&proofs) {
+            SchnorrIdentityProtocol::verify(instance, proof).unwrap();
+        }
+        // every proof draws its own nonces
+        for (i, proof) in proofs.iter().enumerate() {
+            assert!(!proofs[i + 1..].contains(proof));
+        }
+    }
+
+    forwarding_schnorr! {
+        /// Schnorr with a 128-bit challenge, for 128 bits of soundness.
+        ShortChallengeSchnorr, b"short-challenge-schnorr-protocol";
+        const CHALLENGE_BITS: usize = 128;
+    }
+
+    forwarding_schnorr! {
+        /// Schnorr whose verifier only accepts expressions of depth 1.
+        ShallowSchnorr, SchnorrIdentityProtocol::LABEL;
+        const MAX_EXPRESSION_DEPTH: Option<usize> = Some(1);
+    }
+
+    #[test]
+    fn test_schnorr_max_expression_depth() {
+        let (_, witness, instance) = schnorr_statement();
+
+        // f is the public key, but psi is the scaling x*G
+        let proof = SchnorrIdentityProtocol::prove(&witness, &instance).unwrap();
+        assert!(matches!(
+            ShallowSchnorr::verify(&instance, &proof),
+            Err(SigmaProofError::ExpressionTooComplex { depth: 2, max: 1 })
+        ));
+        assert!(matches!(
+            ShallowSchnorr::verify_streaming(&instance, &proof),
+            Err(SigmaProofError::ExpressionTooComplex { depth: 2, max: 1 })
+        ));
+
+        let proof = SchnorrIdentityProtocol::prove_compact(&witness, &instance).unwrap();
+        assert!(matches!(
+            ShallowSchnorr::verify_compact(&instance, &proof),
+            Err(SigmaProofError::ExpressionTooComplex { depth: 2, max: 1 })
+        ));
+
+        let witnesses = [witness.clone(), witness];
+        let proof = SchnorrIdentityProtocol::prove_aggregated(&witnesses, &instance).unwrap();
+        assert!(matches!(
+            ShallowSchnorr::verify_aggregated(&instance, 2, &proof),
+            Err(SigmaProofError::ExpressionTooComplex { depth: 2, max: 1 })
+        ));
+    }
+
+    forwarding_schnorr! {
+        /// Schnorr whose `f` has one more output than `psi`.
+        MismatchedSchnorr, b"mismatched-schnorr-protocol", psi only;
+        fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+            vec![instance.pubkey.clone(), instance.pubkey.clone()]
+        }
+    }
+
+    #[test]
+    fn test_schnorr_try_spec_reports_error() {
+        assert!(matches!(
+            MismatchedSchnorr::try_spec(),
+            Err(SigmaProofError::PsiOutputLengthMismatch)
+        ));
+        let spec = MismatchedSchnorr::spec();
+        assert!(spec.starts_with("#### mismatched-schnorr-protocol\n"));
+        assert!(spec.ends_with(&SigmaProofError::PsiOutputLengthMismatch.to_string()));
+
+        assert_eq!(
+            SchnorrIdentityProtocol::try_spec().unwrap(),
+            SchnorrIdentityProtocol::spec()
+        );
+    }
+
+    forwarding_schnorr! {
+        /// Schnorr claiming one more response than its witness has scalars.
+        OverlongSchnorr, b"overlong-schnorr-protocol";
+        fn num_responses(_instance: &Self::INSTANCE) -> SigmaProofResult<usize> {
+            Ok(2)
+        }
+    }
+
+    #[test]
+    fn test_schnorr_try_spec_reports_from_values_error() {
+        // the dummy witness cannot be built from two scalars
+        assert!(matches!(
+            OverlongSchnorr::try_spec(),
+            Err(SigmaProofError::TooManyScalars {
+                expected: 1,
+                actual: 2
+            })
+        ));
+        let spec = OverlongSchnorr::spec();
+        assert!(spec.starts_with("#### overlong-schnorr-protocol\n"));
+    }
+
+    #[test]
+    fn test_schnorr_short_challenge() {
+        let (_, witness, instance) = schnorr_statement();
+
+        // regular proofs do not send the challenge and keep their size
+        let proof = ShortChallengeSchnorr::prove(&witness, &instance).unwrap();
+        assert_eq!(proof.len(), 64);
+        ShortChallengeSchnorr::verify(&instance, &proof).unwrap();
+        let e = ShortChallengeSchnorr::recover_challenge(&instance, &proof).unwrap();
+        assert!(e.as_bytes()[16..].iter().all(|&b| b == 0));
+
+        // compact proofs shrink from 32 + 32 to 16 + 32 bytes
+        let compact = ShortChallengeSchnorr::prove_compact(&witness, &instance).unwrap();
+        assert_eq!(compact.len(), 16 + 32);
+        assert_eq!(
+            SchnorrIdentityProtocol::prove_compact(&witness, &instance)
+                .unwrap()
+                .len(),
+            32 + 32
+        );
+        ShortChallengeSchnorr::verify_compact(&instance, &compact).unwrap();
+        assert!(matches!(
+            ShortChallengeSchnorr::verify_compact(&instance, &compact[1..]),
+            Err(SigmaProofError::ProofLengthInvalid { .. })
+        ));
+
+        let mut tampered = compact.clone();
+        tampered[0] ^= 1;
+        assert!(matches!(
+            ShortChallengeSchnorr::verify_compact(&instance, &tampered),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    conformance_tests!(SchnorrIdentityProtocol);
+    conformance_tests!(generic_conformance: GenericSchnorr);
+    conformance_tests!(scaled_conformance: ScaledSchnorr);
 }
@@ -0,0 +1,269 @@
+use crate::{
+    absorb::{sealed_witness, FixedLenWitness, SymInstance, SymPoint, SymScalar, SymWitness},
+    compiler::SigmaProof,
+    errors::{SigmaProofError, SigmaProofResult},
+    sigmas::{derive_generators, H},
+};
+use alloc::{borrow::Cow, format, vec, vec::Vec};
+use curve25519_dalek::{traits::MultiscalarMul, RistrettoPoint, Scalar};
+use rand_core::CryptoRngCore;
+#[cfg(not(feature = "std"))]
+use spin::Lazy;
+#[cfg(feature = "std")]
+use std::sync::LazyLock as Lazy;
+
+/// The label the bases `G_i` of [`VectorCommitment`] are derived from.
+const BASES_LABEL: &[u8] = b"sigma-proof-compiler/vector-commitment";
+
+/// The longest vector a [`VectorCommitment`] commits to.
+pub const MAX_LEN: usize = 256;
+
+/// The bases `G_0, ..., G_{MAX_LEN-1}`, derived on first use. Generators are
+/// derived one index at a time, so the bases of shorter vectors are prefixes.
+static BASES: Lazy<Vec<RistrettoPoint>> = Lazy::new(|| derive_generators(BASES_LABEL, MAX_LEN));
+
+/// The protocol label, followed by the decimal digits of the vector length.
+const LABEL_PREFIX: &[u8] = b"vector-commitment-protocol-";
+
+/// Writes [`LABEL_PREFIX`] and the digits of `n` to the start of a buffer
+/// large enough for any `usize`, and returns it with the length written.
+const fn label(n: usize) -> ([u8; LABEL_PREFIX.len() + 20], usize) {
+    let mut buf = [0u8; LABEL_PREFIX.len() + 20];
+    let mut len = 0;
+    while len < LABEL_PREFIX.len() {
+        buf[len] = LABEL_PREFIX[len];
+        len += 1;
+    }
+    let (mut digits, mut count, mut rest) = ([0u8; 20], 0, n);
+    loop {
+        digits[count] = b'0' + (rest % 10) as u8;
+        count += 1;
+        rest /= 10;
+        if rest == 0 {
+            break;
+        }
+    }
+    while count > 0 {
+        count -= 1;
+        buf[len] = digits[count];
+        len += 1;
+    }
+    (buf, len)
+}
+
+/// Proves knowledge of the opening of a Pedersen vector commitment
+/// `C = a_0*G_0 + ... + a_{N-1}*G_{N-1} + r*H`, the building block of
+/// inner-product arguments such as Bulletproofs.
+///
+/// The bases `G_i` are fixed by [`derive_generators`], so the instance is only
+/// the commitment. The proof is a representation of `C` in the `G_i` and `H`,
+/// as in [`GeneralizedOkamoto`](super::generalized_okamoto::GeneralizedOkamoto).
+/// It holds one commitment and `N + 1` responses. `N` is at most [`MAX_LEN`].
+pub struct VectorCommitment<const N: usize>;
+
+impl<const N: usize> VectorCommitment<N> {
+    const LABEL_BUF: &'static ([u8; LABEL_PREFIX.len() + 20], usize) = &label(N);
+
+    /// The bases `G_0, ..., G_{N-1}` the vector is committed in.
+    pub fn bases() -> &'static [RistrettoPoint] {
+        const {
+            assert!(
+                N <= MAX_LEN,
+                "VectorCommitment commits to at most MAX_LEN values"
+            )
+        };
+        &BASES[..N]
+    }
+
+    /// Commits to `values` with the blinding `blinding`.
+    pub fn commit(values: &[Scalar; N], blinding: Scalar) -> RistrettoPoint {
+        RistrettoPoint::multiscalar_mul(values, Self::bases())
+            + blinding * H.evaluate().expect("H is a constant")
+    }
+}
+
+/// The opening of a [`VectorCommitment`]: the vector, then the blinding.
+#[derive(Clone)]
+pub struct VectorCommitmentWitness<const N: usize> {
+    values: [SymScalar; N],
+    blinding: SymScalar,
+}
+
+impl<const N: usize> VectorCommitmentWitness<N> {
+    /// The opening of [`VectorCommitment::commit`] for the same arguments.
+    pub fn new(values: [Scalar; N], blinding: Scalar) -> Self {
+        Self {
+            values: values.map(SymScalar::Const),
+            blinding: SymScalar::Const(blinding),
+        }
+    }
+}
+
+impl<const N: usize> sealed_witness::Sealed for VectorCommitmentWitness<N> {}
+
+impl<const N: usize> SymWitness for VectorCommitmentWitness<N> {
+    fn values(&self) -> SigmaProofResult<Vec<Scalar>> {
+        let mut values = self.values.values()?;
+        values.extend(self.blinding.values()?);
+        Ok(values)
+    }
+
+    /// Expects the `N` values, followed by the blinding.
+    fn from_values(scalars: &[Scalar]) -> SigmaProofResult<Self> {
+        if scalars.len() < N + 1 {
+            return Err(SigmaProofError::InsufficientScalars);
+        }
+        if scalars.len() > N + 1 {
+            return Err(SigmaProofError::TooManyScalars {
+                expected: N + 1,
+                actual: scalars.len(),
+            });
+        }
+        Ok(Self {
+            values: SymWitness::from_values(&scalars[..N])?,
+            blinding: SymWitness::from_values(&scalars[N..])?,
+        })
+    }
+
+    fn num_scalars() -> Option<usize> {
+        Some(Self::NUM_SCALARS)
+    }
+
+    fn get_var_name(index: usize) -> Cow<'static, str> {
+        match index {
+            i if i < N => format!("a_{i}").into(),
+            i if i == N => "r".into(),
+            _ => "unknown".into(),
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    fn zeroize(&mut self) {
+        SymWitness::zeroize(&mut self.values);
+        SymWitness::zeroize(&mut self.blinding);
+    }
+
+    fn scalar_len(&self) -> usize {
+        Self::NUM_SCALARS
+    }
+
+    fn rand_like<R: CryptoRngCore + ?Sized>(&self, rng: &mut R) -> Self {
+        Self::rand(rng)
+    }
+}
+
+impl<const N: usize> FixedLenWitness for VectorCommitmentWitness<N> {
+    const NUM_SCALARS: usize = N + 1;
+
+    fn rand<R: CryptoRngCore + ?Sized>(rng: &mut R) -> Self {
+        Self {
+            values: FixedLenWitness::rand(rng),
+            blinding: FixedLenWitness::rand(rng),
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const N: usize> zeroize::Zeroize for VectorCommitmentWitness<N> {
+    fn zeroize(&mut self) {
+        SymWitness::zeroize(self);
+    }
+}
+
+#[derive(SymInstance, Clone)]
+pub struct VectorCommitmentInstance {
+    commitment: SymPoint,
+}
+
+impl<const N: usize> SigmaProof for VectorCommitment<N> {
+    const LABEL: &'static [u8] = Self::LABEL_BUF.0.split_at(Self::LABEL_BUF.1).0;
+    const NUM_EQUATIONS: Option<usize> = Some(1);
+
+    type WITNESS = VectorCommitmentWitness<N>;
+    type INSTANCE = VectorCommitmentInstance;
+
+    fn f(instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        vec![instance.commitment.clone()]
+    }
+
+    fn psi(witness: &Self::WITNESS, _instance: &Self::INSTANCE) -> Vec<SymPoint> {
+        let terms: Vec<_> = witness
+            .values
+            .iter()
+            .cloned()
+            .zip(Self::bases().iter().copied().map(SymPoint::Const))
+            .chain([(witness.blinding.clone(), H.clone())])
+            .collect();
+        vec![SymPoint::linear_combination(&terms)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statement() -> (VectorCommitmentWitness<4>, VectorCommitmentInstance) {
+        let rng = &mut rand::rngs::OsRng;
+        let values: [Scalar; 4] = core::array::from_fn(|_| Scalar::random(rng));
+        let blinding = Scalar::random(rng);
+        let commitment = VectorCommitment::<4>::commit(&values, blinding);
+        (
+            VectorCommitmentWitness::new(values, blinding),
+            VectorCommitmentInstance::new(commitment),
+        )
+    }
+
+    #[test]
+    fn test_vector_commitment() {
+        let (witness, instance) = statement();
+        let proof = VectorCommitment::<4>::prove_checked(&witness, &instance).unwrap();
+        assert_eq!(proof.len(), 32 * (1 + 5));
+        VectorCommitment::<4>::verify(&instance, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_vector_commitment_wrong_opening() {
+        let (witness, instance) = statement();
+        let mut scalars = witness.values().unwrap();
+        scalars[2] += Scalar::ONE;
+        let wrong = VectorCommitmentWitness::<4>::from_values(&scalars).unwrap();
+        assert!(matches!(
+            VectorCommitment::<4>::prove_checked(&wrong, &instance),
+            Err(SigmaProofError::InstanceConsistency)
+        ));
+        let proof = VectorCommitment::<4>::prove(&wrong, &instance).unwrap();
+        assert!(matches!(
+            VectorCommitment::<4>::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+
+        // the bases depend on their position, so a permuted vector is wrong
+        scalars[2] -= Scalar::ONE;
+        scalars.swap(0, 1);
+        let permuted = VectorCommitmentWitness::<4>::from_values(&scalars).unwrap();
+        let proof = VectorCommitment::<4>::prove(&permuted, &instance).unwrap();
+        assert!(VectorCommitment::<4>::verify(&instance, &proof).is_err());
+    }
+
+    #[test]
+    fn test_vector_commitment_names() {
+        assert_eq!(
+            VectorCommitment::<4>::LABEL,
+            b"vector-commitment-protocol-4"
+        );
+        assert_eq!(
+            VectorCommitment::<10>::LABEL,
+            b"vector-commitment-protocol-10"
+        );
+        assert_eq!(
+            VectorCommitment::<0>::LABEL,
+            b"vector-commitment-protocol-0"
+        );
+        let names: Vec<_> = (0..5)
+            .map(VectorCommitmentWitness::<4>::get_var_name)
+            .collect();
+        assert_eq!(names, ["a_0", "a_1", "a_2", "a_3", "r"]);
+    }
+
+    conformance_tests!(VectorCommitment<4>);
+}
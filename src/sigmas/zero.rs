@@ -3,7 +3,24 @@ use crate::{
     compiler::SigmaProof,
     sigmas::H,
 };
-
+use alloc::{vec, vec::Vec};
+
+/// Proves that a twisted ElGamal ciphertext encrypts zero, given the secret
+/// key `s` of its recipient.
+///
+/// A twisted ElGamal public key is `P = s^-1*H`, and a ciphertext of `m` is
+/// the Pedersen commitment `C = m*G + r*H` with the decrypt handle `D = r*P`.
+/// Decryption computes `C - s*D = m*G`. The prover shows knowledge of `s`
+/// such that
+///
+/// ```text
+/// s*P = H
+/// s*D = C
+/// ```
+///
+/// The first equation proves that `s` is the secret key of `P`, and the
+/// second that decrypting with it gives `C - s*D = 0`, i.e. `m = 0`. This is
+/// the equality of the discrete logs of `H` and `C` in the bases `P` and `D`.
 pub struct ZeroCheckProtocol;
 
 #[derive(SymWitness, Clone)]
@@ -38,7 +55,7 @@ impl SigmaProof for ZeroCheckProtocol {
 
         vec![
             secret_key * instance.pubkey.clone(), // = H
-            secret_key * instance.handle.clone(), // = rH = enc(0, r)
+            secret_key * instance.handle.clone(), // = r*H = C iff m = 0
         ]
     }
 }
@@ -46,6 +63,7 @@ impl SigmaProof for ZeroCheckProtocol {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{errors::SigmaProofError, sigmas::G};
     use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
     use curve25519_dalek::Scalar;
 
@@ -65,13 +83,13 @@ mod tests {
 
         // zero_commitment = 0 * G + r * H
         let zero_commitment = r * H.clone();
-        // Compute the decrypt handle D = s*P
+        // Compute the decrypt handle D = r*P
         let handle = r * &public_key;
 
         let instance = ZeroCheckInstance {
             pubkey: public_key,
             commitment: zero_commitment,
-            handle: handle,
+            handle,
         };
 
         // Generate and verify proof
@@ -119,4 +137,60 @@ mod tests {
         // Verification should fail
         assert!(ZeroCheckProtocol::verify(&instance, &proof).is_err());
     }
+
+    #[test]
+    fn test_zero_check_rejects_nonzero_encryption() {
+        let rng = &mut rand::rngs::OsRng;
+        let secret = Scalar::random(rng);
+        let witness = ZeroCheckWitness {
+            secret_key: SymScalar::Const(secret),
+        };
+        let public_key = secret.invert() * H.clone();
+        let r = Scalar::random(rng);
+
+        // an encryption of 1 under the prover's own key
+        let commitment = Scalar::ONE * G + r * H.clone();
+        let instance = ZeroCheckInstance::new(public_key.clone(), commitment, r * &public_key);
+        assert!(matches!(
+            ZeroCheckProtocol::prove_checked(&witness, &instance),
+            Err(SigmaProofError::InstanceConsistency)
+        ));
+        let proof = ZeroCheckProtocol::prove(&witness, &instance).unwrap();
+        assert!(matches!(
+            ZeroCheckProtocol::verify(&instance, &proof),
+            Err(SigmaProofError::EquationCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_zero_check_prove_checked() {
+        let rng = &mut rand::rngs::OsRng;
+        let secret = Scalar::random(rng);
+        let witness = ZeroCheckWitness {
+            secret_key: SymScalar::Const(secret),
+        };
+        let public_key = secret.invert() * H.clone();
+        let r = Scalar::random(rng);
+        let instance = ZeroCheckInstance::new(public_key.clone(), r * H.clone(), r * &public_key);
+        let proof = ZeroCheckProtocol::prove_checked(&witness, &instance).unwrap();
+        ZeroCheckProtocol::verify(&instance, &proof).unwrap();
+
+        // the handle does not match the commitment
+        let instance = ZeroCheckInstance::new(
+            public_key.clone(),
+            r * H.clone(),
+            Scalar::random(rng) * &public_key,
+        );
+        assert!(matches!(
+            ZeroCheckProtocol::prove_checked(&witness, &instance),
+            Err(SigmaProofError::InstanceConsistency)
+        ));
+
+        // plain prove still produces a proof, which does not verify
+        let proof = ZeroCheckProtocol::prove(&witness, &instance).unwrap();
+        assert!(!proof.is_empty());
+        assert!(ZeroCheckProtocol::verify(&instance, &proof).is_err());
+    }
+
+    conformance_tests!(ZeroCheckProtocol);
 }
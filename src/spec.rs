@@ -0,0 +1,93 @@
+//! Specifications of several protocols rendered as one document.
+
+use alloc::{format, string::String, vec::Vec};
+
+/// Generates the specification of a protocol, e.g.
+/// [`crate::compiler::SigmaProof::spec`].
+pub type SpecFn = fn() -> String;
+
+/// A list of named protocols whose [`crate::compiler::SigmaProof::spec`] are
+/// rendered together, see [`register_protocol!`](crate::register_protocol).
+#[derive(Default)]
+pub struct SpecRegistry {
+    protocols: Vec<(&'static str, SpecFn)>,
+}
+
+impl SpecRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a protocol, whose specification is generated by `spec`.
+    pub fn register(&mut self, name: &'static str, spec: SpecFn) -> &mut Self {
+        self.protocols.push((name, spec));
+        self
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.protocols.iter().map(|(name, _)| *name)
+    }
+
+    /// The specifications of all protocols in registration order, each under
+    /// a header with its name.
+    pub fn render_all(&self) -> String {
+        self.protocols
+            .iter()
+            .map(|(name, spec)| format!("## {name}\n\n{}\n", spec()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Builds a [`SpecRegistry`] of the given protocols, each named after its
+/// type, e.g. `register_protocol!(SchnorrIdentityProtocol, Chaum)`.
+#[macro_export]
+macro_rules! register_protocol {
+    ($($protocol:ty),* $(,)?) => {{
+        let mut registry = $crate::spec::SpecRegistry::new();
+        $(
+            registry.register(
+                stringify!($protocol),
+                <$protocol as $crate::compiler::SigmaProof>::spec,
+            );
+        )*
+        registry
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sigmas::{
+        chaum::Chaum, dleq::DleqProtocol, okamoto::Okamoto, schnorr::SchnorrIdentityProtocol,
+    };
+
+    #[test]
+    fn test_render_all() {
+        let registry = register_protocol!(SchnorrIdentityProtocol, Chaum, Okamoto, DleqProtocol);
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            [
+                "SchnorrIdentityProtocol",
+                "Chaum",
+                "Okamoto",
+                "DleqProtocol"
+            ]
+        );
+
+        let document = registry.render_all();
+        let labels = [
+            "schnorr-identity-protocol",
+            "chaum-protocol",
+            "okamoto-protocol",
+            "dleq-protocol",
+        ];
+        let mut position = 0;
+        for label in labels {
+            let found = document[position..]
+                .find(&format!("labeled as `{label}`"))
+                .unwrap_or_else(|| panic!("missing {label}"));
+            position += found;
+        }
+        assert!(document.starts_with("## SchnorrIdentityProtocol\n\n"));
+    }
+}
@@ -0,0 +1,543 @@
+//! A protocol's statement, and its export to a line-based DSL or to a Graphviz
+//! DOT constraint graph.
+//!
+//! The DSL declares the protocol, its witness, its instance and one `prove`
+//! line per equation `f_i(X) = psi_i(w)`:
+//!
+//! ```text
+//! protocol schnorr-identity-protocol;
+//! witness privatekey;
+//! instance pubkey:point;
+//! prove pubkey = privatekey * G;
+//! ```
+//!
+//! The grammar is:
+//!
+//! ```text
+//! program  := "protocol" label ";" NL
+//!             "witness" names ";" NL
+//!             "instance" fields ";" NL
+//!             ("prove" point "=" point ";" NL)*
+//! names    := (name ("," name)*)?
+//! fields   := (name ":" ("scalar" | "point") ("," name ":" ("scalar" | "point"))*)?
+//! point    := "O" | name | hex | scalar "*" point
+//!           | "(" point ("+" | "-") point ")" | "(-" point ")"
+//! scalar   := name | number | hex
+//!           | "(" scalar ("+" | "-" | "*" | "/") scalar ")" | "(-" scalar ")"
+//!           | "(" scalar "^" number ")"
+//! ```
+//!
+//! `O` is the identity, names refer to witness or instance fields or to
+//! well-known generators such as `G` and `H`, and other constants are written
+//! as `0x` followed by the hex of their canonical encoding.
+
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, RistrettoPoint, Scalar};
+use sha2::{Digest, Sha256};
+
+use crate::equations::{SymPoint, SymScalar};
+
+/// The equations of a protocol, evaluated symbolically on placeholder values
+/// that identify the witness and instance fields, see
+/// [`crate::compiler::SigmaProof::statement`].
+pub struct Statement {
+    pub label: &'static [u8],
+    /// Names of the witness scalars; the `i`-th one is the placeholder `i + 1`.
+    pub witness: Vec<Cow<'static, str>>,
+    pub instance_scalars: Vec<(Scalar, String)>,
+    pub instance_points: Vec<(RistrettoPoint, String)>,
+    pub f: Vec<SymPoint>,
+    pub psi: Vec<SymPoint>,
+}
+
+impl Statement {
+    /// Renders the statement in the DSL described in the [module
+    /// documentation](self).
+    pub fn to_dsl(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "protocol {};", String::from_utf8_lossy(self.label));
+        let _ = writeln!(out, "witness {};", self.witness.join(", "));
+
+        let fields: Vec<String> = self
+            .instance_scalars
+            .iter()
+            .map(|(_, name)| format!("{name}:scalar"))
+            .chain(
+                self.instance_points
+                    .iter()
+                    .map(|(_, name)| format!("{name}:point")),
+            )
+            .collect();
+        let _ = writeln!(out, "instance {};", fields.join(", "));
+
+        for (f, psi) in self.f.iter().zip(&self.psi) {
+            let _ = writeln!(
+                out,
+                "prove {} = {};",
+                self.point_to_dsl(f),
+                self.point_to_dsl(psi)
+            );
+        }
+        out
+    }
+
+    /// A fingerprint of the shape of the statement: its label, the number of
+    /// witness and instance values, and the expressions of `f` and `psi`.
+    ///
+    /// Field names are not hashed, so protocols that only differ in how they
+    /// name their fields have the same fingerprint.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"sigma-proof-compiler/statement/v1");
+        hasher.update((self.label.len() as u64).to_le_bytes());
+        hasher.update(self.label);
+        for len in [
+            self.witness.len(),
+            self.instance_scalars.len(),
+            self.instance_points.len(),
+            self.f.len(),
+            self.psi.len(),
+        ] {
+            hasher.update((len as u64).to_le_bytes());
+        }
+        for point in self.f.iter().chain(&self.psi) {
+            self.hash_point(&mut hasher, point);
+        }
+        hasher.finalize().into()
+    }
+
+    /// Hashes `point` in prefix order, with a tag per node and instance
+    /// values referred to by index.
+    fn hash_point(&self, hasher: &mut Sha256, point: &SymPoint) {
+        match point {
+            SymPoint::Identity => hasher.update([0]),
+            SymPoint::WellKnownConst(name, p) => {
+                hasher.update([1]);
+                hasher.update((name.len() as u64).to_le_bytes());
+                hasher.update(name.as_bytes());
+                hasher.update(p.compress().as_bytes());
+            }
+            SymPoint::Const(p) | SymPoint::Var(Some(p)) => {
+                match self.instance_points.iter().position(|(q, _)| q == p) {
+                    Some(i) => hash_index(hasher, 2, i),
+                    None => {
+                        hasher.update([3]);
+                        hasher.update(p.compress().as_bytes());
+                    }
+                }
+            }
+            SymPoint::Var(None) => hasher.update([4]),
+            SymPoint::Add(p1, p2) => {
+                hasher.update([5]);
+                self.hash_point(hasher, p1);
+                self.hash_point(hasher, p2);
+            }
+            SymPoint::Sub(p1, p2) => {
+                hasher.update([6]);
+                self.hash_point(hasher, p1);
+                self.hash_point(hasher, p2);
+            }
+            SymPoint::Neg(p) => {
+                hasher.update([7]);
+                self.hash_point(hasher, p);
+            }
+            SymPoint::Scale(s, p) => {
+                hasher.update([8]);
+                self.hash_scalar(hasher, s);
+                self.hash_point(hasher, p);
+            }
+        }
+    }
+
+    /// Like [`Statement::hash_point`], for scalars.
+    fn hash_scalar(&self, hasher: &mut Sha256, scalar: &SymScalar) {
+        match scalar {
+            SymScalar::Var(Some(s)) => {
+                match (0..self.witness.len()).find(|i| Scalar::from((i + 1) as u64) == *s) {
+                    Some(i) => hash_index(hasher, 16, i),
+                    None => hash_constant(hasher, s),
+                }
+            }
+            SymScalar::Const(s) => match self.instance_scalars.iter().position(|(t, _)| t == s) {
+                Some(i) => hash_index(hasher, 17, i),
+                None => hash_constant(hasher, s),
+            },
+            SymScalar::Var(None) => hasher.update([19]),
+            SymScalar::Add(s1, s2) => {
+                hasher.update([20]);
+                self.hash_scalar(hasher, s1);
+                self.hash_scalar(hasher, s2);
+            }
+            SymScalar::Sub(s1, s2) => {
+                hasher.update([21]);
+                self.hash_scalar(hasher, s1);
+                self.hash_scalar(hasher, s2);
+            }
+            SymScalar::Mul(s1, s2) => {
+                hasher.update([22]);
+                self.hash_scalar(hasher, s1);
+                self.hash_scalar(hasher, s2);
+            }
+            SymScalar::Neg(s) => {
+                hasher.update([23]);
+                self.hash_scalar(hasher, s);
+            }
+            SymScalar::Inv(s) => {
+                hasher.update([24]);
+                self.hash_scalar(hasher, s);
+            }
+            SymScalar::Pow(s, exp) => {
+                hasher.update([25]);
+                hasher.update(exp.to_le_bytes());
+                self.hash_scalar(hasher, s);
+            }
+        }
+    }
+
+    /// Renders the constraint graph of the statement in Graphviz DOT: one node
+    /// per witness scalar, instance field, generator and equation, with edges
+    /// into each equation from the values it references.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "digraph {} {{",
+            dot_string(&String::from_utf8_lossy(self.label))
+        );
+        for (i, name) in self.witness.iter().enumerate() {
+            let _ = writeln!(out, "  w{i} [label={}, shape=ellipse];", dot_string(name));
+        }
+        for (i, (_, name)) in self.instance_scalars.iter().enumerate() {
+            let _ = writeln!(out, "  s{i} [label={}, shape=box];", dot_string(name));
+        }
+        for (i, (_, name)) in self.instance_points.iter().enumerate() {
+            let _ = writeln!(out, "  p{i} [label={}, shape=box];", dot_string(name));
+        }
+
+        // bases of psi that are not instance points
+        let mut generators = Vec::new();
+        for psi in &self.psi {
+            psi.collect_generators_into(&mut generators);
+        }
+        generators.retain(|(_, g)| !self.instance_points.iter().any(|(p, _)| p == g));
+        for (i, (name, point)) in generators.iter().enumerate() {
+            let label = match name {
+                Some(name) => name.to_string(),
+                None => self.point_to_dsl(&SymPoint::Const(*point)),
+            };
+            let _ = writeln!(
+                out,
+                "  g{i} [label={}, shape=box, style=rounded];",
+                dot_string(&label)
+            );
+        }
+
+        for (i, (f, psi)) in self.f.iter().zip(&self.psi).enumerate() {
+            let equation = format!("{} = {}", self.point_to_dsl(f), self.point_to_dsl(psi));
+            let _ = writeln!(
+                out,
+                "  e{i} [label={}, shape=diamond];",
+                dot_string(&equation)
+            );
+
+            let mut inputs = Vec::new();
+            self.point_inputs(psi, &generators, &mut inputs);
+            self.point_inputs(f, &generators, &mut inputs);
+            for input in inputs {
+                let _ = writeln!(out, "  {input} -> e{i};");
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Adds the DOT nodes of the values referenced by `point` to `inputs`.
+    fn point_inputs(
+        &self,
+        point: &SymPoint,
+        generators: &[(Option<&'static str>, RistrettoPoint)],
+        inputs: &mut Vec<String>,
+    ) {
+        let node = match point {
+            SymPoint::Identity | SymPoint::Var(None) => return,
+            SymPoint::WellKnownConst(_, p) | SymPoint::Const(p) | SymPoint::Var(Some(p)) => {
+                if let Some(i) = self.instance_points.iter().position(|(q, _)| q == p) {
+                    format!("p{i}")
+                } else if let Some(i) = generators.iter().position(|(_, g)| g == p) {
+                    format!("g{i}")
+                } else {
+                    return;
+                }
+            }
+            SymPoint::Add(p1, p2) | SymPoint::Sub(p1, p2) => {
+                self.point_inputs(p1, generators, inputs);
+                self.point_inputs(p2, generators, inputs);
+                return;
+            }
+            SymPoint::Neg(p) => {
+                self.point_inputs(p, generators, inputs);
+                return;
+            }
+            SymPoint::Scale(s, p) => {
+                self.scalar_inputs(s, inputs);
+                self.point_inputs(p, generators, inputs);
+                return;
+            }
+        };
+        if !inputs.contains(&node) {
+            inputs.push(node);
+        }
+    }
+
+    /// Adds the DOT nodes of the witness and instance scalars referenced by
+    /// `scalar` to `inputs`.
+    fn scalar_inputs(&self, scalar: &SymScalar, inputs: &mut Vec<String>) {
+        let node = match scalar {
+            SymScalar::Var(Some(s)) => {
+                match (0..self.witness.len()).find(|i| Scalar::from((i + 1) as u64) == *s) {
+                    Some(i) => format!("w{i}"),
+                    None => return,
+                }
+            }
+            SymScalar::Const(s) => match self.instance_scalars.iter().position(|(t, _)| t == s) {
+                Some(i) => format!("s{i}"),
+                None => return,
+            },
+            SymScalar::Var(None) => return,
+            SymScalar::Add(s1, s2) | SymScalar::Sub(s1, s2) | SymScalar::Mul(s1, s2) => {
+                self.scalar_inputs(s1, inputs);
+                self.scalar_inputs(s2, inputs);
+                return;
+            }
+            SymScalar::Neg(s) | SymScalar::Inv(s) | SymScalar::Pow(s, _) => {
+                self.scalar_inputs(s, inputs);
+                return;
+            }
+        };
+        if !inputs.contains(&node) {
+            inputs.push(node);
+        }
+    }
+
+    fn point_to_dsl(&self, point: &SymPoint) -> String {
+        match point {
+            SymPoint::Identity => "O".to_string(),
+            SymPoint::WellKnownConst(name, _) => name.to_string(),
+            SymPoint::Const(p) | SymPoint::Var(Some(p)) => {
+                if let Some((_, name)) = self.instance_points.iter().find(|(q, _)| q == p) {
+                    name.to_string()
+                } else if *p == RISTRETTO_BASEPOINT_POINT {
+                    "G".to_string()
+                } else {
+                    hex(p.compress().as_bytes())
+                }
+            }
+            SymPoint::Var(None) => "?".to_string(),
+            SymPoint::Add(p1, p2) => {
+                format!("({} + {})", self.point_to_dsl(p1), self.point_to_dsl(p2))
+            }
+            SymPoint::Sub(p1, p2) => {
+                format!("({} - {})", self.point_to_dsl(p1), self.point_to_dsl(p2))
+            }
+            SymPoint::Neg(p) => format!("(-{})", self.point_to_dsl(p)),
+            SymPoint::Scale(s, p) => {
+                format!("{} * {}", self.scalar_to_dsl(s), self.point_to_dsl(p))
+            }
+        }
+    }
+
+    fn scalar_to_dsl(&self, scalar: &SymScalar) -> String {
+        match scalar {
+            SymScalar::Var(Some(s)) => self
+                .witness
+                .iter()
+                .enumerate()
+                .find(|(i, _)| Scalar::from((i + 1) as u64) == *s)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_else(|| constant_to_dsl(s)),
+            SymScalar::Const(s) => self
+                .instance_scalars
+                .iter()
+                .find(|(t, _)| t == s)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_else(|| constant_to_dsl(s)),
+            SymScalar::Var(None) => "?".to_string(),
+            SymScalar::Add(s1, s2) => {
+                format!("({} + {})", self.scalar_to_dsl(s1), self.scalar_to_dsl(s2))
+            }
+            SymScalar::Sub(s1, s2) => {
+                format!("({} - {})", self.scalar_to_dsl(s1), self.scalar_to_dsl(s2))
+            }
+            SymScalar::Neg(s) => format!("(-{})", self.scalar_to_dsl(s)),
+            SymScalar::Mul(s1, s2) => {
+                format!("({} * {})", self.scalar_to_dsl(s1), self.scalar_to_dsl(s2))
+            }
+            SymScalar::Inv(s) => format!("(1 / {})", self.scalar_to_dsl(s)),
+            SymScalar::Pow(s, exp) => format!("({} ^ {exp})", self.scalar_to_dsl(s)),
+        }
+    }
+}
+
+/// Writes small constants in decimal and others in hex.
+fn constant_to_dsl(scalar: &Scalar) -> String {
+    let bytes = scalar.as_bytes();
+    if bytes[8..].iter().all(|b| *b == 0) {
+        let mut small = [0u8; 8];
+        small.copy_from_slice(&bytes[..8]);
+        u64::from_le_bytes(small).to_string()
+    } else {
+        hex(bytes)
+    }
+}
+
+fn hash_index(hasher: &mut Sha256, tag: u8, index: usize) {
+    hasher.update([tag]);
+    hasher.update((index as u64).to_le_bytes());
+}
+
+fn hash_constant(hasher: &mut Sha256, scalar: &Scalar) {
+    hasher.update([18]);
+    hasher.update(scalar.as_bytes());
+}
+
+/// Quotes `s` as a DOT string.
+fn dot_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut out = String::from("0x");
+    for byte in bytes {
+        let _ = write!(out, "{byte:02x}");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        compiler::SigmaProof,
+        sigmas::{
+            chaum::Chaum,
+            okamoto::Okamoto,
+            schnorr::{ScaledSchnorr, SchnorrIdentityProtocol},
+            zero::ZeroCheckProtocol,
+        },
+    };
+
+    #[test]
+    fn test_schnorr_dsl() {
+        assert_eq!(
+            SchnorrIdentityProtocol::to_dsl().unwrap(),
+            "protocol schnorr-identity-protocol;\n\
+             witness privatekey;\n\
+             instance pubkey:point;\n\
+             prove pubkey = privatekey * G;\n"
+        );
+    }
+
+    #[test]
+    fn test_chaum_dsl() {
+        assert_eq!(
+            Chaum::to_dsl().unwrap(),
+            "protocol chaum-protocol;\n\
+             witness x;\n\
+             instance point1:point, point2:point;\n\
+             prove point1 = x * G;\n\
+             prove point2 = x * H;\n"
+        );
+    }
+
+    #[test]
+    fn test_okamoto_dsl() {
+        assert_eq!(
+            Okamoto::to_dsl().unwrap(),
+            "protocol okamoto-protocol;\n\
+             witness x, y;\n\
+             instance point:point;\n\
+             prove point = (x * G + y * H);\n"
+        );
+    }
+
+    #[test]
+    fn test_zero_check_dsl() {
+        assert_eq!(
+            ZeroCheckProtocol::to_dsl().unwrap(),
+            "protocol zero-check-protocol;\n\
+             witness secret_key;\n\
+             instance pubkey:point, commitment:point, handle:point;\n\
+             prove H = secret_key * pubkey;\n\
+             prove commitment = secret_key * handle;\n"
+        );
+    }
+
+    #[test]
+    fn test_scaled_schnorr_dsl() {
+        assert_eq!(
+            ScaledSchnorr::to_dsl().unwrap(),
+            "protocol scaled-schnorr-protocol;\n\
+             witness privatekey;\n\
+             instance multiplier:scalar, pubkey:point;\n\
+             prove pubkey = (multiplier * privatekey) * G;\n"
+        );
+    }
+
+    #[test]
+    fn test_chaum_dot() {
+        let dot = Chaum::spec_dot();
+        assert!(dot.starts_with("digraph \"chaum-protocol\" {\n"));
+        assert_eq!(dot.matches("shape=ellipse").count(), 1);
+        assert!(dot.contains("w0 [label=\"x\", shape=ellipse];"));
+        assert!(dot.contains("g0 [label=\"G\", shape=box, style=rounded];"));
+        assert!(dot.contains("g1 [label=\"H\", shape=box, style=rounded];"));
+        assert_eq!(dot.matches("style=rounded").count(), 2);
+        assert_eq!(dot.matches("shape=diamond").count(), 2);
+        assert!(dot.contains("e0 [label=\"point1 = x * G\", shape=diamond];"));
+
+        // each equation depends on the witness, its base and its instance point
+        for edge in [
+            "w0 -> e0", "g0 -> e0", "p0 -> e0", "w0 -> e1", "g1 -> e1", "p1 -> e1",
+        ] {
+            assert!(dot.contains(edge), "missing {edge}");
+        }
+        assert!(!dot.contains("g1 -> e0"));
+    }
+
+    #[test]
+    fn test_scaled_schnorr_dot() {
+        let dot = ScaledSchnorr::spec_dot();
+        assert!(dot.contains("s0 [label=\"multiplier\", shape=box];"));
+        assert!(dot.contains("s0 -> e0"));
+        assert!(dot.contains("w0 -> e0"));
+    }
+
+    #[test]
+    fn test_statement_hash() {
+        let schnorr = SchnorrIdentityProtocol::statement_hash().unwrap();
+        let hashes = [
+            schnorr,
+            Chaum::statement_hash().unwrap(),
+            Okamoto::statement_hash().unwrap(),
+            ScaledSchnorr::statement_hash().unwrap(),
+            ZeroCheckProtocol::statement_hash().unwrap(),
+        ];
+        for (i, a) in hashes.iter().enumerate() {
+            for b in &hashes[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+
+        // pinned, so that the hash is stable across runs and builds
+        assert_eq!(
+            super::hex(&schnorr),
+            "0xcdbb55a2beb9ad9fff92a4d87434bb09ea7ea6310cc702ba233c74ed633c345b"
+        );
+    }
+}
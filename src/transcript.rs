@@ -1,71 +1,332 @@
-use curve25519_dalek::{
-    ristretto::{CompressedRistretto, RistrettoPoint},
-    scalar::Scalar,
+use alloc::{borrow::Cow, vec::Vec};
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{
+    errors::{SigmaProofError, SigmaProofResult},
+    wire::{point_from_wire, point_to_wire, scalar_from_wire, scalar_to_wire, Endianness},
 };
-use std::io::{Read, Write};
 
-pub(crate) struct ProofTranscript {
+/// The version of the transcript layout, absorbed first by every
+/// [`ProofTranscript`] and carried by the framed proofs of
+/// [`crate::compiler::SigmaProof::prove_framed`].
+///
+/// It is bumped whenever proofs change, e.g. when a dependency hashes
+/// differently, so that old framed proofs are rejected with
+/// [`SigmaProofError::TranscriptVersionMismatch`] rather than failing their
+/// equations.
+pub const TRANSCRIPT_VERSION: u8 = 1;
+
+/// The duplex sponge a [`ProofTranscript`] absorbs messages into and squeezes
+/// challenges from.
+///
+/// Proofs are only compatible between provers and verifiers using the same
+/// backend.
+pub trait TranscriptBackend {
+    fn new(label: &'static [u8]) -> Self;
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]);
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]);
+}
+
+/// The default backend.
+impl TranscriptBackend for merlin::Transcript {
+    fn new(label: &'static [u8]) -> Self {
+        merlin::Transcript::new(label)
+    }
+
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        merlin::Transcript::append_message(self, label, message);
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        merlin::Transcript::challenge_bytes(self, label, dest);
+    }
+}
+
+/// A Merlin backend that can be checkpointed, see
+/// [`ProofTranscript::export_state`].
+///
+/// Merlin cannot serialize its sponge, so this backend keeps every operation
+/// applied to it, labels included, and frames them under fixed labels of its
+/// own so that they can be replayed from owned data. Its proofs differ from
+/// those of the default backend.
+#[derive(Clone)]
+pub struct CheckpointTranscript {
     state: merlin::Transcript,
-    proof: std::io::Cursor<Vec<u8>>,
+    label: Cow<'static, [u8]>,
+    log: Vec<CheckpointOperation>,
+}
+
+#[derive(Clone)]
+enum CheckpointOperation {
+    Absorb(Cow<'static, [u8]>, Vec<u8>),
+    Squeeze(Cow<'static, [u8]>, usize),
+}
+
+const ABSORB: u8 = 0;
+const SQUEEZE: u8 = 1;
+
+/// The longest squeeze a [`CheckpointTranscript`] replays, that of
+/// [`ProofTranscript::challenge`].
+const MAX_SQUEEZE_LEN: usize = 64;
+
+/// The shortest encoding of an operation: a tag, an empty label and a length.
+const MIN_OPERATION_LEN: usize = 1 + 8 + 8;
+
+impl CheckpointTranscript {
+    fn with_label(label: Cow<'static, [u8]>) -> Self {
+        let mut state = merlin::Transcript::new(b"sigma-proof-compiler/checkpoint");
+        state.append_message(b"label", &label);
+        Self {
+            state,
+            label,
+            log: Vec::new(),
+        }
+    }
+
+    fn absorb(&mut self, label: Cow<'static, [u8]>, message: &[u8]) {
+        self.state.append_message(b"label", &label);
+        self.state.append_message(b"message", message);
+        self.log
+            .push(CheckpointOperation::Absorb(label, message.to_vec()));
+    }
+
+    fn squeeze(&mut self, label: Cow<'static, [u8]>, dest: &mut [u8]) {
+        self.state.append_message(b"label", &label);
+        self.state.challenge_bytes(b"challenge", dest);
+        self.log
+            .push(CheckpointOperation::Squeeze(label, dest.len()));
+    }
+}
+
+impl TranscriptBackend for CheckpointTranscript {
+    fn new(label: &'static [u8]) -> Self {
+        Self::with_label(Cow::Borrowed(label))
+    }
+
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.absorb(Cow::Borrowed(label), message);
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        self.squeeze(Cow::Borrowed(label), dest);
+    }
+}
+
+#[derive(Clone)]
+pub struct ProofTranscript<B = merlin::Transcript> {
+    state: B,
+    proof: Vec<u8>,
+    position: usize,
     is_prover: bool,
 }
 
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Operation {
+    Absorb(&'static [u8], Vec<u8>),
+    Squeeze(&'static [u8], usize),
+}
+
 impl ProofTranscript {
-    pub(crate) fn new_prover(label: &'static [u8]) -> Self {
-        Self {
-            state: merlin::Transcript::new(label),
-            proof: std::io::Cursor::new(Vec::new()),
-            is_prover: true,
+    /// A deterministic RNG for the prover's nonces, keyed by everything
+    /// absorbed so far and by the `witness`.
+    pub(crate) fn nonce_rng(&self, witness: &[Scalar]) -> NonceRng {
+        let mut state = self.state.clone();
+        for scalar in witness {
+            state.append_message(b"witness", scalar.as_bytes());
         }
+        NonceRng(state)
     }
+}
 
-    pub(crate) fn new_verifier(label: &'static [u8], proof: &[u8]) -> Self {
-        Self {
-            state: merlin::Transcript::new(label),
-            proof: std::io::Cursor::new(proof.to_vec()),
-            is_prover: false,
+/// See [`ProofTranscript::nonce_rng`]. Its output is squeezed from a fork of
+/// the transcript, so it is pseudorandom as long as the witness is secret.
+pub(crate) struct NonceRng(merlin::Transcript);
+
+impl RngCore for NonceRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.challenge_bytes(b"nonce", dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for NonceRng {}
+
+impl ProofTranscript<CheckpointTranscript> {
+    /// Serializes the transcript so that a session can be checkpointed between
+    /// rounds and resumed with [`ProofTranscript::import_state`], possibly in
+    /// another process.
+    ///
+    /// The state contains everything absorbed so far, including the proof
+    /// bytes, so it must be kept as secret as the proof in progress.
+    pub fn export_state(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.push(self.is_prover as u8);
+        write_bytes(&mut out, &self.state.label);
+        write_bytes(&mut out, &self.proof);
+        out.extend_from_slice(&(self.position as u64).to_le_bytes());
+        out.extend_from_slice(&(self.state.log.len() as u64).to_le_bytes());
+        for operation in &self.state.log {
+            match operation {
+                CheckpointOperation::Absorb(label, message) => {
+                    out.push(ABSORB);
+                    write_bytes(&mut out, label);
+                    write_bytes(&mut out, message);
+                }
+                CheckpointOperation::Squeeze(label, len) => {
+                    out.push(SQUEEZE);
+                    write_bytes(&mut out, label);
+                    out.extend_from_slice(&(*len as u64).to_le_bytes());
+                }
+            }
+        }
+        out
+    }
+
+    /// Resumes a transcript from the output of
+    /// [`ProofTranscript::export_state`], by replaying every operation on a
+    /// fresh backend.
+    pub fn import_state(bytes: &[u8]) -> SigmaProofResult<Self> {
+        let mut reader = StateReader { bytes };
+        let is_prover = match reader.byte()? {
+            0 => false,
+            1 => true,
+            _ => return Err(SigmaProofError::InvalidTranscriptState),
+        };
+        let label = reader.bytes()?;
+        let proof = reader.bytes()?.to_vec();
+        let position = reader.len()?;
+        if position > proof.len() {
+            return Err(SigmaProofError::InvalidTranscriptState);
+        }
+
+        let mut state = CheckpointTranscript::with_label(Cow::Owned(label.to_vec()));
+        let num_operations = reader.len()?;
+        if num_operations > reader.bytes.len() / MIN_OPERATION_LEN {
+            return Err(SigmaProofError::InvalidTranscriptState);
+        }
+        for _ in 0..num_operations {
+            match reader.byte()? {
+                ABSORB => {
+                    let label = reader.bytes()?;
+                    let message = reader.bytes()?;
+                    state.absorb(Cow::Owned(label.to_vec()), message);
+                }
+                SQUEEZE => {
+                    let label = reader.bytes()?;
+                    let len = reader.len()?;
+                    if len > MAX_SQUEEZE_LEN {
+                        return Err(SigmaProofError::InvalidTranscriptState);
+                    }
+                    let mut dest = [0u8; MAX_SQUEEZE_LEN];
+                    state.squeeze(Cow::Owned(label.to_vec()), &mut dest[..len]);
+                }
+                _ => return Err(SigmaProofError::InvalidTranscriptState),
+            }
+        }
+        if !reader.bytes.is_empty() {
+            return Err(SigmaProofError::InvalidTranscriptState);
         }
+        Ok(Self {
+            state,
+            proof,
+            position,
+            is_prover,
+        })
+    }
+}
+
+impl<B: TranscriptBackend> ProofTranscript<B> {
+    /// A transcript for the prover, which writes the proof.
+    pub(crate) fn prover(label: &'static [u8]) -> Self {
+        Self::with_version(label, Vec::new(), true, TRANSCRIPT_VERSION)
+    }
+
+    /// A transcript for the verifier, which reads `proof`.
+    pub(crate) fn verifier(label: &'static [u8], proof: &[u8]) -> Self {
+        Self::with_version(label, proof.to_vec(), false, TRANSCRIPT_VERSION)
+    }
+
+    /// A transcript that absorbs `version` as its [`TRANSCRIPT_VERSION`].
+    fn with_version(label: &'static [u8], proof: Vec<u8>, is_prover: bool, version: u8) -> Self {
+        let mut transcript = Self {
+            state: B::new(label),
+            proof,
+            position: 0,
+            is_prover,
+        };
+        transcript.absorb(b"version", &[version]);
+        transcript
+    }
+
+    fn absorb(&mut self, label: &'static [u8], message: &[u8]) {
+        self.state.append_message(label, message);
+    }
+
+    fn squeeze(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        self.state.challenge_bytes(label, dest);
+    }
+
+    pub(crate) fn common_absorb_bytes(&mut self, label: &'static [u8], bytes: &[u8]) {
+        self.absorb(label, bytes);
     }
 
     pub(crate) fn common_absorb_scalar(&mut self, label: &'static [u8], scalar: &Scalar) {
-        self.state.append_message(label, scalar.as_bytes());
+        self.absorb(label, &scalar_to_wire(scalar, Endianness::Little));
     }
 
+    /// Absorbs the canonical compressed encoding of `point`.
     pub(crate) fn common_absorb_point(&mut self, label: &'static [u8], point: &RistrettoPoint) {
-        self.state
-            .append_message(label, point.compress().as_bytes());
+        self.absorb(label, &point_to_wire(point));
     }
 
     pub(crate) fn prover_absorb_scalar(&mut self, label: &'static [u8], scalar: &Scalar) {
         assert!(self.is_prover);
         self.common_absorb_scalar(label, scalar);
-        self.proof.write_all(scalar.as_bytes()).unwrap();
+        self.proof
+            .extend_from_slice(&scalar_to_wire(scalar, Endianness::Little));
     }
 
-    pub(crate) fn verifier_receives_all_scalars(
+    pub(crate) fn verifier_receive_scalars(
         &mut self,
         label: &'static [u8],
+        count: usize,
     ) -> Option<Vec<Scalar>> {
         assert!(!self.is_prover);
-        let mut scalars = Vec::new();
-        loop {
-            let mut buf = [0u8; 32];
-            match self.proof.read_exact(&mut buf) {
-                Ok(()) => {
-                    let scalar = Scalar::from_canonical_bytes(buf).into_option()?;
-                    self.common_absorb_scalar(label, &scalar);
-                    scalars.push(scalar);
-                }
-                Err(_) => break,
-            }
+        let mut scalars = Vec::with_capacity(count);
+        for _ in 0..count {
+            let buf = self.read_chunk()?;
+            let scalar = scalar_from_wire(buf, Endianness::Little)?;
+            self.common_absorb_scalar(label, &scalar);
+            scalars.push(scalar);
         }
         Some(scalars)
     }
 
+    /// Reads the next 32 bytes of the proof, if any.
+    fn read_chunk(&mut self) -> Option<[u8; 32]> {
+        let chunk = self.proof.get(self.position..self.position + 32)?;
+        self.position += 32;
+        chunk.try_into().ok()
+    }
+
     pub(crate) fn prover_absorb_point(&mut self, label: &'static [u8], point: &RistrettoPoint) {
         assert!(self.is_prover);
-        self.common_absorb_point(label, &point);
-        self.proof.write_all(point.compress().as_bytes()).unwrap();
+        self.common_absorb_point(label, point);
+        self.proof.extend_from_slice(&point_to_wire(point));
     }
 
     pub(crate) fn verifier_receive_points(
@@ -76,24 +337,343 @@ impl ProofTranscript {
         assert!(!self.is_prover);
         let mut points = Vec::with_capacity(count);
         for _ in 0..count {
-            let mut buf = [0u8; 32];
-            if self.proof.read_exact(&mut buf).is_err() {
-                return None;
-            }
-            let point = CompressedRistretto(buf).decompress()?;
-            self.common_absorb_point(label, &point); // TODO: we recompress here :/
+            let buf = self.read_chunk()?;
+            // decompression rejects non-canonical encodings, so `buf` is the
+            // encoding the prover absorbed and needs no recompression
+            let point = point_from_wire(buf)?;
+            self.absorb(label, &buf);
             points.push(point);
         }
         Some(points)
     }
 
-    pub(crate) fn challenge(&mut self, label: &'static [u8]) -> Scalar {
+    /// Absorbs the next `count` points of the proof as raw bytes, without
+    /// decoding them. The caller must decode them later and reject the proof
+    /// if any is not a canonical encoding.
+    pub(crate) fn verifier_absorb_point_bytes(
+        &mut self,
+        label: &'static [u8],
+        count: usize,
+    ) -> Option<()> {
+        assert!(!self.is_prover);
+        for _ in 0..count {
+            let buf = self.read_chunk()?;
+            self.absorb(label, &buf);
+        }
+        Some(())
+    }
+
+    /// Draws a challenge scalar from the transcript.
+    ///
+    /// 64 bytes are squeezed and reduced modulo the group order, so the bias
+    /// of the result is at most 2^-250 and no rejection sampling is needed.
+    pub fn challenge(&mut self, label: &'static [u8]) -> Scalar {
         let mut buf = [0u8; 64];
-        self.state.challenge_bytes(label, &mut buf);
+        self.squeeze(label, &mut buf);
         Scalar::from_bytes_mod_order_wide(&buf)
     }
 
+    /// Draws a challenge of at most `bits` bits from the transcript.
+    ///
+    /// `ceil(bits / 8)` bytes are squeezed, the bits above `bits` are cleared
+    /// and the result is zero-extended to a scalar. With 256 bits or more this
+    /// is [`ProofTranscript::challenge`].
+    ///
+    /// # Panics
+    ///
+    /// If `bits` is zero.
+    pub fn challenge_bits(&mut self, label: &'static [u8], bits: usize) -> Scalar {
+        assert!(bits > 0, "a challenge needs at least one bit");
+        if bits >= 256 {
+            return self.challenge(label);
+        }
+        let mut buf = [0u8; 32];
+        let len = bits.div_ceil(8);
+        self.squeeze(label, &mut buf[..len]);
+        buf[len - 1] &= mask_top_byte(bits);
+        Scalar::from_bytes_mod_order(buf)
+    }
+
+    /// Draws `n` independent challenge scalars under the same label.
+    ///
+    /// Every draw updates the transcript state, so consecutive challenges are
+    /// distinct even though they share a label.
+    pub fn challenge_scalars(&mut self, label: &'static [u8], n: usize) -> Vec<Scalar> {
+        (0..n).map(|_| self.challenge(label)).collect()
+    }
+
     pub(crate) fn finalize(self) -> Vec<u8> {
-        self.proof.into_inner()
+        self.proof
+    }
+}
+
+/// The mask keeping the low bits of the last byte of a `bits`-bit value.
+pub(crate) fn mask_top_byte(bits: usize) -> u8 {
+    match bits % 8 {
+        0 => 0xff,
+        rem => (1u8 << rem) - 1,
+    }
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// Parses the output of [`ProofTranscript::export_state`].
+struct StateReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> StateReader<'a> {
+    fn take(&mut self, n: usize) -> SigmaProofResult<&'a [u8]> {
+        if n > self.bytes.len() {
+            return Err(SigmaProofError::InvalidTranscriptState);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn byte(&mut self) -> SigmaProofResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn len(&mut self) -> SigmaProofResult<usize> {
+        let bytes = self.take(8)?.try_into().expect("8 bytes were taken");
+        usize::try_from(u64::from_le_bytes(bytes))
+            .map_err(|_| SigmaProofError::InvalidTranscriptState)
+    }
+
+    fn bytes(&mut self) -> SigmaProofResult<&'a [u8]> {
+        let len = self.len()?;
+        self.take(len)
+    }
+}
+
+/// A Merlin backend that records every operation applied to the transcripts
+/// created on the current thread, so that tests can assert what the prover
+/// and the verifier absorb, and in which order.
+///
+/// Its proofs are those of the default backend.
+#[cfg(test)]
+pub(crate) struct RecordingTranscript(merlin::Transcript);
+
+#[cfg(test)]
+std::thread_local! {
+    static RECORDED: core::cell::RefCell<Vec<Operation>> = const {
+        core::cell::RefCell::new(Vec::new())
+    };
+}
+
+#[cfg(test)]
+impl RecordingTranscript {
+    /// Returns the operations recorded on this thread since the last call.
+    pub(crate) fn take() -> Vec<Operation> {
+        RECORDED.with(|recorded| recorded.take())
+    }
+
+    fn record(operation: Operation) {
+        RECORDED.with(|recorded| recorded.borrow_mut().push(operation));
+    }
+}
+
+#[cfg(test)]
+impl TranscriptBackend for RecordingTranscript {
+    fn new(label: &'static [u8]) -> Self {
+        Self(merlin::Transcript::new(label))
+    }
+
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        Self::record(Operation::Absorb(label, message.to_vec()));
+        self.0.append_message(label, message);
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        Self::record(Operation::Squeeze(label, dest.len()));
+        self.0.challenge_bytes(label, dest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded_transcript() -> ProofTranscript {
+        let mut transcript = ProofTranscript::prover(b"test-transcript");
+        transcript.common_absorb_scalar(b"", &Scalar::from(42u64));
+        transcript
+    }
+
+    fn seeded_checkpoint() -> ProofTranscript<CheckpointTranscript> {
+        let mut transcript = ProofTranscript::prover(b"test-transcript");
+        transcript.common_absorb_scalar(b"", &Scalar::from(42u64));
+        transcript
+    }
+
+    #[test]
+    fn test_challenge_scalars_deterministic() {
+        let first = seeded_transcript().challenge_scalars(b"e", 3);
+        let second = seeded_transcript().challenge_scalars(b"e", 3);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_challenge_scalars_independent() {
+        let challenges = seeded_transcript().challenge_scalars(b"e", 3);
+        assert_eq!(challenges.len(), 3);
+        assert_ne!(challenges[0], challenges[1]);
+        assert_ne!(challenges[1], challenges[2]);
+        assert_ne!(challenges[0], challenges[2]);
+    }
+
+    #[test]
+    fn test_challenge_scalars_distinct_across_labels() {
+        let e = seeded_transcript().challenge_scalars(b"e", 2);
+        let c = seeded_transcript().challenge_scalars(b"c", 2);
+        assert_ne!(e, c);
+    }
+
+    #[test]
+    fn test_receive_points_rejects_non_canonical_encoding() {
+        // the field element p = 2^255 - 19, i.e. a non-reduced encoding of 0
+        let mut non_canonical = [0xff; 32];
+        non_canonical[0] = 0xed;
+        non_canonical[31] = 0x7f;
+
+        let mut transcript: ProofTranscript =
+            ProofTranscript::verifier(b"test-transcript", &non_canonical);
+        assert!(transcript.verifier_receive_points(b"r", 1).is_none());
+
+        let canonical = RistrettoPoint::default().compress().to_bytes();
+        let mut transcript: ProofTranscript =
+            ProofTranscript::verifier(b"test-transcript", &canonical);
+        assert_eq!(
+            transcript.verifier_receive_points(b"r", 1),
+            Some(vec![RistrettoPoint::default()])
+        );
+    }
+
+    #[test]
+    fn test_export_import_state_same_challenge() {
+        let rng = &mut rand::rngs::OsRng;
+        let mut transcript = seeded_checkpoint();
+        // round 1: the prover sends its commitments
+        transcript.prover_absorb_point(b"commitment", &RistrettoPoint::random(rng));
+        transcript.prover_absorb_point(b"commitment", &RistrettoPoint::random(rng));
+
+        let state = transcript.export_state();
+        let mut resumed = ProofTranscript::import_state(&state).unwrap();
+        assert_eq!(resumed.export_state(), state);
+        assert_eq!(resumed.challenge(b"e"), transcript.challenge(b"e"));
+
+        let response = Scalar::random(rng);
+        transcript.prover_absorb_scalar(b"response", &response);
+        resumed.prover_absorb_scalar(b"response", &response);
+        assert_eq!(resumed.finalize(), transcript.finalize());
+    }
+
+    #[test]
+    fn test_import_state_resumes_verifier() {
+        let point = RistrettoPoint::default().compress().to_bytes();
+        let proof = [point, point].concat();
+        let mut transcript =
+            ProofTranscript::<CheckpointTranscript>::verifier(b"test-transcript", &proof);
+        transcript.verifier_receive_points(b"r", 1).unwrap();
+
+        let state = transcript.export_state();
+        let mut resumed = ProofTranscript::import_state(&state).unwrap();
+        assert_eq!(
+            resumed.verifier_receive_points(b"r", 1),
+            transcript.verifier_receive_points(b"r", 1)
+        );
+        assert!(resumed.verifier_receive_points(b"r", 1).is_none());
+        assert_eq!(resumed.challenge(b"e"), transcript.challenge(b"e"));
+    }
+
+    #[test]
+    fn test_version_changes_challenges() {
+        let challenge = |version| {
+            let mut transcript = ProofTranscript::<merlin::Transcript>::with_version(
+                b"test-transcript",
+                Vec::new(),
+                true,
+                version,
+            );
+            transcript.common_absorb_scalar(b"", &Scalar::from(42u64));
+            transcript.challenge(b"e")
+        };
+        assert_eq!(
+            challenge(TRANSCRIPT_VERSION),
+            seeded_transcript().challenge(b"e")
+        );
+        assert_ne!(
+            challenge(TRANSCRIPT_VERSION + 1),
+            challenge(TRANSCRIPT_VERSION)
+        );
+    }
+
+    #[test]
+    fn test_import_state_rejects_malformed_state() {
+        let state = seeded_checkpoint().export_state();
+        for len in 0..state.len() {
+            assert!(matches!(
+                ProofTranscript::import_state(&state[..len]),
+                Err(SigmaProofError::InvalidTranscriptState)
+            ));
+        }
+
+        let mut trailing = state.clone();
+        trailing.push(0);
+        assert!(ProofTranscript::import_state(&trailing).is_err());
+
+        let mut bad_role = state;
+        bad_role[0] = 2;
+        assert!(ProofTranscript::import_state(&bad_role).is_err());
+    }
+
+    /// The state of [`seeded_checkpoint`] after squeezing a challenge, and the
+    /// offset of the encoded length of that squeeze.
+    fn squeezed_state() -> (Vec<u8>, usize) {
+        let mut transcript = seeded_checkpoint();
+        transcript.challenge(b"e");
+        let state = transcript.export_state();
+        (state.clone(), state.len() - 8)
+    }
+
+    #[test]
+    fn test_import_state_rejects_truncated_state() {
+        let (state, _) = squeezed_state();
+        assert!(ProofTranscript::import_state(&state).is_ok());
+
+        // a count of operations that the remaining bytes cannot hold
+        let mut truncated = state.clone();
+        let count = 1 + 2 * 8 + b"test-transcript".len() + 8;
+        truncated[count..count + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            ProofTranscript::import_state(&truncated),
+            Err(SigmaProofError::InvalidTranscriptState)
+        ));
+
+        assert!(matches!(
+            ProofTranscript::import_state(&state[..state.len() - 1]),
+            Err(SigmaProofError::InvalidTranscriptState)
+        ));
+    }
+
+    #[test]
+    fn test_import_state_rejects_oversized_squeeze() {
+        let (mut state, len) = squeezed_state();
+        state[len..].copy_from_slice(&(MAX_SQUEEZE_LEN as u64 + 1).to_le_bytes());
+        assert!(matches!(
+            ProofTranscript::import_state(&state),
+            Err(SigmaProofError::InvalidTranscriptState)
+        ));
+
+        state[len..].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            ProofTranscript::import_state(&state),
+            Err(SigmaProofError::InvalidTranscriptState)
+        ));
     }
 }
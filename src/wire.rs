@@ -0,0 +1,110 @@
+//! The byte encodings of scalars and points in proofs and instances.
+//!
+//! Proofs, instance encodings and transcripts use the canonical Ristretto
+//! encodings: a scalar is its 32-byte little-endian representation, reduced
+//! modulo the group order `l`, and a point is its 32-byte compressed Ristretto
+//! encoding. Implementations that store scalars big-endian can convert with
+//! [`Endianness::Big`], which only reverses the bytes.
+
+use curve25519_dalek::{ristretto::CompressedRistretto, RistrettoPoint, Scalar};
+
+/// The byte order of a serialized scalar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    /// The canonical Ristretto order, used by this crate's proofs.
+    #[default]
+    Little,
+    Big,
+}
+
+/// Encodes `scalar` in the given byte order.
+pub fn scalar_to_wire(scalar: &Scalar, endianness: Endianness) -> [u8; 32] {
+    let mut bytes = scalar.to_bytes();
+    if endianness == Endianness::Big {
+        bytes.reverse();
+    }
+    bytes
+}
+
+/// Decodes a scalar encoded by [`scalar_to_wire`] in the given byte order.
+///
+/// Returns `None` if the scalar is not reduced modulo `l`, so that each scalar
+/// has a single encoding.
+pub fn scalar_from_wire(mut bytes: [u8; 32], endianness: Endianness) -> Option<Scalar> {
+    if endianness == Endianness::Big {
+        bytes.reverse();
+    }
+    Scalar::from_canonical_bytes(bytes).into_option()
+}
+
+/// Encodes `point` in its compressed Ristretto encoding.
+pub fn point_to_wire(point: &RistrettoPoint) -> [u8; 32] {
+    point.compress().to_bytes()
+}
+
+/// Decodes a point encoded by [`point_to_wire`].
+///
+/// Returns `None` if the bytes are not a canonical Ristretto encoding.
+pub fn point_from_wire(bytes: [u8; 32]) -> Option<RistrettoPoint> {
+    CompressedRistretto(bytes).decompress()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    #[test]
+    fn test_known_answers() {
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert_eq!(scalar_to_wire(&Scalar::ONE, Endianness::Little), one);
+        one.reverse();
+        assert_eq!(scalar_to_wire(&Scalar::ONE, Endianness::Big), one);
+
+        let basepoint = [
+            0xe2, 0xf2, 0xae, 0x0a, 0x6a, 0xbc, 0x4e, 0x71, 0xa8, 0x84, 0xa9, 0x61, 0xc5, 0x00,
+            0x51, 0x5f, 0x58, 0xe3, 0x0b, 0x6a, 0xa5, 0x82, 0xdd, 0x8d, 0xb6, 0xa6, 0x59, 0x45,
+            0xe0, 0x8d, 0x2d, 0x76,
+        ];
+        assert_eq!(point_to_wire(&RISTRETTO_BASEPOINT_POINT), basepoint);
+        assert_eq!(point_from_wire(basepoint), Some(RISTRETTO_BASEPOINT_POINT));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let rng = &mut rand::rngs::OsRng;
+        let scalar = Scalar::random(rng);
+        for endianness in [Endianness::Little, Endianness::Big] {
+            let bytes = scalar_to_wire(&scalar, endianness);
+            assert_eq!(scalar_from_wire(bytes, endianness), Some(scalar));
+        }
+        assert_eq!(
+            scalar_to_wire(&scalar, Endianness::Little),
+            scalar_to_wire(&scalar, Endianness::default())
+        );
+
+        let point = RistrettoPoint::random(rng);
+        assert_eq!(point_from_wire(point_to_wire(&point)), Some(point));
+    }
+
+    #[test]
+    fn test_rejects_non_canonical() {
+        // l itself, which reduces to 0
+        let l = [
+            0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9,
+            0xde, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x10,
+        ];
+        assert!(scalar_from_wire(l, Endianness::Little).is_none());
+        let mut reversed = l;
+        reversed.reverse();
+        assert!(scalar_from_wire(reversed, Endianness::Big).is_none());
+
+        // the field element p = 2^255 - 19, i.e. a non-reduced encoding of 0
+        let mut non_canonical = [0xff; 32];
+        non_canonical[0] = 0xed;
+        non_canonical[31] = 0x7f;
+        assert!(point_from_wire(non_canonical).is_none());
+    }
+}
@@ -0,0 +1,221 @@
+//! Feeds random and corrupted proofs to every verifier, which must reject them
+//! with an error rather than panic.
+
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use rand::{rngs::OsRng, Rng, RngCore};
+use sigma_proof_compiler::{
+    absorb::{SymInstance, SymWitness, RUNTIME_SAMPLE_LEN},
+    combinators::{SameWitnessAnd, SigmaOr},
+    compiler::SigmaProof,
+    proof::{decode_instance, peek_label, Dispatcher, Proof},
+    sigmas::{
+        bit::BitProof,
+        chaum::Chaum,
+        commit_equality::EqualityOfCommitments,
+        commit_inequality::CommitInequality,
+        commitment_sum::CommitmentSum,
+        dleq::DleqProtocol,
+        elgamal::{ElGamalEncryption, ElGamalKnownMessage},
+        generalized_okamoto::GeneralizedOkamoto,
+        is_zero::IsZero,
+        known_value::CommitmentToKnownValue,
+        linear_combination::LinearCombinationProof,
+        linear_relation::LinearRelation,
+        okamoto::Okamoto,
+        one_of_many::OneOfMany,
+        range::RangeProof,
+        schnorr::{GenericSchnorr, ScaledSchnorr, SchnorrIdentityProtocol},
+        vector_commitment::VectorCommitment,
+        zero::ZeroCheckProtocol,
+    },
+    transcript::ProofTranscript,
+};
+
+const ROUNDS: usize = 64;
+
+/// The encoding of the field element `p = 2^255 - 19`, which is neither a
+/// canonical point nor a canonical scalar.
+const NON_CANONICAL: [u8; 32] = {
+    let mut bytes = [0xff; 32];
+    bytes[0] = 0xed;
+    bytes[31] = 0x7f;
+    bytes
+};
+
+fn random_bytes(rng: &mut OsRng, len: usize) -> Vec<u8> {
+    let mut bytes = vec![0; len];
+    rng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Random bytes of a random length, a multiple of 32 half of the time.
+fn random_proof(rng: &mut OsRng) -> Vec<u8> {
+    let len = if rng.gen() {
+        32 * rng.gen_range(0..12)
+    } else {
+        rng.gen_range(0..400)
+    };
+    random_bytes(rng, len)
+}
+
+/// Flips a random bit, overwrites a chunk with a non-canonical encoding,
+/// truncates or extends `proof`.
+fn corrupt(rng: &mut OsRng, mut proof: Vec<u8>) -> Vec<u8> {
+    match rng.gen_range(0..4) {
+        0 if !proof.is_empty() => {
+            let i = rng.gen_range(0..proof.len());
+            proof[i] ^= 1 << rng.gen_range(0..8);
+        }
+        1 if proof.len() >= 32 => {
+            let chunk = rng.gen_range(0..proof.len() / 32);
+            proof[32 * chunk..32 * (chunk + 1)].copy_from_slice(&NON_CANONICAL);
+        }
+        2 => proof.truncate(rng.gen_range(0..=proof.len())),
+        _ => {
+            let len = rng.gen_range(1..64);
+            proof.extend(random_bytes(rng, len));
+        }
+    }
+    proof
+}
+
+fn random_instance<P: SigmaProof>(rng: &mut OsRng) -> P::INSTANCE {
+    let num_scalars = P::INSTANCE::num_scalars().unwrap_or(RUNTIME_SAMPLE_LEN);
+    let num_points = P::INSTANCE::num_points().unwrap_or(RUNTIME_SAMPLE_LEN);
+    let scalars: Vec<Scalar> = (0..num_scalars).map(|_| Scalar::random(rng)).collect();
+    let points: Vec<RistrettoPoint> = (0..num_points)
+        .map(|_| RistrettoPoint::random(rng))
+        .collect();
+    P::INSTANCE::from_values(&scalars, &points).unwrap()
+}
+
+/// Runs every verifier of `P` on `proof`, ignoring the results.
+fn verify_all<P: SigmaProof>(instance: &P::INSTANCE, proof: &[u8]) {
+    let _ = P::verify(instance, proof);
+    let _ = P::verify_with_context(instance, proof, b"context");
+    let _ = P::verify_with_challenge(instance, proof);
+    let _ = P::verify_framed(instance, proof);
+    let _ = P::verify_compact(instance, proof);
+    let _ = P::verify_streaming(instance, proof);
+    let _ = P::recover_challenge(instance, proof);
+}
+
+fn fuzz<P: SigmaProof>() {
+    let rng = &mut OsRng;
+    for _ in 0..ROUNDS {
+        let instance = random_instance::<P>(rng);
+        verify_all::<P>(&instance, &random_proof(rng));
+
+        // proofs of the right shape, which get further into the verifiers
+        let scalars: Vec<Scalar> = (0..P::num_responses(&instance).unwrap())
+            .map(|_| Scalar::random(rng))
+            .collect();
+        let witness = P::WITNESS::from_values(&scalars).unwrap();
+        for proof in [
+            P::prove(&witness, &instance),
+            P::prove_framed(&witness, &instance),
+            P::prove_compact(&witness, &instance),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            verify_all::<P>(&instance, &corrupt(rng, proof));
+        }
+
+        let _ = decode_instance::<P::INSTANCE>(&random_proof(rng));
+    }
+}
+
+#[test]
+fn test_fuzz_verify() {
+    fuzz::<SchnorrIdentityProtocol>();
+    fuzz::<GenericSchnorr>();
+    fuzz::<ScaledSchnorr>();
+    fuzz::<Chaum>();
+    fuzz::<DleqProtocol>();
+    fuzz::<ElGamalEncryption>();
+    fuzz::<ElGamalKnownMessage>();
+    fuzz::<Okamoto>();
+    fuzz::<OneOfMany>();
+    fuzz::<GeneralizedOkamoto<3>>();
+    fuzz::<LinearRelation<2, 3>>();
+    fuzz::<ZeroCheckProtocol>();
+    fuzz::<IsZero>();
+    fuzz::<CommitmentToKnownValue>();
+    fuzz::<LinearCombinationProof>();
+    fuzz::<EqualityOfCommitments>();
+    fuzz::<CommitInequality>();
+    fuzz::<CommitmentSum>();
+    fuzz::<BitProof>();
+    fuzz::<RangeProof<8>>();
+    fuzz::<VectorCommitment<4>>();
+    fuzz::<SigmaOr<Chaum, Okamoto>>();
+    fuzz::<SameWitnessAnd<SchnorrIdentityProtocol, SchnorrIdentityProtocol>>();
+}
+
+#[test]
+fn test_fuzz_dispatcher() {
+    let rng = &mut OsRng;
+    let mut dispatcher = Dispatcher::new();
+    dispatcher
+        .register::<SchnorrIdentityProtocol>()
+        .register::<Chaum>();
+
+    for _ in 0..ROUNDS {
+        let (instance, proof) = (random_proof(rng), random_proof(rng));
+        let _ = dispatcher.verify(&instance, &proof);
+        let _ = peek_label(&proof);
+        let _ = Proof::from_self_describing(&proof);
+
+        // a known label followed by random bytes
+        let framed = Proof::new::<Chaum>(proof).to_self_describing();
+        let _ = dispatcher.verify(&instance, &corrupt(rng, framed));
+    }
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// A well-formed transcript state, in the format of
+/// [`ProofTranscript::export_state`], with random operations.
+fn random_state(rng: &mut OsRng) -> Vec<u8> {
+    let mut state = vec![rng.gen_range(0..2)];
+    write_bytes(&mut state, b"fuzz-transcript");
+    let proof = random_proof(rng);
+    write_bytes(&mut state, &proof);
+    let position = rng.gen_range(0..=proof.len()) as u64;
+    state.extend_from_slice(&position.to_le_bytes());
+
+    let num_operations = rng.gen_range(0..8u64);
+    state.extend_from_slice(&num_operations.to_le_bytes());
+    for _ in 0..num_operations {
+        let len = rng.gen_range(0..8);
+        let label = random_bytes(rng, len);
+        if rng.gen() {
+            state.push(0);
+            write_bytes(&mut state, &label);
+            write_bytes(&mut state, &random_proof(rng));
+        } else {
+            state.push(1);
+            write_bytes(&mut state, &label);
+            state.extend_from_slice(&rng.gen_range(0..=64u64).to_le_bytes());
+        }
+    }
+    state
+}
+
+#[test]
+fn test_fuzz_import_state() {
+    let rng = &mut OsRng;
+    for _ in 0..ROUNDS {
+        let _ = ProofTranscript::import_state(&random_proof(rng));
+
+        let state = random_state(rng);
+        let transcript = ProofTranscript::import_state(&state).unwrap();
+        assert_eq!(transcript.export_state(), state);
+
+        let _ = ProofTranscript::import_state(&corrupt(rng, state));
+    }
+}
@@ -0,0 +1,15 @@
+use sigma_proof_compiler::absorb::{SymInstance, SymPoint, SymScalar, SymWitness};
+
+#[derive(SymWitness, Clone)]
+enum EitherWitness {
+    Left(SymScalar),
+    Right(SymScalar),
+}
+
+#[derive(SymInstance, Clone)]
+enum EitherInstance {
+    Left(SymPoint),
+    Right(SymPoint),
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+// The derives expand to paths into `sigma_proof_compiler` itself, so this
+// re-exports its modules under the same paths.
+extern crate alloc;
+
+mod absorb {
+    pub use sigma_proof_compiler::absorb::*;
+}
+
+mod equations {
+    pub use sigma_proof_compiler::equations::*;
+}
+
+mod errors {
+    pub use sigma_proof_compiler::errors::*;
+}
+
+mod proof {
+    pub use sigma_proof_compiler::proof::*;
+}
+
+use absorb::{SymInstance, SymPoint};
+
+#[derive(SymInstance)]
+struct PointInstance {
+    point: SymPoint,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use sigma_proof_compiler::absorb::{SymInstance, SymWitness};
+
+#[derive(SymWitness)]
+union BytesWitness {
+    bytes: [u8; 32],
+}
+
+#[derive(SymInstance)]
+union BytesInstance {
+    bytes: [u8; 32],
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use sigma_proof_compiler::absorb::{SymInstance, SymPoint};
+
+#[derive(SymInstance, Clone)]
+#[sym(copy)]
+struct PubkeyInstance {
+    pubkey: SymPoint,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use sigma_proof_compiler::absorb::{SymScalar, SymWitness};
+
+#[derive(SymWitness, Clone)]
+struct SecretWitness {
+    #[sym(skip_absorb)]
+    secret: SymScalar,
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+use sigma_proof_compiler::absorb::{SymInstance, SymPoint, SymScalar, SymWitness};
+
+#[derive(SymWitness, Clone)]
+struct PairWitness {
+    x: SymScalar,
+    pair: (SymScalar, SymScalar),
+}
+
+#[derive(SymInstance, Clone)]
+struct PointsInstance(SymPoint, [SymPoint; 2]);
+
+// only fields marked `nested` are taken for nested instances
+#[derive(SymInstance, Clone)]
+struct CountInstance {
+    point: SymPoint,
+    count: u64,
+}
+
+#[derive(SymInstance, Clone)]
+struct NestedPointInstance {
+    #[sym(nested)]
+    point: SymPoint,
+}
+
+fn main() {}